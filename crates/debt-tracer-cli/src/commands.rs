@@ -0,0 +1,205 @@
+use crate::config::Config;
+use crate::ledger::{self, Balance};
+use crate::output;
+use anyhow::Context;
+use debt_tracer_client::types::{GetDebtJSONResponse, JsonData};
+use debt_tracer_client::{Client, LoginOutcome};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const TOKEN_NAME: &str = "debt-tracer-cli";
+const TOKEN_SCOPES: [&str; 2] = ["debts:read", "debts:write"];
+
+#[derive(Deserialize)]
+struct CreatedToken {
+    token: String,
+}
+
+pub struct NewDebt {
+    pub counterparty: String,
+    pub amount: f64,
+    pub currency: Option<String>,
+    pub description: String,
+    pub due_date: Option<String>,
+    pub i_owe: bool,
+}
+
+pub struct DebtFilter {
+    pub status: Option<String>,
+    pub currency: Option<String>,
+    pub counterparty: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Created {
+    debt_id: String,
+}
+
+#[derive(Serialize)]
+struct Paid {
+    debt_id: String,
+}
+
+fn client(config_path: &Path) -> Result<Client, anyhow::Error> {
+    let config = Config::load(config_path)?;
+    Ok(Client::new(config.server)?.with_token(config.token))
+}
+
+/// There is deliberately no `--password` flag: arguments are visible to other
+/// users in the process list.
+fn read_password() -> Result<String, anyhow::Error> {
+    match std::env::var("DEBT_TRACER_PASSWORD") {
+        Ok(password) => Ok(password),
+        Err(_) => rpassword::prompt_password("Password: ").context("Failed to read the password."),
+    }
+}
+
+pub async fn login(
+    config_path: &Path,
+    server: String,
+    username: String,
+) -> Result<(), anyhow::Error> {
+    let password = read_password()?;
+    let client = Client::new(server.clone())?;
+    if client.login(username, password).await? == LoginOutcome::TwoFactorRequired {
+        let code = rpassword::prompt_password("Two-factor code: ")
+            .context("Failed to read the two-factor code.")?;
+        client.login_two_factor(code.trim()).await?;
+    }
+
+    let response = client
+        .post("/tokens")
+        .json(&serde_json::json!({ "name": TOKEN_NAME, "scopes": TOKEN_SCOPES }))
+        .send()
+        .await?
+        .error_for_status()
+        .context("Failed to create an API token.")?;
+    let created: CreatedToken = response.json().await?;
+
+    let config = Config {
+        server,
+        token: created.token,
+    };
+    config.save(config_path)?;
+    eprintln!("Logged in. Credentials saved to {}", config_path.display());
+    Ok(())
+}
+
+pub async fn add_debt(
+    config_path: &Path,
+    json: bool,
+    new_debt: NewDebt,
+) -> Result<(), anyhow::Error> {
+    let client = client(config_path)?;
+    let user = client.get_user().await?;
+    let (creditor_id, debtor_id) = if new_debt.i_owe {
+        (new_debt.counterparty, user.user_id)
+    } else {
+        (user.user_id, new_debt.counterparty)
+    };
+    let body = JsonData {
+        debtor_id,
+        creditor_id,
+        amount: new_debt.amount,
+        currency: new_debt.currency.unwrap_or(user.default_currency),
+        description: new_debt.description,
+        due_date: new_debt.due_date,
+    };
+    let created = client.create_debt(&body).await?;
+    let created = [Created {
+        debt_id: created.debt_id,
+    }];
+    output::print(json, &created, &["Debt"], |c| vec![c.debt_id.clone()])
+}
+
+pub async fn list_debts(
+    config_path: &Path,
+    json: bool,
+    filter: DebtFilter,
+) -> Result<(), anyhow::Error> {
+    let client = client(config_path)?;
+    let user_id = client.get_user().await?.user_id;
+    let debts: Vec<GetDebtJSONResponse> = client
+        .get_debts()
+        .await?
+        .into_iter()
+        .filter(|debt| filter.status.as_ref().is_none_or(|s| &debt.status == s))
+        .filter(|debt| filter.currency.as_ref().is_none_or(|c| &debt.currency == c))
+        .filter(|debt| {
+            filter
+                .counterparty
+                .as_ref()
+                .is_none_or(|c| ledger::is_with(debt, &user_id, c))
+        })
+        .collect();
+    output::print(
+        json,
+        &debts,
+        &[
+            "ID",
+            "Creditor",
+            "Debtor",
+            "Amount",
+            "Status",
+            "Due",
+            "Description",
+        ],
+        |debt| {
+            vec![
+                debt.debt_id.clone(),
+                debt.creditor_name.clone(),
+                debt.debtor_name.clone(),
+                format!("{:.2} {}", debt.amount, debt.currency),
+                debt.status.clone(),
+                debt.due_date.clone().unwrap_or_default(),
+                debt.description.clone(),
+            ]
+        },
+    )
+}
+
+pub async fn balance(
+    config_path: &Path,
+    json: bool,
+    currency: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let client = client(config_path)?;
+    let user_id = client.get_user().await?.user_id;
+    let debts = client.get_debts().await?;
+    let balances: Vec<Balance> = ledger::balances(&debts, &user_id)
+        .into_iter()
+        .filter(|balance| currency.as_ref().is_none_or(|c| &balance.currency == c))
+        .collect();
+    output::print(json, &balances, &["Counterparty", "Balance"], |balance| {
+        vec![
+            balance.counterparty_name.clone(),
+            format!("{} {}", balance.amount, balance.currency),
+        ]
+    })
+}
+
+pub async fn settle(
+    config_path: &Path,
+    json: bool,
+    counterparty: String,
+    currency: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let client = client(config_path)?;
+    let user_id = client.get_user().await?.user_id;
+    let debts = client.get_debts().await?;
+    let owed: Vec<&GetDebtJSONResponse> = ledger::owed_by(&debts, &user_id, &counterparty)
+        .into_iter()
+        .filter(|debt| currency.as_ref().is_none_or(|c| &debt.currency == c))
+        .collect();
+    if owed.is_empty() {
+        anyhow::bail!("{} owes you nothing.", counterparty);
+    }
+    let mut paid = Vec::new();
+    for debt in owed {
+        client.mark_debt_paid(&debt.debt_id).await?;
+        paid.push(Paid {
+            debt_id: debt.debt_id.clone(),
+        });
+    }
+    output::print(json, &paid, &["Paid"], |p| vec![p.debt_id.clone()])
+}
@@ -0,0 +1,57 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where `login` leaves the server address and the API token it created. The
+/// password itself is never stored.
+#[derive(Serialize, Deserialize)]
+pub struct Config {
+    pub server: String,
+    pub token: String,
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("debt-tracer").join("config.toml"))
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read {}. Run `debt-tracer-cli login` first.",
+                path.display()
+            )
+        })?;
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let contents = toml::to_string(self).context("Failed to encode the config.")?;
+        write_private(path, contents.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The token grants access to the user's debts, so only the owner may read it.
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, contents)
+}
@@ -0,0 +1,162 @@
+use debt_tracer_client::types::GetDebtJSONResponse;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const PAID: &str = "paid";
+
+/// What a counterparty owes the user in one currency, negative when the user
+/// owes them.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Balance {
+    pub counterparty_id: String,
+    pub counterparty_name: String,
+    pub currency: String,
+    pub amount: Decimal,
+}
+
+/// The other party of a debt, as `(user_id, username)`.
+pub fn counterparty<'a>(debt: &'a GetDebtJSONResponse, user_id: &str) -> (&'a str, &'a str) {
+    if debt.creditor_id == user_id {
+        (&debt.debtor_id, &debt.debtor_name)
+    } else {
+        (&debt.creditor_id, &debt.creditor_name)
+    }
+}
+
+/// Positive when the debt is owed to the user.
+pub fn signed_amount(debt: &GetDebtJSONResponse, user_id: &str) -> Decimal {
+    let amount = Decimal::from_f64_retain(debt.amount)
+        .unwrap_or_default()
+        .round_dp(2);
+    if debt.creditor_id == user_id {
+        amount
+    } else {
+        -amount
+    }
+}
+
+/// Counterparties can be named by username or user ID.
+pub fn is_with(debt: &GetDebtJSONResponse, user_id: &str, reference: &str) -> bool {
+    let (id, name) = counterparty(debt, user_id);
+    id == reference || name == reference
+}
+
+/// Net balances of the debts that are not paid yet, leaving out settled pairs
+/// and debts a user recorded with themselves.
+pub fn balances(debts: &[GetDebtJSONResponse], user_id: &str) -> Vec<Balance> {
+    let mut totals: BTreeMap<(&str, &str, &str), Decimal> = BTreeMap::new();
+    for debt in debts {
+        if debt.status == PAID || debt.creditor_id == debt.debtor_id {
+            continue;
+        }
+        let (id, name) = counterparty(debt, user_id);
+        *totals.entry((name, id, &debt.currency)).or_default() += signed_amount(debt, user_id);
+    }
+    totals
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|((name, id, currency), amount)| Balance {
+            counterparty_id: id.to_string(),
+            counterparty_name: name.to_string(),
+            currency: currency.to_string(),
+            amount,
+        })
+        .collect()
+}
+
+/// The debts `reference` still owes the user, which the user can mark as paid.
+pub fn owed_by<'a>(
+    debts: &'a [GetDebtJSONResponse],
+    user_id: &str,
+    reference: &str,
+) -> Vec<&'a GetDebtJSONResponse> {
+    debts
+        .iter()
+        .filter(|debt| debt.status != PAID && debt.creditor_id == user_id)
+        .filter(|debt| debt.debtor_id != user_id && is_with(debt, user_id, reference))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const ME: &str = "me";
+
+    fn debt(
+        creditor: &str,
+        debtor: &str,
+        amount: f64,
+        currency: &str,
+        status: &str,
+    ) -> GetDebtJSONResponse {
+        GetDebtJSONResponse {
+            debt_id: format!("{}-{}-{}", creditor, debtor, amount),
+            creditor_id: creditor.to_string(),
+            creditor_name: format!("{}-name", creditor),
+            debtor_id: debtor.to_string(),
+            debtor_name: format!("{}-name", debtor),
+            amount,
+            currency: currency.to_string(),
+            description: String::new(),
+            status: status.to_string(),
+            due_date: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn balances_net_debts_in_both_directions_per_currency() {
+        let debts = vec![
+            debt(ME, "alice", 10.1, "EUR", "pending"),
+            debt("alice", ME, 0.2, "EUR", "unpaid"),
+            debt(ME, "alice", 5.0, "USD", "unpaid"),
+            debt("bob", ME, 7.5, "EUR", "pending"),
+        ];
+
+        let balances = balances(&debts, ME);
+
+        let amounts: Vec<_> = balances
+            .iter()
+            .map(|b| (b.counterparty_name.as_str(), b.currency.as_str(), b.amount))
+            .collect();
+        assert_eq!(
+            amounts,
+            vec![
+                ("alice-name", "EUR", dec!(9.9)),
+                ("alice-name", "USD", dec!(5)),
+                ("bob-name", "EUR", dec!(-7.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn paid_and_settled_debts_are_left_out() {
+        let debts = vec![
+            debt(ME, "alice", 10.0, "EUR", "paid"),
+            debt(ME, "bob", 3.0, "EUR", "pending"),
+            debt("bob", ME, 3.0, "EUR", "pending"),
+        ];
+
+        assert!(balances(&debts, ME).is_empty());
+    }
+
+    #[test]
+    fn only_unpaid_debts_the_counterparty_owes_can_be_settled() {
+        let debts = vec![
+            debt(ME, "alice", 12.25, "EUR", "pending"),
+            debt(ME, "alice", 3.0, "EUR", "paid"),
+            debt("alice", ME, 4.0, "EUR", "unpaid"),
+            debt(ME, "bob", 1.0, "EUR", "unpaid"),
+        ];
+
+        let owed: Vec<_> = owed_by(&debts, ME, "alice-name")
+            .iter()
+            .map(|debt| debt.debt_id.as_str())
+            .collect();
+
+        assert_eq!(owed, vec!["me-alice-12.25"]);
+    }
+}
@@ -0,0 +1,147 @@
+//! `debt-tracer-cli`, a command-line client for the debt-tracer API.
+//!
+//! `login` creates an API token and stores it in the config file; every other
+//! command authenticates with that token.
+mod commands;
+mod config;
+mod ledger;
+mod output;
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(
+    name = "debt-tracer-cli",
+    version,
+    about = "Track debts from the command line"
+)]
+struct Cli {
+    /// Print JSON instead of tables.
+    #[arg(long, global = true)]
+    json: bool,
+    /// The config file holding the server address and API token.
+    #[arg(long, global = true, env = "DEBT_TRACER_CONFIG")]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Log in and store an API token in the config file. The password is
+    /// read from `DEBT_TRACER_PASSWORD` or a prompt.
+    Login {
+        /// The base URL of the server, e.g. `https://debts.example.com`.
+        #[arg(long)]
+        server: String,
+        #[arg(long)]
+        username: String,
+    },
+    /// Record and list debts.
+    Debt {
+        #[command(subcommand)]
+        command: DebtCommand,
+    },
+    /// Show what each counterparty owes you, or you owe them.
+    Balance {
+        #[arg(long)]
+        currency: Option<String>,
+    },
+    /// Mark the debts a counterparty owes you as paid.
+    Settle {
+        /// A username or user ID.
+        counterparty: String,
+        #[arg(long)]
+        currency: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DebtCommand {
+    /// Record that a counterparty owes you `amount`.
+    Add {
+        /// An email, username or user ID.
+        counterparty: String,
+        amount: f64,
+        /// Defaults to your default currency.
+        #[arg(long)]
+        currency: Option<String>,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// `YYYY-MM-DD`.
+        #[arg(long)]
+        due_date: Option<String>,
+        /// Record that you owe the counterparty instead.
+        #[arg(long)]
+        i_owe: bool,
+    },
+    /// List your debts.
+    List {
+        /// `pending`, `paid` or `unpaid`.
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long)]
+        currency: Option<String>,
+        /// A username or user ID.
+        #[arg(long)]
+        counterparty: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let cli = Cli::parse();
+    let config_path = match cli.config {
+        Some(path) => path,
+        None => config::default_path()
+            .ok_or_else(|| anyhow::anyhow!("No config directory found. Pass --config <PATH>."))?,
+    };
+    let json = cli.json;
+    match cli.command {
+        Command::Login { server, username } => {
+            commands::login(&config_path, server, username).await
+        }
+        Command::Debt {
+            command:
+                DebtCommand::Add {
+                    counterparty,
+                    amount,
+                    currency,
+                    description,
+                    due_date,
+                    i_owe,
+                },
+        } => {
+            let new_debt = commands::NewDebt {
+                counterparty,
+                amount,
+                currency,
+                description,
+                due_date,
+                i_owe,
+            };
+            commands::add_debt(&config_path, json, new_debt).await
+        }
+        Command::Debt {
+            command:
+                DebtCommand::List {
+                    status,
+                    currency,
+                    counterparty,
+                },
+        } => {
+            let filter = commands::DebtFilter {
+                status,
+                currency,
+                counterparty,
+            };
+            commands::list_debts(&config_path, json, filter).await
+        }
+        Command::Balance { currency } => commands::balance(&config_path, json, currency).await,
+        Command::Settle {
+            counterparty,
+            currency,
+        } => commands::settle(&config_path, json, counterparty, currency).await,
+    }
+}
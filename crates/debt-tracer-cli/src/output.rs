@@ -0,0 +1,58 @@
+use serde::Serialize;
+
+/// Print `rows` as pretty JSON, or as a plain-text table with one column per
+/// header.
+pub fn print<T: Serialize>(
+    json: bool,
+    rows: &[T],
+    headers: &[&str],
+    cells: impl Fn(&T) -> Vec<String>,
+) -> Result<(), anyhow::Error> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(rows)?);
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = rows.iter().map(cells).collect();
+    print!("{}", table(headers, &rows));
+    Ok(())
+}
+
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+    let mut table = line(headers.to_vec());
+    for row in rows {
+        table.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_as_wide_as_their_widest_cell() {
+        let rows = vec![
+            vec!["alice".to_string(), "12.50".to_string()],
+            vec!["bob".to_string(), "-3".to_string()],
+        ];
+
+        assert_eq!(
+            table(&["Name", "Amount"], &rows),
+            "Name   Amount\nalice  12.50\nbob    -3\n"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! An async client for the debt-tracer HTTP API.
+//!
+//! Logging in stores the session cookie in the client, so later calls on the
+//! same `Client` are authenticated. Alternatively, `with_token` authenticates
+//! every request with an API token instead.
+pub use debt_tracer_types as types;
+pub use reqwest::{Method, RequestBuilder, Response, StatusCode};
+
+use debt_tracer_types::{
+    CreateDebtJSONResponse, GetDebtJSONResponse, GetUserJSONResponse, JsonData, JsonLoginData,
+    JsonTwoFactorData, SignUpJsonRequestBody,
+};
+use serde::de::DeserializeOwned;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClientError {
+    #[error("Failed to reach the server")]
+    Transport(#[from] reqwest::Error),
+    #[error("The server responded with {status}: {body}")]
+    Status { status: StatusCode, body: String },
+}
+
+impl ClientError {
+    /// The status of the response, if the server answered.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Transport(e) => e.status(),
+            ClientError::Status { status, .. } => Some(*status),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+    LoggedIn,
+    /// The password was right, but the session is only authenticated once
+    /// `login_two_factor` succeeds.
+    TwoFactorRequired,
+}
+
+#[derive(Clone)]
+pub struct Client {
+    base_url: String,
+    http: reqwest::Client,
+    token: Option<String>,
+}
+
+impl Client {
+    /// Redirects are not followed, so the OIDC login redirect can be inspected.
+    pub fn new(base_url: impl Into<String>) -> Result<Self, ClientError> {
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .cookie_store(true)
+            .build()?;
+        Ok(Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http,
+            token: None,
+        })
+    }
+
+    /// Send `token` as a bearer token with every request.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// A request to `path`, relative to the base URL, carrying the session
+    /// cookie or API token. Use it for endpoints without a typed method.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    pub fn get(&self, path: &str) -> RequestBuilder {
+        self.request(Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> RequestBuilder {
+        self.request(Method::POST, path)
+    }
+
+    pub fn patch(&self, path: &str) -> RequestBuilder {
+        self.request(Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> RequestBuilder {
+        self.request(Method::DELETE, path)
+    }
+
+    pub async fn login(
+        &self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Result<LoginOutcome, ClientError> {
+        let body = JsonLoginData {
+            username: username.into(),
+            password: password.into(),
+        };
+        let response = check(self.post("/login").json(&body).send().await?).await?;
+        match response.status() {
+            StatusCode::ACCEPTED => Ok(LoginOutcome::TwoFactorRequired),
+            _ => Ok(LoginOutcome::LoggedIn),
+        }
+    }
+
+    pub async fn login_two_factor(&self, code: impl Into<String>) -> Result<(), ClientError> {
+        let body = JsonTwoFactorData { code: code.into() };
+        check(self.post("/login/2fa").json(&body).send().await?).await?;
+        Ok(())
+    }
+
+    pub async fn sign_up(&self, body: &SignUpJsonRequestBody) -> Result<(), ClientError> {
+        check(self.post("/sign_up").json(body).send().await?).await?;
+        Ok(())
+    }
+
+    pub async fn create_debt(
+        &self,
+        body: &JsonData,
+    ) -> Result<CreateDebtJSONResponse, ClientError> {
+        json(self.post("/debt").json(body).send().await?).await
+    }
+
+    /// Only the creditor can mark a debt as paid.
+    pub async fn mark_debt_paid(&self, debt_id: &str) -> Result<(), ClientError> {
+        check(self.post(&format!("/debt/{}/paid", debt_id)).send().await?).await?;
+        Ok(())
+    }
+
+    pub async fn get_debts(&self) -> Result<Vec<GetDebtJSONResponse>, ClientError> {
+        json(self.get("/debts").send().await?).await
+    }
+
+    pub async fn get_user(&self) -> Result<GetUserJSONResponse, ClientError> {
+        json(self.get("/user").send().await?).await
+    }
+}
+
+async fn check(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(ClientError::Status { status, body })
+}
+
+async fn json<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    Ok(check(response).await?.json().await?)
+}
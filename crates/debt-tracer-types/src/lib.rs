@@ -0,0 +1,83 @@
+//! Request and response bodies of the debt-tracer HTTP API, shared by the
+//! server and its clients. Secrets such as passwords are plain strings here so
+//! clients can serialize them; the server wraps them in `Secret` on arrival.
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JsonData {
+    /// An email, username or user ID. Unknown emails are invited.
+    pub debtor_id: String,
+    /// An email, username or user ID. Unknown emails are invited.
+    pub creditor_id: String,
+    pub amount: f64,
+    pub currency: String,
+    pub description: String,
+    /// `YYYY-MM-DD`.
+    pub due_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateDebtJSONResponse {
+    pub debt_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GetDebtJSONResponse {
+    pub debt_id: String,
+    pub creditor_id: String,
+    pub creditor_name: String,
+    pub debtor_id: String,
+    pub debtor_name: String,
+    pub amount: f64,
+    pub currency: String,
+    pub description: String,
+    pub status: String,
+    pub due_date: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GetUserJSONResponse {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: String,
+    pub default_currency: String,
+    pub timezone: String,
+    pub accept_debts_from_strangers: bool,
+    pub reminder_frequency: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JsonLoginData {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JsonTwoFactorData {
+    /// A TOTP code or an unused recovery code.
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TwoFactorRequiredJSONResponse {
+    pub two_factor_required: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SignUpJsonRequestBody {
+    pub username: String,
+    pub password: String,
+    pub email: String,
+    pub invite_token: Option<String>,
+}
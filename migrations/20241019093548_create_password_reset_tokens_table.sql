@@ -0,0 +1,7 @@
+CREATE TABLE password_reset_tokens(
+    token_hash TEXT PRIMARY KEY,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    expires_at timestamptz NOT NULL,
+    used_at timestamptz
+);
@@ -0,0 +1,5 @@
+BEGIN;
+    ALTER TABLE users ADD COLUMN status TEXT;
+    UPDATE users SET status = 'active';
+    ALTER TABLE users ALTER COLUMN status SET NOT NULL;
+COMMIT;
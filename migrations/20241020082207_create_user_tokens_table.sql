@@ -0,0 +1,5 @@
+CREATE TABLE user_tokens(
+    token_hash TEXT PRIMARY KEY,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE totp_secrets(
+    user_id uuid PRIMARY KEY
+        REFERENCES users (user_id),
+    secret TEXT NOT NULL,
+    confirmed_at timestamptz,
+    last_used_step BIGINT
+);
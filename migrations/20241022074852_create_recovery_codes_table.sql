@@ -0,0 +1,7 @@
+CREATE TABLE recovery_codes(
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    code_hash TEXT NOT NULL,
+    used_at timestamptz,
+    PRIMARY KEY (user_id, code_hash)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE user_identities(
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    email TEXT,
+    created_at timestamptz NOT NULL,
+    PRIMARY KEY (issuer, subject)
+);
@@ -0,0 +1,2 @@
+-- Users signing in through an identity provider do not have a password.
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
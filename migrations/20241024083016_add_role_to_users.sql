@@ -0,0 +1,4 @@
+BEGIN;
+    ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user';
+    UPDATE users SET role = 'admin' WHERE user_id = 'be9cf379-8de1-4269-a0b2-2c3a8a1e9247';
+COMMIT;
@@ -0,0 +1,5 @@
+ALTER TABLE users
+    ADD COLUMN display_name TEXT,
+    ADD COLUMN default_currency TEXT NOT NULL DEFAULT 'USD',
+    ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC',
+    ADD COLUMN created_at timestamptz NOT NULL DEFAULT now();
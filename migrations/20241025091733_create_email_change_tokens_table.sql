@@ -0,0 +1,7 @@
+CREATE TABLE email_change_tokens(
+    token_hash TEXT PRIMARY KEY,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    new_email TEXT NOT NULL,
+    expires_at timestamptz NOT NULL
+);
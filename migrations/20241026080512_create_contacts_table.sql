@@ -0,0 +1,14 @@
+CREATE TABLE contacts(
+    requester_id uuid NOT NULL
+        REFERENCES users (user_id),
+    addressee_id uuid NOT NULL
+        REFERENCES users (user_id),
+    status TEXT NOT NULL,
+    created_at timestamptz NOT NULL,
+    accepted_at timestamptz,
+    PRIMARY KEY (requester_id, addressee_id),
+    CHECK (requester_id <> addressee_id)
+);
+-- A pair of users has a single relationship, whoever asked first.
+CREATE UNIQUE INDEX contacts_pair_idx
+    ON contacts (LEAST(requester_id, addressee_id), GREATEST(requester_id, addressee_id));
@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN accept_debts_from_strangers BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,6 @@
+CREATE TABLE invite_tokens(
+    token_hash TEXT PRIMARY KEY,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    created_at timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,13 @@
+CREATE TABLE notifications(
+    notification_id uuid PRIMARY KEY,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    kind TEXT NOT NULL,
+    debt_id uuid NOT NULL
+        REFERENCES debts (debt_id),
+    actor_id uuid NOT NULL
+        REFERENCES users (user_id),
+    created_at timestamptz NOT NULL,
+    read_at timestamptz
+);
+CREATE INDEX notifications_user_id_created_at_idx ON notifications (user_id, created_at DESC);
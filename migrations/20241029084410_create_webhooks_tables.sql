@@ -0,0 +1,30 @@
+CREATE TABLE webhooks(
+    webhook_id uuid PRIMARY KEY,
+    user_id uuid NOT NULL
+        REFERENCES users (user_id),
+    url TEXT NOT NULL,
+    -- Kept in plain text: it is needed to sign every delivery.
+    secret TEXT NOT NULL,
+    event_types TEXT[] NOT NULL,
+    created_at timestamptz NOT NULL
+);
+CREATE INDEX webhooks_user_id_idx ON webhooks (user_id);
+
+CREATE TABLE webhook_deliveries(
+    delivery_id uuid PRIMARY KEY,
+    webhook_id uuid NOT NULL
+        REFERENCES webhooks (webhook_id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at timestamptz NOT NULL,
+    last_attempt_at timestamptz,
+    response_status INT,
+    last_error TEXT,
+    created_at timestamptz NOT NULL,
+    delivered_at timestamptz
+);
+CREATE INDEX webhook_deliveries_pending_idx
+    ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX webhook_deliveries_webhook_id_idx ON webhook_deliveries (webhook_id, created_at DESC);
@@ -0,0 +1,6 @@
+ALTER TABLE users
+    ADD COLUMN reminder_frequency TEXT NOT NULL DEFAULT 'weekly',
+    ADD COLUMN last_reminder_sent_at timestamptz;
+ALTER TABLE debts
+    ADD COLUMN due_date DATE,
+    ADD COLUMN last_reminded_at timestamptz;
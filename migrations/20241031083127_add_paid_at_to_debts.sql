@@ -0,0 +1,2 @@
+ALTER TABLE debts ADD COLUMN paid_at timestamptz;
+UPDATE debts SET paid_at = created_at WHERE status = 'paid';
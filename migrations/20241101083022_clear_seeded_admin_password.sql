@@ -0,0 +1,8 @@
+-- The seeded admin shipped with a password hash committed to the repository.
+-- Clear it unless it has been changed since; operators set a password with
+-- `debt-tracer reset-password admin` or create their own admin with
+-- `debt-tracer create-user --role admin`.
+UPDATE users
+SET password_hash = NULL, session_version = session_version + 1
+WHERE user_id = 'be9cf379-8de1-4269-a0b2-2c3a8a1e9247'
+    AND password_hash = '$argon2id$v=19$m=15000,t=2,p=1$3W8ve90udH+IlzZCmSOMiA$nBc6ITLsTDHF1L8hKwI8lm1p3je1IG5G7NS+/yBxZKQ';
@@ -0,0 +1,3 @@
+ALTER TABLE invite_tokens ADD COLUMN expires_at timestamptz;
+UPDATE invite_tokens SET expires_at = created_at + interval '30 days';
+ALTER TABLE invite_tokens ALTER COLUMN expires_at SET NOT NULL;
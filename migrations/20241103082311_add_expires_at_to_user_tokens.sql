@@ -0,0 +1,3 @@
+ALTER TABLE user_tokens ADD COLUMN expires_at timestamptz;
+UPDATE user_tokens SET expires_at = now() + interval '1 day';
+ALTER TABLE user_tokens ALTER COLUMN expires_at SET NOT NULL;
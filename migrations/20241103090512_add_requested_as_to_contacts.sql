@@ -0,0 +1,3 @@
+-- What the requester entered to address a contact request, shown to them in
+-- place of the addressee's profile until the request is accepted.
+ALTER TABLE contacts ADD COLUMN requested_as TEXT;
@@ -0,0 +1,20 @@
+-- Accounts that existed before `created_at` was added were stamped with the
+-- time of that migration. Date them back to their first debt or API token.
+UPDATE users
+SET created_at = first_seen.created_at
+FROM (
+    SELECT user_id, MIN(created_at) AS created_at
+    FROM (
+        SELECT creditor_id AS user_id, created_at FROM debts
+        UNION ALL
+        SELECT debtor_id, created_at FROM debts
+        UNION ALL
+        SELECT user_id, created_at FROM api_tokens
+    ) AS activity
+    GROUP BY user_id
+) AS first_seen
+WHERE users.user_id = first_seen.user_id
+    AND first_seen.created_at < users.created_at
+    AND users.created_at <= (
+        SELECT installed_on FROM _sqlx_migrations WHERE version = 20241025091204
+    );
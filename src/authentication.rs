@@ -1,5 +1,23 @@
+mod api_tokens;
 mod middleware;
+mod oidc;
 mod password;
+mod sessions;
+mod throttle;
+mod tokens;
+mod two_factor;
 
-pub use middleware::{reject_anonymous_users, UserId, Username};
-pub use password::{validate_credentials, AuthError, Credentials, UserInfo};
+pub use api_tokens::{authenticate_api_token, AuthenticatedApiToken};
+pub use middleware::{reject_anonymous_users, reject_non_admin_users, UserId, Username};
+pub use oidc::{OidcClient, OidcIdentity, OidcLoginState};
+pub use password::{
+    change_password, compute_password_hash, validate_credentials, AuthError, Credentials, UserInfo,
+};
+pub use sessions::{get_session_version, get_user_role};
+pub use throttle::LoginThrottle;
+pub use tokens::{generate_token, hash_token};
+pub use two_factor::{
+    generate_recovery_codes, generate_totp_secret, get_totp, is_two_factor_enabled,
+    record_totp_step, replace_recovery_codes, totp_uri, validate_second_factor, verify_totp_code,
+    StoredTotp,
+};
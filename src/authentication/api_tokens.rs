@@ -0,0 +1,52 @@
+use super::hash_token;
+use crate::domain::{ApiTokenScope, UserRole, UserStatus};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct AuthenticatedApiToken {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: UserRole,
+    pub scopes: Vec<ApiTokenScope>,
+}
+
+#[tracing::instrument(name = "Authenticate API token", skip(token, pool))]
+pub async fn authenticate_api_token(
+    token: &str,
+    pool: &PgPool,
+) -> Result<Option<AuthenticatedApiToken>, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET last_used_at = now()
+        FROM users
+        WHERE api_tokens.user_id = users.user_id
+            AND api_tokens.token_hash = $1
+            AND api_tokens.revoked_at IS NULL
+            AND users.status = $2
+        RETURNING users.user_id, users.username, users.role, api_tokens.scopes
+        "#,
+        hash_token(token),
+        UserStatus::Active.to_string()
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to perform a query to authenticate an API token.")?;
+
+    row.map(|row| {
+        let scopes = row
+            .scopes
+            .iter()
+            .map(|scope| ApiTokenScope::parse(scope))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?;
+        Ok(AuthenticatedApiToken {
+            user_id: row.user_id,
+            username: row.username,
+            role: UserRole::parse(&row.role).map_err(anyhow::Error::msg)?,
+            scopes,
+        })
+    })
+    .transpose()
+}
@@ -1,10 +1,14 @@
+use super::{authenticate_api_token, get_session_version};
+use crate::domain::{ApiTokenScope, UserRole};
 use crate::session_state::TypedSession;
-use crate::utils::{e401, e500};
+use crate::utils::{e401, e403, e500};
 use actix_web::body::MessageBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::FromRequest;
+use actix_web::http::{header, Method};
 use actix_web::HttpMessage;
+use actix_web::{web, FromRequest};
 use actix_web_lab::middleware::Next;
+use sqlx::PgPool;
 use std::ops::Deref;
 use uuid::Uuid;
 
@@ -46,6 +50,31 @@ pub async fn reject_anonymous_users(
     mut req: ServiceRequest,
     next: Next<impl MessageBody>,
 ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let db_pool = req
+        .app_data::<web::Data<PgPool>>()
+        .cloned()
+        .ok_or_else(|| e500("The database pool is not configured"))?;
+
+    if let Some(token) = bearer_token(&req)? {
+        let required_scope = required_scope(req.method(), req.path()).ok_or_else(|| {
+            e403(anyhow::anyhow!(
+                "API tokens cannot be used to access this endpoint"
+            ))
+        })?;
+        let api_token = authenticate_api_token(&token, &db_pool)
+            .await
+            .map_err(e500)?
+            .ok_or_else(|| e401(anyhow::anyhow!("The API token is invalid")))?;
+        if !api_token.scopes.contains(&required_scope) {
+            let e = anyhow::anyhow!("The API token is missing the {} scope", required_scope);
+            return Err(e403(e));
+        }
+        req.extensions_mut().insert(UserId(api_token.user_id));
+        req.extensions_mut().insert(Username(api_token.username));
+        req.extensions_mut().insert(api_token.role);
+        return next.call(req).await;
+    }
+
     let session = {
         let (http_request, payload) = req.parts_mut();
         TypedSession::from_request(http_request, payload).await
@@ -53,6 +82,14 @@ pub async fn reject_anonymous_users(
 
     match session.get_user_id().map_err(e500)? {
         Some(user_id) => {
+            let current_version = get_session_version(user_id, &db_pool).await.map_err(e500)?;
+            if current_version.is_none()
+                || current_version != session.get_session_version().map_err(e500)?
+            {
+                session.log_out();
+                let e = anyhow::anyhow!("The session is no longer valid");
+                return Err(e401(e));
+            }
             req.extensions_mut().insert(UserId(user_id));
         }
         None => {
@@ -61,6 +98,16 @@ pub async fn reject_anonymous_users(
         }
     };
 
+    match session.get_role().map_err(e500)? {
+        Some(role) => {
+            req.extensions_mut().insert(role);
+        }
+        None => {
+            let e = anyhow::anyhow!("The user has not logged in");
+            Err(e401(e))?;
+        }
+    };
+
     match session.get_username().map_err(e500)? {
         Some(username) => {
             req.extensions_mut().insert(Username(username));
@@ -72,3 +119,63 @@ pub async fn reject_anonymous_users(
         }
     }
 }
+
+// Must be wrapped inside `reject_anonymous_users`, which puts the role in the
+// request extensions. Auditors get read-only access to the admin endpoints.
+pub async fn reject_non_admin_users(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let role = req
+        .extensions()
+        .get::<UserRole>()
+        .copied()
+        .ok_or_else(|| e500("The user role is missing from the request"))?;
+
+    let is_allowed = match role {
+        UserRole::Admin => true,
+        UserRole::Auditor => req.method() == Method::GET,
+        UserRole::User => false,
+    };
+    if !is_allowed {
+        let e = anyhow::anyhow!("The {} role cannot access this endpoint", role);
+        return Err(e403(e));
+    }
+    next.call(req).await
+}
+
+fn bearer_token(req: &ServiceRequest) -> Result<Option<String>, actix_web::Error> {
+    let header_value = match req.headers().get(header::AUTHORIZATION) {
+        Some(header_value) => header_value,
+        None => return Ok(None),
+    };
+    let token = header_value
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| {
+            e401(anyhow::anyhow!(
+                "The 'Authorization' header is not a bearer token"
+            ))
+        })?;
+    Ok(Some(token.to_string()))
+}
+
+// API tokens only grant access to the debt endpoints; everything else needs a session.
+fn required_scope(method: &Method, path: &str) -> Option<ApiTokenScope> {
+    let is_debt_endpoint = ["/debt", "/debts"]
+        .iter()
+        .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)));
+
+    if is_debt_endpoint {
+        if method == Method::GET {
+            Some(ApiTokenScope::DebtsRead)
+        } else {
+            Some(ApiTokenScope::DebtsWrite)
+        }
+    } else if path == "/user" && method == Method::GET {
+        Some(ApiTokenScope::DebtsRead)
+    } else {
+        None
+    }
+}
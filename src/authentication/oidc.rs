@@ -0,0 +1,248 @@
+use super::{generate_token, AuthError};
+use crate::configuration::OidcSettings;
+use anyhow::Context;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use reqwest::{Client, Url};
+use secrecy::ExposeSecret;
+use sha2::{Digest, Sha256};
+
+pub struct OidcClient {
+    http_client: Client,
+    settings: OidcSettings,
+}
+
+/// What has to survive the round trip through the identity provider.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct OidcLoginState {
+    pub state: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+}
+
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(serde::Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(serde::Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    preferred_username: Option<String>,
+}
+
+impl OidcClient {
+    pub fn new(settings: OidcSettings) -> Self {
+        let http_client = Client::builder()
+            .timeout(settings.timeout())
+            .build()
+            .unwrap();
+        Self {
+            http_client,
+            settings,
+        }
+    }
+
+    #[tracing::instrument(name = "Build OIDC authorization URL", skip(self))]
+    pub async fn authorization_url(&self) -> Result<(Url, OidcLoginState), anyhow::Error> {
+        let metadata = self.discover().await?;
+
+        let login_state = OidcLoginState {
+            state: generate_token(),
+            nonce: generate_token(),
+            pkce_verifier: generate_pkce_verifier(),
+        };
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &self.settings.client_id),
+                ("redirect_uri", &self.settings.redirect_url),
+                ("scope", "openid email profile"),
+                ("state", &login_state.state),
+                ("nonce", &login_state.nonce),
+                (
+                    "code_challenge",
+                    &pkce_challenge(&login_state.pkce_verifier),
+                ),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .context("The authorization endpoint is not a valid URL.")?;
+
+        Ok((url, login_state))
+    }
+
+    /// Exchange the authorization code and validate the ID token it is traded for.
+    #[tracing::instrument(name = "Authenticate with OIDC", skip(self, code, login_state))]
+    pub async fn authenticate(
+        &self,
+        code: &str,
+        login_state: &OidcLoginState,
+    ) -> Result<OidcIdentity, AuthError> {
+        let metadata = self.discover().await?;
+
+        let response = self
+            .http_client
+            .post(&metadata.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.settings.redirect_url),
+                ("client_id", &self.settings.client_id),
+                ("client_secret", self.settings.client_secret.expose_secret()),
+                ("code_verifier", &login_state.pkce_verifier),
+            ])
+            .send()
+            .await
+            .context("Failed to call the token endpoint.")?;
+        if !response.status().is_success() {
+            return Err(AuthError::InvalidCredentials(anyhow::anyhow!(
+                "The token endpoint rejected the authorization code with {}.",
+                response.status()
+            )));
+        }
+        let token_response: TokenResponse = response
+            .json()
+            .await
+            .context("Failed to parse the token endpoint response.")?;
+
+        let claims = self
+            .validate_id_token(&metadata, &token_response.id_token)
+            .await?;
+        if claims.nonce.as_deref() != Some(login_state.nonce.as_str()) {
+            return Err(AuthError::InvalidCredentials(anyhow::anyhow!(
+                "The ID token nonce does not match."
+            )));
+        }
+
+        Ok(OidcIdentity {
+            issuer: metadata.issuer,
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified.unwrap_or(false),
+            preferred_username: claims.preferred_username,
+        })
+    }
+
+    async fn validate_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, AuthError> {
+        let header = decode_header(id_token)
+            .context("The ID token is malformed.")
+            .map_err(AuthError::InvalidCredentials)?;
+        if header.alg != Algorithm::RS256 {
+            return Err(AuthError::InvalidCredentials(anyhow::anyhow!(
+                "The ID token is signed with an unsupported algorithm."
+            )));
+        }
+
+        let jwks: JwkSet = self
+            .http_client
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context("Failed to fetch the identity provider's JWKS.")?
+            .json()
+            .await
+            .context("Failed to parse the identity provider's JWKS.")?;
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(|| anyhow::anyhow!("The ID token is signed with an unknown key."))
+        .map_err(AuthError::InvalidCredentials)?;
+        let decoding_key = DecodingKey::from_jwk(jwk).context("Failed to parse a JWK.")?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.settings.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let token = decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+            .context("The ID token is invalid.")
+            .map_err(AuthError::InvalidCredentials)?;
+        Ok(token.claims)
+    }
+
+    async fn discover(&self) -> Result<ProviderMetadata, anyhow::Error> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.settings.issuer_url.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context("Failed to fetch the identity provider's metadata.")?
+            .json()
+            .await
+            .context("Failed to parse the identity provider's metadata.")?;
+        if metadata.issuer.trim_end_matches('/') != self.settings.issuer_url.trim_end_matches('/') {
+            anyhow::bail!("The identity provider's metadata belongs to another issuer.");
+        }
+        Ok(metadata)
+    }
+}
+
+fn generate_pkce_verifier() -> String {
+    let mut rng = thread_rng();
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(64)
+        .collect()
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pkce_challenge_matches_the_rfc_7636_example() {
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+        assert_eq!(
+            pkce_challenge(verifier),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn pkce_verifier_has_a_valid_length() {
+        let verifier = generate_pkce_verifier();
+
+        assert!((43..=128).contains(&verifier.len()));
+    }
+}
@@ -1,8 +1,11 @@
+use crate::domain::UserStatus;
 use crate::telemetry::spawn_blocking_with_tracing;
 use anyhow::Context;
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use argon2::password_hash::SaltString;
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
 use secrecy::{ExposeSecret, Secret};
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
 
 pub struct Credentials {
     pub username: String,
@@ -15,10 +18,20 @@ pub struct UserInfo {
     pub username: String,
 }
 
+struct StoredCredentials {
+    user_info: UserInfo,
+    password_hash: Option<Secret<String>>,
+    status: UserStatus,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum AuthError {
     #[error("Invalid credentials.")]
     InvalidCredentials(#[source] anyhow::Error),
+    #[error("The account has not been confirmed yet.")]
+    UnconfirmedAccount,
+    #[error("The account has been disabled.")]
+    DisabledAccount,
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -28,6 +41,7 @@ pub async fn validate_credentials(
     pool: &PgPool,
 ) -> Result<UserInfo, AuthError> {
     let mut user_info = None;
+    let mut user_status = None;
     let mut expected_password_hash = Secret::new(
         "$argon2id$v=19$m=15000,t=2,p=1$\
         gZiV/M1gPc22ElAH/Jh1Hw$\
@@ -35,10 +49,16 @@ pub async fn validate_credentials(
             .to_string(),
     );
 
-    if let Some((stored_user_info, stored_password_hash)) =
-        get_stored_credentials(&credentials.username, pool).await?
+    // Accounts without a password, e.g. created through an identity provider,
+    // are treated like unknown usernames.
+    if let Some(StoredCredentials {
+        user_info: stored_user_info,
+        password_hash: Some(stored_password_hash),
+        status: stored_user_status,
+    }) = get_stored_credentials(&credentials.username, pool).await?
     {
         user_info = Some(stored_user_info);
+        user_status = Some(stored_user_status);
         expected_password_hash = stored_password_hash;
     }
 
@@ -48,9 +68,16 @@ pub async fn validate_credentials(
     .await
     .context("Failed to spawn a blocking task.")??;
 
-    user_info
+    let user_info = user_info
         .ok_or_else(|| anyhow::anyhow!("Unknown username."))
-        .map_err(AuthError::InvalidCredentials)
+        .map_err(AuthError::InvalidCredentials)?;
+
+    // Only reveal the account status once the password has been verified.
+    match user_status {
+        Some(UserStatus::Active) => Ok(user_info),
+        Some(UserStatus::Disabled) => Err(AuthError::DisabledAccount),
+        _ => Err(AuthError::UnconfirmedAccount),
+    }
 }
 
 #[tracing::instrument(
@@ -78,10 +105,10 @@ fn verify_password_hash(
 async fn get_stored_credentials(
     username: &str,
     pool: &PgPool,
-) -> Result<Option<(UserInfo, Secret<String>)>, anyhow::Error> {
+) -> Result<Option<StoredCredentials>, anyhow::Error> {
     let row = sqlx::query!(
         r#"
-    SELECT user_id, username, password_hash
+    SELECT user_id, username, password_hash, status
     FROM users
     WHERE username = $1
     "#,
@@ -90,14 +117,58 @@ async fn get_stored_credentials(
     .fetch_optional(pool)
     .await
     .context("Failed to perform a query to retrieve stored credentials.")?
-    .map(|row| {
-        (
-            UserInfo {
+    .map(|row| -> Result<_, anyhow::Error> {
+        Ok(StoredCredentials {
+            user_info: UserInfo {
                 user_id: row.user_id,
                 username: row.username,
             },
-            Secret::new(row.password_hash),
-        )
-    });
+            password_hash: row.password_hash.map(Secret::new),
+            status: UserStatus::parse(&row.status).map_err(anyhow::Error::msg)?,
+        })
+    })
+    .transpose()?;
     Ok(row)
 }
+
+/// Ends every session and revokes every API token of the user, since either
+/// may be held by whoever knew the old password.
+#[tracing::instrument(name = "Change password", skip(password, executor))]
+pub async fn change_password(
+    user_id: Uuid,
+    password: Secret<String>,
+    executor: impl PgExecutor<'_>,
+) -> Result<(), anyhow::Error> {
+    let password_hash = spawn_blocking_with_tracing(move || compute_password_hash(password))
+        .await?
+        .context("Failed to hash password")?;
+    sqlx::query!(
+        r#"
+        WITH revoked AS (
+            UPDATE api_tokens SET revoked_at = now()
+            WHERE user_id = $2 AND revoked_at IS NULL
+        )
+        UPDATE users
+        SET password_hash = $1, session_version = session_version + 1
+        WHERE user_id = $2
+        "#,
+        password_hash.expose_secret(),
+        user_id
+    )
+    .execute(executor)
+    .await
+    .context("Failed to change user's password in the database.")?;
+    Ok(())
+}
+
+pub fn compute_password_hash(password: Secret<String>) -> Result<Secret<String>, anyhow::Error> {
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    let password_hash = Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        Params::new(15000, 2, 1, None).unwrap(),
+    )
+    .hash_password(password.expose_secret().as_bytes(), &salt)?
+    .to_string();
+    Ok(Secret::new(password_hash))
+}
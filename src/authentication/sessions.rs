@@ -0,0 +1,46 @@
+use crate::domain::UserRole;
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// Every session records the user's session version at login time. Bumping the
+// version in the database invalidates all sessions issued before the change.
+#[tracing::instrument(name = "Get session version", skip(pool))]
+pub async fn get_session_version(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Option<i32>, anyhow::Error> {
+    let session_version = sqlx::query!(
+        r#"
+        SELECT session_version
+        FROM users
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to perform a query to retrieve the session version.")?
+    .map(|row| row.session_version);
+    Ok(session_version)
+}
+
+#[tracing::instrument(name = "Get user role", skip(pool))]
+pub async fn get_user_role(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Option<UserRole>, anyhow::Error> {
+    sqlx::query!(
+        r#"
+        SELECT role
+        FROM users
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to perform a query to retrieve the user role.")?
+    .map(|row| UserRole::parse(&row.role).map_err(anyhow::Error::msg))
+    .transpose()
+}
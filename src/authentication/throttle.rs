@@ -0,0 +1,187 @@
+use crate::configuration::LoginThrottleSettings;
+use actix_web::HttpRequest;
+use anyhow::Context;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use secrecy::{ExposeSecret, Secret};
+
+// Failed logins are counted per username and, unless turned off, per client IP.
+// Once a counter goes past its threshold, every further failure doubles the
+// lockout, up to a cap.
+#[derive(Clone)]
+pub struct LoginThrottle {
+    connection: ConnectionManager,
+    settings: LoginThrottleSettings,
+}
+
+impl LoginThrottle {
+    pub async fn new(
+        redis_uri: &Secret<String>,
+        settings: LoginThrottleSettings,
+    ) -> Result<Self, anyhow::Error> {
+        let connection = redis::Client::open(redis_uri.expose_secret().as_str())
+            .context("Failed to parse the Redis URI.")?
+            .get_tokio_connection_manager()
+            .await
+            .context("Failed to connect to Redis.")?;
+        Ok(Self {
+            connection,
+            settings,
+        })
+    }
+
+    /// The IP failures are counted against, or `None` when there is no per-IP limit.
+    pub fn client_ip(&self, request: &HttpRequest) -> Option<String> {
+        self.settings.max_attempts_per_ip?;
+        if self.settings.trust_proxy_headers {
+            request
+                .connection_info()
+                .realip_remote_addr()
+                .map(str::to_string)
+        } else {
+            request.peer_addr().map(|addr| addr.ip().to_string())
+        }
+    }
+
+    /// Return how long the client has to wait before trying again, if it is locked out.
+    #[tracing::instrument(name = "Check login lockout", skip(self))]
+    pub async fn retry_after(
+        &self,
+        username: &str,
+        ip: Option<&str>,
+    ) -> Result<Option<u64>, anyhow::Error> {
+        let mut connection = self.connection.clone();
+        let mut retry_after = None;
+        for key in lockout_keys(username, ip) {
+            let ttl: i64 = connection
+                .ttl(&key)
+                .await
+                .context("Failed to read a login lockout from Redis.")?;
+            if ttl > 0 {
+                retry_after = retry_after.max(Some(ttl as u64));
+            }
+        }
+        Ok(retry_after)
+    }
+
+    #[tracing::instrument(name = "Record failed login attempt", skip(self))]
+    pub async fn record_failure(
+        &self,
+        username: &str,
+        ip: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        self.register_failure(
+            &username_key(username),
+            self.settings.max_attempts_per_username,
+        )
+        .await?;
+        if let (Some(ip), Some(max_attempts)) = (ip, self.settings.max_attempts_per_ip) {
+            self.register_failure(&ip_key(ip), max_attempts).await?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Reset failed login attempts", skip(self))]
+    pub async fn record_success(&self, username: &str) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection.clone();
+        let key = username_key(username);
+        connection
+            .del::<_, ()>(&[failures_key(&key), lockout_key(&key)])
+            .await
+            .context("Failed to reset the failed login attempts in Redis.")?;
+        Ok(())
+    }
+
+    async fn register_failure(&self, key: &str, max_attempts: u32) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection.clone();
+        let failures_key = failures_key(key);
+        let failures: u32 = connection
+            .incr(&failures_key, 1)
+            .await
+            .context("Failed to increment the failed login attempts in Redis.")?;
+        connection
+            .expire::<_, ()>(&failures_key, self.settings.failure_window_seconds as usize)
+            .await
+            .context("Failed to set the expiry of the failed login attempts in Redis.")?;
+
+        if let Some(lockout) = lockout_duration(
+            failures,
+            max_attempts,
+            self.settings.base_lockout_seconds,
+            self.settings.max_lockout_seconds,
+        ) {
+            tracing::warn!(
+                lockout_key = %key,
+                failed_attempts = failures,
+                lockout_seconds = lockout,
+                "Too many failed login attempts, locking out."
+            );
+            connection
+                .set_ex::<_, _, ()>(lockout_key(key), 1, lockout as usize)
+                .await
+                .context("Failed to store a login lockout in Redis.")?;
+        }
+        Ok(())
+    }
+}
+
+fn username_key(username: &str) -> String {
+    format!("username:{}", username)
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn failures_key(key: &str) -> String {
+    format!("login_failures:{}", key)
+}
+
+fn lockout_key(key: &str) -> String {
+    format!("login_lockout:{}", key)
+}
+
+fn lockout_keys(username: &str, ip: Option<&str>) -> Vec<String> {
+    let mut keys = vec![lockout_key(&username_key(username))];
+    if let Some(ip) = ip {
+        keys.push(lockout_key(&ip_key(ip)));
+    }
+    keys
+}
+
+fn lockout_duration(
+    failures: u32,
+    max_attempts: u32,
+    base_lockout_seconds: u64,
+    max_lockout_seconds: u64,
+) -> Option<u64> {
+    if failures < max_attempts {
+        return None;
+    }
+    let exponent = (failures - max_attempts).min(32);
+    let lockout = base_lockout_seconds.saturating_mul(1 << exponent);
+    Some(lockout.min(max_lockout_seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_lockout_below_the_threshold() {
+        assert_eq!(lockout_duration(4, 5, 30, 3600), None);
+    }
+
+    #[test]
+    fn lockout_doubles_for_every_failure_past_the_threshold() {
+        assert_eq!(lockout_duration(5, 5, 30, 3600), Some(30));
+        assert_eq!(lockout_duration(6, 5, 30, 3600), Some(60));
+        assert_eq!(lockout_duration(7, 5, 30, 3600), Some(120));
+    }
+
+    #[test]
+    fn lockout_is_capped() {
+        assert_eq!(lockout_duration(12, 5, 30, 3600), Some(3600));
+        assert_eq!(lockout_duration(u32::MAX, 5, 30, 3600), Some(3600));
+    }
+}
@@ -0,0 +1,216 @@
+use super::{generate_token, hash_token};
+use anyhow::Context;
+use rand::Rng;
+use secrecy::{ExposeSecret, Secret};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use std::time::{SystemTime, UNIX_EPOCH};
+use totp_rs::{Algorithm, TOTP};
+use uuid::Uuid;
+
+const TOTP_ISSUER: &str = "debt-tracer";
+const TOTP_STEP_SECONDS: u64 = 30;
+const RECOVERY_CODES_COUNT: usize = 10;
+
+pub struct StoredTotp {
+    pub secret: Secret<String>,
+    pub confirmed: bool,
+    pub last_used_step: Option<i64>,
+}
+
+pub fn generate_totp_secret() -> Secret<String> {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill(&mut bytes);
+    Secret::new(
+        totp_rs::Secret::Raw(bytes.to_vec())
+            .to_encoded()
+            .to_string(),
+    )
+}
+
+pub fn totp_uri(secret: &Secret<String>, username: &str) -> Result<String, anyhow::Error> {
+    Ok(build_totp(secret, username)?.get_url())
+}
+
+/// Return the time step matched by the code, refusing steps that were already used.
+pub fn verify_totp_code(
+    secret: &Secret<String>,
+    username: &str,
+    code: &str,
+    last_used_step: Option<i64>,
+) -> Result<Option<i64>, anyhow::Error> {
+    let totp = build_totp(secret, username)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("The system clock is set before the UNIX epoch.")?
+        .as_secs();
+    let current_step = (now / TOTP_STEP_SECONDS) as i64;
+
+    // Accept one step of clock drift in either direction.
+    let matched_step = (current_step - 1..=current_step + 1)
+        .filter(|step| last_used_step.is_none_or(|last_used_step| *step > last_used_step))
+        .find(|step| totp.check(code, *step as u64 * TOTP_STEP_SECONDS));
+    Ok(matched_step)
+}
+
+fn build_totp(secret: &Secret<String>, username: &str) -> Result<TOTP, anyhow::Error> {
+    let secret = totp_rs::Secret::Encoded(secret.expose_secret().clone())
+        .to_bytes()
+        .map_err(|e| anyhow::anyhow!("Failed to decode the TOTP secret: {:?}", e))?;
+    // Colons separate the issuer from the account name in otpauth URIs.
+    let account_name = username.replace(':', "");
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        TOTP_STEP_SECONDS,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account_name,
+    )
+    .context("Failed to build a TOTP generator.")
+}
+
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODES_COUNT)
+        .map(|_| generate_token().chars().take(10).collect::<String>())
+        .map(|code| code.to_lowercase())
+        .collect()
+}
+
+#[tracing::instrument(name = "Get stored TOTP secret", skip(executor))]
+pub async fn get_totp(
+    user_id: Uuid,
+    executor: impl PgExecutor<'_>,
+) -> Result<Option<StoredTotp>, anyhow::Error> {
+    let totp = sqlx::query!(
+        r#"
+        SELECT secret, confirmed_at, last_used_step
+        FROM totp_secrets
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to perform a query to retrieve the TOTP secret.")?
+    .map(|row| StoredTotp {
+        secret: Secret::new(row.secret),
+        confirmed: row.confirmed_at.is_some(),
+        last_used_step: row.last_used_step,
+    });
+    Ok(totp)
+}
+
+#[tracing::instrument(
+    name = "Check whether two-factor authentication is enabled",
+    skip(pool)
+)]
+pub async fn is_two_factor_enabled(user_id: Uuid, pool: &PgPool) -> Result<bool, anyhow::Error> {
+    Ok(get_totp(user_id, pool)
+        .await?
+        .is_some_and(|totp| totp.confirmed))
+}
+
+#[tracing::instrument(name = "Record used TOTP step", skip(executor))]
+pub async fn record_totp_step(
+    user_id: Uuid,
+    step: i64,
+    executor: impl PgExecutor<'_>,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE totp_secrets
+        SET last_used_step = $1
+        WHERE user_id = $2
+        "#,
+        step,
+        user_id
+    )
+    .execute(executor)
+    .await
+    .context("Failed to record the used TOTP step.")?;
+    Ok(())
+}
+
+/// Check a TOTP code, falling back to the user's unused recovery codes.
+#[tracing::instrument(name = "Validate second factor", skip(code, pool))]
+pub async fn validate_second_factor(
+    user_id: Uuid,
+    username: &str,
+    code: &str,
+    pool: &PgPool,
+) -> Result<bool, anyhow::Error> {
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let totp = match get_totp(user_id, &mut *transaction).await? {
+        Some(totp) if totp.confirmed => totp,
+        _ => return Ok(false),
+    };
+
+    let is_valid = match verify_totp_code(&totp.secret, username, code.trim(), totp.last_used_step)?
+    {
+        Some(step) => {
+            record_totp_step(user_id, step, &mut *transaction).await?;
+            true
+        }
+        None => consume_recovery_code(&mut transaction, user_id, code).await?,
+    };
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to validate a second factor.")?;
+    Ok(is_valid)
+}
+
+#[tracing::instrument(name = "Consume recovery code", skip(transaction, code))]
+async fn consume_recovery_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, anyhow::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE recovery_codes
+        SET used_at = now()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+        user_id,
+        hash_token(&code.trim().to_lowercase())
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to consume a recovery code.")?;
+    Ok(result.rows_affected() == 1)
+}
+
+#[tracing::instrument(name = "Replace recovery codes", skip(transaction, codes))]
+pub async fn replace_recovery_codes(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    codes: &[String],
+) -> Result<(), anyhow::Error> {
+    sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut **transaction)
+        .await
+        .context("Failed to delete the previous recovery codes.")?;
+    let code_hashes = codes
+        .iter()
+        .map(|code| hash_token(code))
+        .collect::<Vec<_>>();
+    sqlx::query!(
+        r#"
+        INSERT INTO recovery_codes (user_id, code_hash)
+        SELECT $1, * FROM UNNEST($2::TEXT[])
+        "#,
+        user_id,
+        &code_hashes
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert the recovery codes.")?;
+    Ok(())
+}
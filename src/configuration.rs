@@ -1,13 +1,24 @@
+use crate::authentication::OidcClient;
+use crate::email_client::EmailClient;
+use crate::webhook_delivery_worker::PublicAddressResolver;
 use secrecy::{ExposeSecret, Secret};
-use serde_aux::field_attributes::deserialize_number_from_string;
+use serde_aux::field_attributes::{
+    deserialize_number_from_string, deserialize_option_number_from_string,
+};
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
 use sqlx::ConnectOptions;
+use std::sync::Arc;
 
 #[derive(Clone, serde::Deserialize)]
 pub struct Settings {
     pub database: DatabaseSettings,
     pub application: ApplicationSettings,
     pub redis_uri: Secret<String>,
+    pub email_client: EmailClientSettings,
+    pub login_throttle: LoginThrottleSettings,
+    pub webhooks: WebhookSettings,
+    // Single sign-on is disabled unless an identity provider is configured.
+    pub oidc: Option<OidcSettings>,
 }
 
 #[derive(Clone, serde::Deserialize)]
@@ -16,6 +27,10 @@ pub struct ApplicationSettings {
     pub port: u16,
     pub host: String,
     pub base_url: String,
+    /// Where the web frontend is served. Emailed links that need a form, such
+    /// as invites and password resets, point at its pages rather than at the
+    /// API.
+    pub frontend_base_url: String,
     pub hmac_secret: Secret<String>,
 }
 
@@ -30,6 +45,101 @@ pub struct DatabaseSettings {
     pub require_ssl: bool,
 }
 
+#[derive(Clone, serde::Deserialize)]
+pub struct EmailClientSettings {
+    pub base_url: String,
+    pub sender_email: String,
+    pub authorization_token: Secret<String>,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub timeout_milliseconds: u64,
+}
+
+impl EmailClientSettings {
+    pub fn client(self) -> EmailClient {
+        let timeout = self.timeout();
+        EmailClient::new(
+            self.base_url,
+            self.sender_email,
+            self.authorization_token,
+            timeout,
+        )
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_milliseconds)
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct LoginThrottleSettings {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_attempts_per_username: u32,
+    /// Leave unset to only count failures per username.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    pub max_attempts_per_ip: Option<u32>,
+    /// Read the client IP from `Forwarded` or `X-Forwarded-For`. Only enable
+    /// this behind a reverse proxy that sets them: clients can send any value.
+    pub trust_proxy_headers: bool,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub base_lockout_seconds: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_lockout_seconds: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub failure_window_seconds: u64,
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct WebhookSettings {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub timeout_milliseconds: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_attempts: i32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub base_backoff_seconds: u64,
+    // Webhooks are registered by users, so by default they may not reach into
+    // the server's own network. Only enable this for local receivers.
+    #[serde(default)]
+    pub allow_private_targets: bool,
+}
+
+impl WebhookSettings {
+    pub fn client(&self) -> reqwest::Client {
+        let builder = reqwest::Client::builder()
+            .timeout(self.timeout())
+            .redirect(reqwest::redirect::Policy::none());
+        let builder = if self.allow_private_targets {
+            builder
+        } else {
+            builder.dns_resolver(Arc::new(PublicAddressResolver))
+        };
+        builder.build().unwrap()
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_milliseconds)
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct OidcSettings {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Secret<String>,
+    pub redirect_url: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub timeout_milliseconds: u64,
+}
+
+impl OidcSettings {
+    pub fn client(self) -> OidcClient {
+        OidcClient::new(self)
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_milliseconds)
+    }
+}
+
 impl DatabaseSettings {
     pub fn with_db(&self) -> PgConnectOptions {
         let options = self.without_db().database(&self.database_name);
@@ -1,71 +1,168 @@
 use crate::authentication::UserId;
-use crate::domain::{DebtAmount, DebtCurrency, DebtDescription, DebtStatus, DebtUserId, NewDebt};
-use crate::utils::error_chain_fmt;
+use crate::domain::{
+    ContactStatus, DebtAmount, DebtCurrency, DebtDescription, DebtDueDate, DebtStatus, DebtUserId,
+    NewDebt, NotificationKind, UserReference, UserStatus, WebhookEventType,
+};
+use crate::email_client::EmailClient;
+use crate::event_bus::{Event, EventBus};
+use crate::routes::contacts::are_contacts;
+use crate::routes::notifications::{notify_debt_parties, Notification};
+use crate::routes::signup::invite::{invite_user, send_invite_email, Invite};
+use crate::routes::webhooks::enqueue_webhook_deliveries;
+use crate::startup::FrontendBaseUrl;
+use crate::utils::{error_chain_fmt, escape_html};
 use actix_web::http::StatusCode;
 use actix_web::web;
 use actix_web::ResponseError;
 use anyhow::Context;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rust_decimal::prelude::*;
-use serde::Deserialize;
-use serde::Serialize;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 use crate::utils::e500;
 
-#[derive(serde::Deserialize)]
-pub struct JsonData {
-    debtor_id: String,
-    creditor_id: String,
-    amount: f64,
-    currency: String,
-    description: String,
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-pub struct CreateDebtJSONResponse {
-    debt_id: String,
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-pub struct GetDebtJSONResponse {
-    pub debt_id: String,
-    pub creditor_id: String,
-    pub creditor_name: String,
-    pub debtor_id: String,
-    pub debtor_name: String,
-    pub amount: f64,
-    pub currency: String,
-    pub description: String,
-    pub status: String,
-    pub created_at: String,
-}
-
-impl TryFrom<JsonData> for NewDebt {
-    type Error = String;
-
-    fn try_from(json_data: JsonData) -> Result<Self, Self::Error> {
-        let debtor_id = DebtUserId::parse(&json_data.debtor_id)?;
-        let creditor_id = DebtUserId::parse(&json_data.creditor_id)?;
-        let amount = DebtAmount::parse(json_data.amount)?;
-        let currency = DebtCurrency::parse(json_data.currency)?;
-        let description = DebtDescription::parse(json_data.description)?;
-
-        Ok(Self {
-            debtor_id,
-            creditor_id,
-            amount,
-            currency,
-            description,
-            status: DebtStatus::Pending,
-        })
+pub use debt_tracer_types::{CreateDebtJSONResponse, GetDebtJSONResponse, JsonData};
+
+/// Validate the payload, resolving the creditor and debtor to existing users.
+/// Unknown email addresses are invited as placeholder users.
+async fn into_new_debt(
+    json_data: JsonData,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(NewDebt, Vec<Invite>), CreateDebtError> {
+    let mut invites = Vec::new();
+    let debtor_id = resolve_user(&json_data.debtor_id, transaction, &mut invites).await?;
+    let creditor_id = resolve_user(&json_data.creditor_id, transaction, &mut invites).await?;
+    let amount = DebtAmount::parse(json_data.amount).map_err(CreateDebtError::ValidationError)?;
+    let currency =
+        DebtCurrency::parse(json_data.currency).map_err(CreateDebtError::ValidationError)?;
+    let description =
+        DebtDescription::parse(json_data.description).map_err(CreateDebtError::ValidationError)?;
+    let due_date = json_data
+        .due_date
+        .as_deref()
+        .map(DebtDueDate::parse)
+        .transpose()
+        .map_err(CreateDebtError::ValidationError)?;
+
+    let new_debt = NewDebt {
+        debtor_id,
+        creditor_id,
+        amount,
+        currency,
+        description,
+        due_date,
+        status: DebtStatus::Pending,
+    };
+    Ok((new_debt, invites))
+}
+
+pub(crate) async fn resolve_user(
+    reference: &str,
+    transaction: &mut Transaction<'_, Postgres>,
+    invites: &mut Vec<Invite>,
+) -> Result<DebtUserId, CreateDebtError> {
+    let reference = UserReference::parse(reference).map_err(CreateDebtError::ValidationError)?;
+    if let Some(user_id) = reference.resolve(&mut **transaction).await? {
+        return Ok(user_id);
+    }
+    match &reference {
+        UserReference::Email(email) => {
+            let (user_id, invite) = invite_user(transaction, email).await?;
+            invites.push(invite);
+            Ok(DebtUserId::from(user_id))
+        }
+        // Unknown users get the same answer as users who do not accept the
+        // debt, so that usernames and IDs cannot be probed.
+        _ => Err(CreateDebtError::NotAContact),
+    }
+}
+
+/// Users may only record debts they are a party to. This is checked before the
+/// other party is resolved or invited, so the answer says nothing about them.
+async fn ensure_caller_is_a_party(
+    json_data: &JsonData,
+    user_id: Uuid,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(), CreateDebtError> {
+    for reference in [&json_data.creditor_id, &json_data.debtor_id] {
+        let reference =
+            UserReference::parse(reference).map_err(CreateDebtError::ValidationError)?;
+        let party = reference.resolve(&mut **transaction).await?;
+        if party.is_some_and(|party| *party.as_ref() == user_id) {
+            return Ok(());
+        }
+    }
+    Err(CreateDebtError::NotAParty)
+}
+
+/// Only accepted contacts may record a debt against someone, unless that
+/// user has opted in to debts from strangers. Invited users cannot answer a
+/// contact request yet, so inviting them makes the inviter a contact.
+pub(crate) async fn ensure_counterparty_accepts(
+    user_id: Uuid,
+    counterparty: &DebtUserId,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(), CreateDebtError> {
+    let counterparty_id = *counterparty.as_ref();
+    if counterparty_id == user_id
+        || are_contacts(user_id, counterparty_id, &mut **transaction).await?
+    {
+        return Ok(());
+    }
+    let counterparty = sqlx::query!(
+        "SELECT status, accept_debts_from_strangers FROM users WHERE user_id = $1",
+        counterparty_id
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to check whether a user accepts debts from strangers.")?;
+    if counterparty.status == UserStatus::Invited.to_string() {
+        add_accepted_contact(user_id, counterparty_id, transaction).await?;
+        return Ok(());
+    }
+    if !counterparty.accept_debts_from_strangers {
+        return Err(CreateDebtError::NotAContact);
     }
+    Ok(())
 }
 
+async fn add_accepted_contact(
+    user_id: Uuid,
+    other_user_id: Uuid,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO contacts (requester_id, addressee_id, status, created_at, accepted_at)
+        VALUES ($1, $2, $3, now(), now())
+        ON CONFLICT DO NOTHING
+        "#,
+        user_id,
+        other_user_id,
+        ContactStatus::Accepted.to_string()
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to add the invited user as a contact.")?;
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/debt",
+    tag = "debts",
+    request_body = JsonData,
+    responses(
+        (status = 200, body = CreateDebtJSONResponse),
+        (status = 400, description = "Invalid debt"),
+        (status = 401, description = "Not logged in"),
+        (status = 403, description = "Not a party to the debt, or the counterparty does not accept it"),
+    ),
+)]
 #[tracing::instrument(
     name = "Creating a debt",
-    skip(body, db_pool),
+    skip(body, user_id, db_pool, email_client, frontend_base_url, event_bus),
     fields(
         creditor_id = %body.creditor_id,
         debtor_id =%body.debtor_id,
@@ -75,50 +172,166 @@ impl TryFrom<JsonData> for NewDebt {
 )]
 pub async fn create_debt(
     body: web::Json<JsonData>,
+    user_id: web::ReqData<UserId>,
     db_pool: web::Data<PgPool>,
+    email_client: web::Data<EmailClient>,
+    frontend_base_url: web::Data<FrontendBaseUrl>,
+    event_bus: web::Data<EventBus>,
 ) -> Result<web::Json<CreateDebtJSONResponse>, CreateDebtError> {
-    let new_debt: NewDebt = body
-        .0
-        .try_into()
-        .map_err(CreateDebtError::ValidationError)?;
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    ensure_caller_is_a_party(&body, **user_id, &mut transaction).await?;
+    let (new_debt, invites) = into_new_debt(body.into_inner(), &mut transaction).await?;
+    for counterparty in [&new_debt.creditor_id, &new_debt.debtor_id] {
+        ensure_counterparty_accepts(**user_id, counterparty, &mut transaction).await?;
+    }
 
     let debt_id = Uuid::new_v4();
 
+    insert_debt(&mut transaction, debt_id, &new_debt, Utc::now()).await?;
+
+    let notifications = notify_debt_parties(
+        &mut transaction,
+        NotificationKind::DebtCreated,
+        debt_id,
+        **user_id,
+    )
+    .await?;
+
+    let parties = [*new_debt.creditor_id.as_ref(), *new_debt.debtor_id.as_ref()];
+    let payload = serde_json::json!({
+        "event_type": WebhookEventType::DebtCreated.to_string(),
+        "created_at": Utc::now().to_rfc3339(),
+        "data": {
+            "debt_id": debt_id,
+            "creditor_id": parties[0],
+            "debtor_id": parties[1],
+            "amount": new_debt.amount.as_ref(),
+            "currency": new_debt.currency.to_string(),
+            "description": new_debt.description.as_ref(),
+            "status": new_debt.status.to_string(),
+            "due_date": new_debt.due_date.map(|due_date| due_date.as_ref().to_string()),
+        },
+    });
+    enqueue_webhook_deliveries(
+        &mut transaction,
+        WebhookEventType::DebtCreated,
+        &parties,
+        &payload,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new debt.")?;
+
+    publish_debt_created(&event_bus, &new_debt, debt_id, &notifications).await;
+
+    // The debt is stored either way; the invite can be resent by signing up.
+    for invite in &invites {
+        if let Err(e) = send_invite_email(&email_client, invite, &frontend_base_url.0).await {
+            tracing::warn!(error.cause_chain = ?e, "Failed to send an invite email.");
+        }
+    }
+
+    let res = CreateDebtJSONResponse {
+        debt_id: debt_id.to_string(),
+    };
+    Ok(web::Json(res))
+}
+
+/// Debts recorded as already paid are considered paid when they were created.
+pub(crate) async fn insert_debt(
+    transaction: &mut Transaction<'_, Postgres>,
+    debt_id: Uuid,
+    new_debt: &NewDebt,
+    created_at: DateTime<Utc>,
+) -> Result<(), anyhow::Error> {
     sqlx::query!(
         r#"
-        INSERT INTO debts (debt_id, creditor_id, debtor_id, amount, currency, description, status, created_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        INSERT INTO debts (debt_id, creditor_id, debtor_id, amount, currency, description, status, due_date, created_at, paid_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         "#,
-        debt_id.clone(),
+        debt_id,
         new_debt.creditor_id.as_ref(),
         new_debt.debtor_id.as_ref(),
         new_debt.amount.as_ref(),
         new_debt.currency.to_string(),
         new_debt.description.as_ref(),
         new_debt.status.to_string(),
-        Utc::now()
+        new_debt.due_date.map(|due_date| *due_date.as_ref()),
+        created_at,
+        matches!(new_debt.status, DebtStatus::Paid).then_some(created_at)
     )
-    .execute(db_pool.get_ref())
+    .execute(&mut **transaction)
     .await
     .context("Failed to insert new debt into the database.")?;
+    Ok(())
+}
 
-    let res = CreateDebtJSONResponse {
-        debt_id: debt_id.to_string(),
-    };
-    Ok(web::Json(res))
+/// The debt is already stored, so failing to push the update to connected
+/// clients is logged rather than reported.
+async fn publish_debt_created(
+    event_bus: &EventBus,
+    new_debt: &NewDebt,
+    debt_id: Uuid,
+    notifications: &[Notification],
+) {
+    let mut events = vec![];
+    for party in [new_debt.creditor_id.as_ref(), new_debt.debtor_id.as_ref()] {
+        if !events.iter().any(|(user_id, _)| user_id == party) {
+            events.push((*party, Event::DebtCreated { debt_id }));
+        }
+    }
+    for notification in notifications {
+        events.push((
+            notification.user_id,
+            Event::Notification {
+                notification_id: notification.notification_id,
+                debt_id,
+            },
+        ));
+    }
+    for (user_id, event) in events {
+        if let Err(e) = event_bus.publish(user_id, &event).await {
+            tracing::warn!(error.cause_chain = ?e, "Failed to publish an event.");
+        }
+    }
 }
 
+#[utoipa::path(
+    get,
+    path = "/debts",
+    tag = "debts",
+    responses(
+        (status = 200, body = Vec<GetDebtJSONResponse>),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
 #[tracing::instrument(name = "Getting list of debts by User ID", skip(db_pool))]
 pub async fn get_debts_by_user_id(
     user_id: web::ReqData<UserId>,
     db_pool: web::Data<PgPool>,
 ) -> Result<web::Json<Vec<GetDebtJSONResponse>>, actix_web::Error> {
-    let user_id = *user_id.into_inner();
-    let pool = db_pool.as_ref();
+    let debts = fetch_debts_by_user_id(*user_id.into_inner(), db_pool.as_ref())
+        .await
+        .context("Internal Server Error")
+        .map_err(e500)?;
+    Ok(web::Json(debts))
+}
 
+#[tracing::instrument(name = "Fetching debts by User ID", skip(pool))]
+pub async fn fetch_debts_by_user_id(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Vec<GetDebtJSONResponse>, anyhow::Error> {
     let result = sqlx::query!(
         "SELECT debt_id, users_1.user_id as creditor_id, users_1.username as creditor_name, \
-        users_2.user_id as debtor_id, users_2.username as debtor_name, amount, currency, description, status, created_at \
+        users_2.user_id as debtor_id, users_2.username as debtor_name, amount, currency, description, debts.status, debts.due_date, debts.created_at \
         FROM debts JOIN users users_1 ON debts.creditor_id =  users_1.user_id \
         JOIN users users_2 ON debts.debtor_id = users_2.user_id \
         WHERE creditor_id = $1 OR debtor_id = $1",
@@ -126,37 +339,36 @@ pub async fn get_debts_by_user_id(
     )
     .fetch_all(pool)
     .await
-    .context("Failed to fetch debts from the database.")
-    .context("Internal Server Error");
-
-    match result {
-        Ok(result) => {
-            let result = result
-                .into_iter()
-                .map(|row| GetDebtJSONResponse {
-                    debt_id: row.debt_id.to_string(),
-                    creditor_id: row.creditor_id.to_string(),
-                    creditor_name: row.creditor_name,
-                    debtor_id: row.debtor_id.to_string(),
-                    debtor_name: row.debtor_name,
-                    // temporarily unwrap this value
-                    amount: row.amount.to_f64().expect("Failed to convert big decimal"),
-                    description: row.description,
-                    currency: row.currency,
-                    status: row.status,
-                    created_at: row.created_at.to_string(),
-                })
-                .collect::<Vec<_>>();
-            Ok(web::Json(result))
-        }
-        Err(e) => Err(e500(e)),
-    }
+    .context("Failed to fetch debts from the database.")?;
+
+    let debts = result
+        .into_iter()
+        .map(|row| GetDebtJSONResponse {
+            debt_id: row.debt_id.to_string(),
+            creditor_id: row.creditor_id.to_string(),
+            creditor_name: row.creditor_name,
+            debtor_id: row.debtor_id.to_string(),
+            debtor_name: row.debtor_name,
+            // temporarily unwrap this value
+            amount: row.amount.to_f64().expect("Failed to convert big decimal"),
+            description: row.description,
+            currency: row.currency,
+            status: row.status,
+            due_date: row.due_date.map(|due_date| due_date.to_string()),
+            created_at: row.created_at.to_string(),
+        })
+        .collect::<Vec<_>>();
+    Ok(debts)
 }
 
 #[derive(thiserror::Error)]
 pub enum CreateDebtError {
     #[error("{0}")]
     ValidationError(String),
+    #[error("You can only record debts you are a party to.")]
+    NotAParty,
+    #[error("The counterparty does not accept debts from you.")]
+    NotAContact,
     #[error("Internal Server Error")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -171,7 +383,220 @@ impl ResponseError for CreateDebtError {
     fn status_code(&self) -> StatusCode {
         match self {
             CreateDebtError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            CreateDebtError::NotAParty => StatusCode::FORBIDDEN,
+            CreateDebtError::NotAContact => StatusCode::FORBIDDEN,
             CreateDebtError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
+
+#[derive(thiserror::Error)]
+pub enum RemindDebtorError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("The debt was not found.")]
+    DebtNotFound,
+    #[error("The debtor was already reminded of this debt today.")]
+    TooManyReminders,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RemindDebtorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RemindDebtorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RemindDebtorError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            RemindDebtorError::DebtNotFound => StatusCode::NOT_FOUND,
+            RemindDebtorError::TooManyReminders => StatusCode::TOO_MANY_REQUESTS,
+            RemindDebtorError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> actix_web::HttpResponse {
+        let mut response = actix_web::HttpResponse::build(self.status_code());
+        if let RemindDebtorError::TooManyReminders = self {
+            response.insert_header((actix_web::http::header::RETRY_AFTER, 24 * 60 * 60));
+        }
+        response.body(self.to_string())
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum MarkDebtPaidError {
+    #[error("The debt was not found.")]
+    DebtNotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for MarkDebtPaidError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for MarkDebtPaidError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MarkDebtPaidError::DebtNotFound => StatusCode::NOT_FOUND,
+            MarkDebtPaidError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Let the creditor nudge the debtor by email, at most once a day per debt.
+#[utoipa::path(
+    post,
+    path = "/debt/{debt_id}/remind",
+    tag = "debts",
+    params(("debt_id" = Uuid, Path, description = "Debt ID")),
+    responses(
+        (status = 200, description = "The debtor was reminded"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such debt owed to the user"),
+        (status = 429, description = "The debtor was already reminded today"),
+    ),
+)]
+#[tracing::instrument(name = "Reminding a debtor", skip(db_pool, email_client))]
+pub async fn remind_debtor(
+    debt_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<EmailClient>,
+) -> Result<actix_web::HttpResponse, RemindDebtorError> {
+    let debt_id = debt_id.into_inner();
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let debt = sqlx::query!(
+        r#"
+        SELECT debts.status, debts.amount, debts.currency, debts.description,
+            creditors.username AS creditor_name, debtors.email AS debtor_email,
+            debtors.status AS debtor_status
+        FROM debts
+        JOIN users creditors ON creditors.user_id = debts.creditor_id
+        JOIN users debtors ON debtors.user_id = debts.debtor_id
+        WHERE debts.debt_id = $1 AND debts.creditor_id = $2
+        FOR UPDATE OF debts
+        "#,
+        debt_id,
+        **user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch the debt.")?
+    .ok_or(RemindDebtorError::DebtNotFound)?;
+
+    if debt.status == DebtStatus::Paid.to_string() {
+        return Err(RemindDebtorError::ValidationError(
+            "The debt has already been paid.".into(),
+        ));
+    }
+    let debtor_status = UserStatus::parse(&debt.debtor_status).map_err(anyhow::Error::msg)?;
+    if !matches!(debtor_status, UserStatus::Active | UserStatus::Invited) {
+        return Err(RemindDebtorError::ValidationError(
+            "The debtor cannot receive reminders.".into(),
+        ));
+    }
+
+    let claimed = sqlx::query!(
+        r#"
+        UPDATE debts SET last_reminded_at = now()
+        WHERE debt_id = $1
+            AND (last_reminded_at IS NULL OR last_reminded_at <= now() - interval '1 day')
+        "#,
+        debt_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to record the reminder.")?
+    .rows_affected();
+    if claimed == 0 {
+        return Err(RemindDebtorError::TooManyReminders);
+    }
+
+    send_nudge_email(
+        &email_client,
+        &debt.debtor_email,
+        &debt.creditor_name,
+        &format!("{} {}", debt.amount, debt.currency),
+        &debt.description,
+    )
+    .await
+    .context("Failed to send the reminder email.")?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to record a reminder.")?;
+
+    Ok(actix_web::HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Send a debt reminder", skip(email_client, description))]
+async fn send_nudge_email(
+    email_client: &EmailClient,
+    recipient: &str,
+    creditor_name: &str,
+    amount: &str,
+    description: &str,
+) -> Result<(), reqwest::Error> {
+    let plain_body = format!(
+        "{} is reminding you that you owe them {} for {}.",
+        creditor_name, amount, description
+    );
+    let html_body = escape_html(&plain_body);
+    email_client
+        .send_email(
+            recipient,
+            "A reminder about a debt",
+            &html_body,
+            &plain_body,
+        )
+        .await
+}
+
+/// Only the creditor can confirm that a debt was paid. Marking a paid debt
+/// again keeps the original `paid_at`.
+#[utoipa::path(
+    post,
+    path = "/debt/{debt_id}/paid",
+    tag = "debts",
+    params(("debt_id" = Uuid, Path, description = "Debt ID")),
+    responses(
+        (status = 200, description = "The debt is marked as paid"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such debt owed to the user"),
+    ),
+)]
+#[tracing::instrument(name = "Marking a debt as paid", skip(db_pool))]
+pub async fn mark_debt_paid(
+    debt_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<actix_web::HttpResponse, MarkDebtPaidError> {
+    let updated = sqlx::query!(
+        r#"
+        UPDATE debts SET status = $1, paid_at = COALESCE(paid_at, now())
+        WHERE debt_id = $2 AND creditor_id = $3
+        "#,
+        DebtStatus::Paid.to_string(),
+        debt_id.into_inner(),
+        **user_id
+    )
+    .execute(db_pool.as_ref())
+    .await
+    .context("Failed to mark the debt as paid.")?
+    .rows_affected();
+    if updated == 0 {
+        return Err(MarkDebtPaidError::DebtNotFound);
+    }
+    Ok(actix_web::HttpResponse::Ok().finish())
+}
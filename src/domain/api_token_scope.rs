@@ -0,0 +1,46 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiTokenScope {
+    DebtsRead,
+    DebtsWrite,
+}
+
+const DEBTS_READ_STR: &str = "debts:read";
+const DEBTS_WRITE_STR: &str = "debts:write";
+
+impl std::fmt::Display for ApiTokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::ApiTokenScope::{DebtsRead, DebtsWrite};
+        match self {
+            DebtsRead => DEBTS_READ_STR.to_string().fmt(f),
+            DebtsWrite => DEBTS_WRITE_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl ApiTokenScope {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::ApiTokenScope::{DebtsRead, DebtsWrite};
+
+        match s {
+            DEBTS_READ_STR => Ok(DebtsRead),
+            DEBTS_WRITE_STR => Ok(DebtsWrite),
+            _ => Err(format!("{} is not a valid scope", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_scope_is_rejected() {
+        assert_err!(ApiTokenScope::parse("debts:delete"));
+    }
+
+    #[test]
+    fn debts_read_is_parsed_successfully() {
+        assert_ok!(ApiTokenScope::parse(DEBTS_READ_STR));
+    }
+}
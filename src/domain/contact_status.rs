@@ -0,0 +1,46 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactStatus {
+    Pending,
+    Accepted,
+}
+
+const PENDING_STR: &str = "pending";
+const ACCEPTED_STR: &str = "accepted";
+
+impl std::fmt::Display for ContactStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::ContactStatus::{Accepted, Pending};
+        match self {
+            Pending => PENDING_STR.to_string().fmt(f),
+            Accepted => ACCEPTED_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl ContactStatus {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::ContactStatus::{Accepted, Pending};
+
+        match s {
+            PENDING_STR => Ok(Pending),
+            ACCEPTED_STR => Ok(Accepted),
+            _ => Err(format!("{} is not a valid contact status", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_status_is_rejected() {
+        assert_err!(ContactStatus::parse("blocked"));
+    }
+
+    #[test]
+    fn accepted_is_parsed_successfully() {
+        assert_ok!(ContactStatus::parse(ACCEPTED_STR));
+    }
+}
@@ -0,0 +1,46 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug)]
+pub struct DisplayName(String);
+
+impl DisplayName {
+    pub fn parse(s: String) -> Result<Self, String> {
+        let s = s.trim().to_string();
+        let length = s.graphemes(true).count();
+
+        if length == 0 {
+            Err("The display name cannot be empty.".to_string())
+        } else if length > 64 {
+            Err("The display name must be at most 64 characters long.".to_string())
+        } else {
+            Ok(Self(s))
+        }
+    }
+}
+
+impl AsRef<str> for DisplayName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn a_whitespace_only_display_name_is_rejected() {
+        assert_err!(DisplayName::parse("   ".to_string()));
+    }
+
+    #[test]
+    fn a_display_name_longer_than_64_graphemes_is_rejected() {
+        assert_err!(DisplayName::parse("ё".repeat(65)));
+    }
+
+    #[test]
+    fn a_64_grapheme_long_display_name_is_valid() {
+        assert_ok!(DisplayName::parse("ё".repeat(64)));
+    }
+}
@@ -1,10 +1,37 @@
+mod api_token_scope;
+mod contact_status;
+mod display_name;
 mod new_debt;
+mod new_password;
 mod new_user;
+mod notification_kind;
+mod reminder_frequency;
+mod user_email;
+mod user_reference;
+mod user_role;
+mod user_status;
+mod user_timezone;
+mod webhook_delivery_status;
+mod webhook_event_type;
 
+pub use api_token_scope::ApiTokenScope;
+pub use contact_status::ContactStatus;
+pub use display_name::DisplayName;
 pub use new_debt::DebtAmount;
 pub use new_debt::DebtCurrency;
 pub use new_debt::DebtDescription;
+pub use new_debt::DebtDueDate;
 pub use new_debt::DebtStatus;
 pub use new_debt::DebtUserId;
 pub use new_debt::NewDebt;
+pub use new_password::NewPassword;
 pub use new_user::NewUser;
+pub use notification_kind::NotificationKind;
+pub use reminder_frequency::ReminderFrequency;
+pub use user_email::UserEmail;
+pub use user_reference::UserReference;
+pub use user_role::UserRole;
+pub use user_status::UserStatus;
+pub use user_timezone::UserTimezone;
+pub use webhook_delivery_status::WebhookDeliveryStatus;
+pub use webhook_event_type::WebhookEventType;
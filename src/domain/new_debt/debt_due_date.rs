@@ -0,0 +1,40 @@
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DebtDueDate(NaiveDate);
+
+impl AsRef<NaiveDate> for DebtDueDate {
+    fn as_ref(&self) -> &NaiveDate {
+        &self.0
+    }
+}
+
+impl DebtDueDate {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("{} is not a valid date, expected YYYY-MM-DD", s))?;
+
+        Ok(Self(date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn an_iso_date_is_valid() {
+        assert_ok!(DebtDueDate::parse("2024-10-30"));
+    }
+
+    #[test]
+    fn other_date_formats_are_rejected() {
+        assert_err!(DebtDueDate::parse("30/10/2024"));
+    }
+
+    #[test]
+    fn impossible_dates_are_rejected() {
+        assert_err!(DebtDueDate::parse("2024-02-30"));
+    }
+}
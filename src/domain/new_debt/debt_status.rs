@@ -19,3 +19,32 @@ impl std::fmt::Display for DebtStatus {
         }
     }
 }
+
+impl DebtStatus {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::DebtStatus::{Paid, Pending, Unpaid};
+
+        match s {
+            PENDING_STR => Ok(Pending),
+            PAID_STR => Ok(Paid),
+            UNPAID_STR => Ok(Unpaid),
+            _ => Err(format!("{} is not a valid debt status", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_status_is_rejected() {
+        assert_err!(DebtStatus::parse("forgiven"));
+    }
+
+    #[test]
+    fn paid_is_parsed_successfully() {
+        assert_ok!(DebtStatus::parse(PAID_STR));
+    }
+}
@@ -1,6 +1,6 @@
 use uuid::Uuid;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DebtUserId(Uuid);
 
 impl AsRef<Uuid> for DebtUserId {
@@ -9,6 +9,12 @@ impl AsRef<Uuid> for DebtUserId {
     }
 }
 
+impl From<Uuid> for DebtUserId {
+    fn from(user_id: Uuid) -> Self {
+        Self(user_id)
+    }
+}
+
 impl DebtUserId {
     pub fn parse(s: &str) -> Result<Self, String> {
         let uuid = Uuid::parse_str(s).map_err(|_| format!("{} is not valid UUID", s))?;
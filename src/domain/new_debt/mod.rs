@@ -1,12 +1,14 @@
 mod debt_amount;
 mod debt_currency;
 mod debt_description;
+mod debt_due_date;
 mod debt_status;
 mod debt_user_id;
 
 pub use debt_amount::DebtAmount;
 pub use debt_currency::DebtCurrency;
 pub use debt_description::DebtDescription;
+pub use debt_due_date::DebtDueDate;
 pub use debt_status::DebtStatus;
 pub use debt_user_id::DebtUserId;
 
@@ -17,5 +19,6 @@ pub struct NewDebt {
     pub amount: DebtAmount,
     pub currency: DebtCurrency,
     pub description: DebtDescription,
+    pub due_date: Option<DebtDueDate>,
     pub status: DebtStatus,
 }
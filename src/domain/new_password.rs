@@ -0,0 +1,47 @@
+use secrecy::{ExposeSecret, Secret};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug)]
+pub struct NewPassword(Secret<String>);
+
+impl NewPassword {
+    pub fn parse(password: Secret<String>) -> Result<Self, String> {
+        let length = password.expose_secret().graphemes(true).count();
+
+        if length < 12 {
+            Err("The new password must be at least 12 characters long.".to_string())
+        } else if length > 128 {
+            Err("The new password must be at most 128 characters long.".to_string())
+        } else {
+            Ok(Self(password))
+        }
+    }
+
+    pub fn inner(self) -> Secret<String> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn a_password_shorter_than_12_graphemes_is_rejected() {
+        let password = Secret::new("a".repeat(11));
+        assert_err!(NewPassword::parse(password));
+    }
+
+    #[test]
+    fn a_password_longer_than_128_graphemes_is_rejected() {
+        let password = Secret::new("a".repeat(129));
+        assert_err!(NewPassword::parse(password));
+    }
+
+    #[test]
+    fn a_12_grapheme_long_password_is_valid() {
+        let password = Secret::new("ё".repeat(12));
+        assert_ok!(NewPassword::parse(password));
+    }
+}
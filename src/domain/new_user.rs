@@ -2,11 +2,14 @@ use argon2::password_hash::SaltString;
 use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
 use uuid::Uuid;
 
+use super::UserStatus;
+
 pub struct NewUser {
     pub user_id: Uuid,
     pub username: String,
     pub password_hash: String,
     pub email: String,
+    pub status: UserStatus,
 }
 
 impl NewUser {
@@ -26,6 +29,7 @@ impl NewUser {
             username,
             password_hash,
             email,
+            status: UserStatus::PendingConfirmation,
         }
     }
 }
@@ -0,0 +1,44 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationKind {
+    DebtCreated,
+}
+
+const DEBT_CREATED_STR: &str = "debt_created";
+
+impl std::fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::NotificationKind::DebtCreated;
+        match self {
+            DebtCreated => DEBT_CREATED_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl NotificationKind {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::NotificationKind::DebtCreated;
+
+        match s {
+            DEBT_CREATED_STR => Ok(DebtCreated),
+            _ => Err(format!("{} is not a valid notification kind", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok_eq};
+
+    #[test]
+    fn unknown_kind_is_rejected() {
+        assert_err!(NotificationKind::parse("debt_forgotten"));
+    }
+
+    #[test]
+    fn kind_round_trips_through_its_string_form() {
+        let kind = NotificationKind::DebtCreated;
+
+        assert_ok_eq!(NotificationKind::parse(&kind.to_string()), kind);
+    }
+}
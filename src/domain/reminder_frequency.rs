@@ -0,0 +1,50 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReminderFrequency {
+    Off,
+    Weekly,
+    WhenOverdue,
+}
+
+const OFF_STR: &str = "off";
+const WEEKLY_STR: &str = "weekly";
+const WHEN_OVERDUE_STR: &str = "when_overdue";
+
+impl std::fmt::Display for ReminderFrequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::ReminderFrequency::{Off, Weekly, WhenOverdue};
+        match self {
+            Off => OFF_STR.to_string().fmt(f),
+            Weekly => WEEKLY_STR.to_string().fmt(f),
+            WhenOverdue => WHEN_OVERDUE_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl ReminderFrequency {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::ReminderFrequency::{Off, Weekly, WhenOverdue};
+
+        match s {
+            OFF_STR => Ok(Off),
+            WEEKLY_STR => Ok(Weekly),
+            WHEN_OVERDUE_STR => Ok(WhenOverdue),
+            _ => Err(format!("{} is not a valid reminder frequency", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_frequency_is_rejected() {
+        assert_err!(ReminderFrequency::parse("daily"));
+    }
+
+    #[test]
+    fn when_overdue_is_parsed_successfully() {
+        assert_ok!(ReminderFrequency::parse(WHEN_OVERDUE_STR));
+    }
+}
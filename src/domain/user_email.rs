@@ -0,0 +1,64 @@
+#[derive(Debug)]
+pub struct UserEmail(String);
+
+impl UserEmail {
+    /// A light syntax check: whether the address exists is only known once the
+    /// confirmation link sent to it is followed.
+    pub fn parse(s: String) -> Result<Self, String> {
+        let s = s.trim().to_string();
+        let invalid = || Err(format!("{} is not a valid email address.", s));
+
+        if s.len() > 254 || s.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return invalid();
+        }
+        match s.split_once('@') {
+            Some((local, domain))
+                if !local.is_empty()
+                    && !domain.contains('@')
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.') =>
+            {
+                Ok(Self(s))
+            }
+            _ => invalid(),
+        }
+    }
+}
+
+impl AsRef<str> for UserEmail {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn a_valid_email_is_accepted() {
+        assert_ok!(UserEmail::parse("ursula@debt-tracer.com".to_string()));
+    }
+
+    #[test]
+    fn an_email_without_an_at_sign_is_rejected() {
+        assert_err!(UserEmail::parse("ursula.debt-tracer.com".to_string()));
+    }
+
+    #[test]
+    fn an_email_without_a_local_part_is_rejected() {
+        assert_err!(UserEmail::parse("@debt-tracer.com".to_string()));
+    }
+
+    #[test]
+    fn an_email_without_a_domain_is_rejected() {
+        assert_err!(UserEmail::parse("ursula@".to_string()));
+    }
+
+    #[test]
+    fn an_email_with_whitespace_is_rejected() {
+        assert_err!(UserEmail::parse("ur sula@debt-tracer.com".to_string()));
+    }
+}
@@ -0,0 +1,106 @@
+use super::{DebtUserId, UserStatus};
+use anyhow::Context;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// How a client may refer to another user: by ID, username or email.
+#[derive(Debug, PartialEq)]
+pub enum UserReference {
+    Id(Uuid),
+    Username(String),
+    Email(String),
+}
+
+impl UserReference {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+
+        if s.is_empty() {
+            Err("A user reference cannot be empty.".to_string())
+        } else if let Ok(user_id) = Uuid::parse_str(s) {
+            Ok(Self::Id(user_id))
+        } else if s.contains('@') {
+            Ok(Self::Email(s.to_string()))
+        } else {
+            Ok(Self::Username(s.to_string()))
+        }
+    }
+
+    /// Look up the referenced user, ignoring deleted accounts. Usernames may look
+    /// like UUIDs, so an ID falls back to matching a username.
+    #[tracing::instrument(name = "Resolve user reference", skip(executor))]
+    pub async fn resolve(
+        &self,
+        executor: impl PgExecutor<'_>,
+    ) -> Result<Option<DebtUserId>, anyhow::Error> {
+        let (user_id, username, email) = match self {
+            Self::Id(user_id) => (Some(*user_id), Some(user_id.to_string()), None),
+            Self::Username(username) => (None, Some(username.clone()), None),
+            Self::Email(email) => (None, None, Some(email.as_str())),
+        };
+        let user_id = sqlx::query!(
+            r#"
+            SELECT user_id
+            FROM users
+            WHERE (user_id = $1 OR username = $2 OR email = $3) AND status <> $4
+            ORDER BY (user_id = $1) IS TRUE DESC
+            LIMIT 1
+            "#,
+            user_id,
+            username,
+            email,
+            UserStatus::Deleted.to_string()
+        )
+        .fetch_optional(executor)
+        .await
+        .context("Failed to perform a query to resolve a user reference.")?
+        .map(|row| DebtUserId::from(row.user_id));
+        Ok(user_id)
+    }
+}
+
+impl std::fmt::Display for UserReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Id(user_id) => user_id.fmt(f),
+            Self::Username(username) => username.fmt(f),
+            Self::Email(email) => email.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok_eq};
+
+    #[test]
+    fn empty_reference_is_rejected() {
+        assert_err!(UserReference::parse("  "));
+    }
+
+    #[test]
+    fn uuid_is_parsed_as_an_id() {
+        let user_id = Uuid::new_v4();
+        assert_ok_eq!(
+            UserReference::parse(&user_id.to_string()),
+            UserReference::Id(user_id)
+        );
+    }
+
+    #[test]
+    fn reference_with_an_at_sign_is_parsed_as_an_email() {
+        assert_ok_eq!(
+            UserReference::parse("ursula@debt-tracer.com"),
+            UserReference::Email("ursula@debt-tracer.com".to_string())
+        );
+    }
+
+    #[test]
+    fn anything_else_is_parsed_as_a_username() {
+        assert_ok_eq!(
+            UserReference::parse(" ursula "),
+            UserReference::Username("ursula".to_string())
+        );
+    }
+}
@@ -0,0 +1,52 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UserRole {
+    User,
+    Admin,
+    Auditor,
+}
+
+const USER_STR: &str = "user";
+const ADMIN_STR: &str = "admin";
+const AUDITOR_STR: &str = "auditor";
+
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::UserRole::{Admin, Auditor, User};
+        match self {
+            User => USER_STR.to_string().fmt(f),
+            Admin => ADMIN_STR.to_string().fmt(f),
+            Auditor => AUDITOR_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl UserRole {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::UserRole::{Admin, Auditor, User};
+
+        match s {
+            USER_STR => Ok(User),
+            ADMIN_STR => Ok(Admin),
+            AUDITOR_STR => Ok(Auditor),
+            _ => Err(format!("{} is not a valid role", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok_eq};
+
+    #[test]
+    fn unknown_role_is_rejected() {
+        assert_err!(UserRole::parse("superuser"));
+    }
+
+    #[test]
+    fn roles_round_trip_through_their_string_form() {
+        for role in [UserRole::User, UserRole::Admin, UserRole::Auditor] {
+            assert_ok_eq!(UserRole::parse(&role.to_string()), role);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+#[derive(Debug, PartialEq)]
+pub enum UserStatus {
+    PendingConfirmation,
+    Invited,
+    Active,
+    Disabled,
+    Deleted,
+}
+
+const PENDING_CONFIRMATION_STR: &str = "pending_confirmation";
+const INVITED_STR: &str = "invited";
+const ACTIVE_STR: &str = "active";
+const DISABLED_STR: &str = "disabled";
+const DELETED_STR: &str = "deleted";
+
+impl std::fmt::Display for UserStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::UserStatus::{Active, Deleted, Disabled, Invited, PendingConfirmation};
+        match self {
+            PendingConfirmation => PENDING_CONFIRMATION_STR.to_string().fmt(f),
+            Invited => INVITED_STR.to_string().fmt(f),
+            Active => ACTIVE_STR.to_string().fmt(f),
+            Disabled => DISABLED_STR.to_string().fmt(f),
+            Deleted => DELETED_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl UserStatus {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::UserStatus::{Active, Deleted, Disabled, Invited, PendingConfirmation};
+
+        match s {
+            PENDING_CONFIRMATION_STR => Ok(PendingConfirmation),
+            INVITED_STR => Ok(Invited),
+            ACTIVE_STR => Ok(Active),
+            DISABLED_STR => Ok(Disabled),
+            DELETED_STR => Ok(Deleted),
+            _ => Err(format!("{} is not a valid user status", s)),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use chrono_tz::Tz;
+
+#[derive(Debug)]
+pub struct UserTimezone(Tz);
+
+impl UserTimezone {
+    pub fn parse(s: String) -> Result<Self, String> {
+        s.parse::<Tz>()
+            .map(Self)
+            .map_err(|_| format!("{} is not a valid IANA timezone", s))
+    }
+}
+
+impl std::fmt::Display for UserTimezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.name().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_timezone_is_rejected() {
+        assert_err!(UserTimezone::parse("Mars/Olympus_Mons".to_string()));
+    }
+
+    #[test]
+    fn iana_timezone_is_parsed_successfully() {
+        assert_ok!(UserTimezone::parse("Europe/Berlin".to_string()));
+    }
+}
@@ -0,0 +1,50 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+const PENDING_STR: &str = "pending";
+const DELIVERED_STR: &str = "delivered";
+const FAILED_STR: &str = "failed";
+
+impl std::fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::WebhookDeliveryStatus::{Delivered, Failed, Pending};
+        match self {
+            Pending => PENDING_STR.to_string().fmt(f),
+            Delivered => DELIVERED_STR.to_string().fmt(f),
+            Failed => FAILED_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl WebhookDeliveryStatus {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::WebhookDeliveryStatus::{Delivered, Failed, Pending};
+
+        match s {
+            PENDING_STR => Ok(Pending),
+            DELIVERED_STR => Ok(Delivered),
+            FAILED_STR => Ok(Failed),
+            _ => Err(format!("{} is not a valid webhook delivery status", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_status_is_rejected() {
+        assert_err!(WebhookDeliveryStatus::parse("lost"));
+    }
+
+    #[test]
+    fn delivered_is_parsed_successfully() {
+        assert_ok!(WebhookDeliveryStatus::parse(DELIVERED_STR));
+    }
+}
@@ -0,0 +1,42 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebhookEventType {
+    DebtCreated,
+}
+
+const DEBT_CREATED_STR: &str = "debt_created";
+
+impl std::fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use self::WebhookEventType::DebtCreated;
+        match self {
+            DebtCreated => DEBT_CREATED_STR.to_string().fmt(f),
+        }
+    }
+}
+
+impl WebhookEventType {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        use self::WebhookEventType::DebtCreated;
+
+        match s {
+            DEBT_CREATED_STR => Ok(DebtCreated),
+            _ => Err(format!("{} is not a valid webhook event type", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn unknown_event_type_is_rejected() {
+        assert_err!(WebhookEventType::parse("debt_forgotten"));
+    }
+
+    #[test]
+    fn debt_created_is_parsed_successfully() {
+        assert_ok!(WebhookEventType::parse(DEBT_CREATED_STR));
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Context;
+use futures_util::{Stream, StreamExt};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use secrecy::{ExposeSecret, Secret};
+use uuid::Uuid;
+
+/// Something a user's clients should refresh, pushed over `GET /events`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    DebtCreated {
+        debt_id: Uuid,
+    },
+    Notification {
+        notification_id: Uuid,
+        debt_id: Uuid,
+    },
+}
+
+// Events go through Redis pub/sub, with one channel per user, so that a client
+// gets them whichever instance it is connected to.
+#[derive(Clone)]
+pub struct EventBus {
+    client: redis::Client,
+    connection: ConnectionManager,
+}
+
+impl EventBus {
+    pub async fn new(redis_uri: &Secret<String>) -> Result<Self, anyhow::Error> {
+        let client = redis::Client::open(redis_uri.expose_secret().as_str())
+            .context("Failed to parse the Redis URI.")?;
+        let connection = client
+            .get_tokio_connection_manager()
+            .await
+            .context("Failed to connect to Redis.")?;
+        Ok(Self { client, connection })
+    }
+
+    #[tracing::instrument(name = "Publish event", skip(self))]
+    pub async fn publish(&self, user_id: Uuid, event: &Event) -> Result<(), anyhow::Error> {
+        let payload = serde_json::to_string(event).context("Failed to serialise an event.")?;
+        let mut connection = self.connection.clone();
+        let _subscribers: usize = connection
+            .publish(channel(user_id), payload)
+            .await
+            .context("Failed to publish an event to Redis.")?;
+        Ok(())
+    }
+
+    /// Subscribing takes a dedicated Redis connection, held for as long as the
+    /// returned stream is.
+    #[tracing::instrument(name = "Subscribe to events", skip(self))]
+    pub async fn subscribe(
+        &self,
+        user_id: Uuid,
+    ) -> Result<impl Stream<Item = Event>, anyhow::Error> {
+        let mut pubsub = self
+            .client
+            .get_tokio_connection()
+            .await
+            .context("Failed to connect to Redis.")?
+            .into_pubsub();
+        pubsub
+            .subscribe(channel(user_id))
+            .await
+            .context("Failed to subscribe to the user's events.")?;
+        let events = pubsub.into_on_message().filter_map(|message| async move {
+            let payload: String = message.get_payload().ok()?;
+            serde_json::from_str(&payload).ok()
+        });
+        Ok(events)
+    }
+}
+
+fn channel(user_id: Uuid) -> String {
+    format!("events:{}", user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_tagged_with_their_type() {
+        let debt_id = Uuid::new_v4();
+
+        let json = serde_json::to_value(Event::DebtCreated { debt_id }).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "debt_created", "debt_id": debt_id })
+        );
+    }
+}
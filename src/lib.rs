@@ -2,8 +2,15 @@ pub mod authentication;
 pub mod configuration;
 pub mod debts;
 pub mod domain;
+pub mod email_client;
+pub mod event_bus;
+pub mod openapi;
+pub mod operations;
+pub mod pdf;
+pub mod reminder_worker;
 pub mod routes;
 pub mod session_state;
 pub mod startup;
 pub mod telemetry;
 pub mod utils;
+pub mod webhook_delivery_worker;
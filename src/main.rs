@@ -1,17 +1,172 @@
-use debt_tracer::configuration::get_configuration;
-use debt_tracer::startup::Application;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use debt_tracer::configuration::{get_configuration, Settings};
+use debt_tracer::domain::UserRole;
+use debt_tracer::operations;
+use debt_tracer::reminder_worker;
+use debt_tracer::startup::{get_connection_pool, Application};
 use debt_tracer::telemetry::{get_subscriber, init_subscriber};
+use debt_tracer::webhook_delivery_worker::run_worker_until_stopped;
+use secrecy::Secret;
+use std::fmt::{Debug, Display};
+use tokio::task::JoinError;
+
+#[derive(Parser)]
+#[command(name = "debt-tracer", version, about = "The debt-tracer server")]
+struct Cli {
+    /// Defaults to `serve`.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the API together with the webhook delivery and reminder workers.
+    Serve,
+    /// Apply the pending database migrations.
+    Migrate,
+    /// Create an active user, skipping the email confirmation.
+    ///
+    /// The password is read from `DEBT_TRACER_PASSWORD`, or prompted for.
+    CreateUser {
+        username: String,
+        email: String,
+        /// `user`, `admin` or `auditor`.
+        #[arg(long, default_value = "user")]
+        role: String,
+    },
+    /// Set a user's password and log them out everywhere.
+    ///
+    /// The password is read from `DEBT_TRACER_PASSWORD`, or prompted for.
+    ResetPassword { username: String },
+    /// Check that the configuration loads and its services are reachable.
+    CheckConfig,
+    /// Recompute every user's net balance per currency from the debts.
+    RecomputeBalances,
+}
 
 #[tokio::main]
-async fn main() -> Result<(), std::io::Error> {
-    let subscriber = get_subscriber("debt-tracer".into(), "info".into(), std::io::stdout);
-    init_subscriber(subscriber);
+async fn main() -> anyhow::Result<()> {
+    let command = Cli::parse().command.unwrap_or(Command::Serve);
+
+    // Only the server logs to stdout; the other commands print their results
+    // there and keep the logs out of the way.
+    if let Command::Serve = command {
+        let subscriber = get_subscriber("debt-tracer".into(), "info".into(), std::io::stdout);
+        init_subscriber(subscriber);
+    } else {
+        let subscriber = get_subscriber("debt-tracer".into(), "warn".into(), std::io::stderr);
+        init_subscriber(subscriber);
+    }
+
+    let configuration = get_configuration().context("Failed to read configuration.")?;
+
+    match command {
+        Command::Serve => serve(configuration).await,
+        Command::Migrate => {
+            operations::migrate(&get_connection_pool(&configuration.database)).await?;
+            println!("The database is up to date.");
+            Ok(())
+        }
+        Command::CreateUser {
+            username,
+            email,
+            role,
+        } => {
+            let role = UserRole::parse(&role).map_err(anyhow::Error::msg)?;
+            let password = read_password()?;
+            let pool = get_connection_pool(&configuration.database);
+            let user_id = operations::create_user(&pool, &username, &email, password, role).await?;
+            println!("Created {} with ID {}.", username, user_id);
+            Ok(())
+        }
+        Command::ResetPassword { username } => {
+            let password = read_password()?;
+            let pool = get_connection_pool(&configuration.database);
+            operations::reset_password(&pool, &username, password).await?;
+            println!("Reset the password of {}.", username);
+            Ok(())
+        }
+        Command::CheckConfig => check_config(&configuration).await,
+        Command::RecomputeBalances => recompute_balances(&configuration).await,
+    }
+}
 
-    let configuration = get_configuration().expect("Failed to read configuration.");
+async fn serve(configuration: Settings) -> anyhow::Result<()> {
+    let application = Application::build(configuration.clone()).await?;
+    let application_task = tokio::spawn(application.run_until_stopped());
+    let worker_task = tokio::spawn(run_worker_until_stopped(configuration.clone()));
+    let reminder_task = tokio::spawn(reminder_worker::run_worker_until_stopped(configuration));
 
-    let application = Application::build(configuration)
-        .await
-        .expect("Failed to build application.");
+    tokio::select! {
+        o = application_task => report_exit("API", o),
+        o = worker_task => report_exit("Webhook delivery worker", o),
+        o = reminder_task => report_exit("Reminder worker", o),
+    };
+
+    Ok(())
+}
+
+/// Passwords are read from `DEBT_TRACER_PASSWORD` or a prompt, never from the
+/// command line where they would end up in the shell history and process list.
+fn read_password() -> anyhow::Result<Secret<String>> {
+    let password = match std::env::var("DEBT_TRACER_PASSWORD") {
+        Ok(password) => password,
+        Err(_) => {
+            rpassword::prompt_password("Password: ").context("Failed to read the password.")?
+        }
+    };
+    Ok(Secret::new(password))
+}
+
+async fn check_config(configuration: &Settings) -> anyhow::Result<()> {
+    let mut failures = 0;
+    for (name, outcome) in operations::check_config(configuration).await {
+        match outcome {
+            Ok(()) => println!("ok      {}", name),
+            Err(e) => {
+                failures += 1;
+                println!("FAILED  {}: {:#}", name, e);
+            }
+        }
+    }
+    if failures > 0 {
+        anyhow::bail!("{} check(s) failed.", failures);
+    }
+    Ok(())
+}
+
+async fn recompute_balances(configuration: &Settings) -> anyhow::Result<()> {
+    let pool = get_connection_pool(&configuration.database);
+    for balance in operations::recompute_balances(&pool).await? {
+        println!(
+            "{}\t{}\t{}\t{}",
+            balance.user_id, balance.username, balance.currency, balance.net
+        );
+    }
+    Ok(())
+}
 
-    application.run_until_stopped().await
+fn report_exit(task_name: &str, outcome: Result<Result<(), impl Debug + Display>, JoinError>) {
+    match outcome {
+        Ok(Ok(())) => {
+            tracing::info!("{} has exited", task_name)
+        }
+        Ok(Err(e)) => {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{} failed",
+                task_name
+            )
+        }
+        Err(e) => {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{}' task failed to complete",
+                task_name
+            )
+        }
+    }
 }
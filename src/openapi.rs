@@ -0,0 +1,77 @@
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+/// The OpenAPI document for every route registered in `startup::run`. Routes
+/// need a session cookie or an API token unless they opt out with
+/// `security(())`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Debt Tracer"),
+    paths(
+        crate::startup::health_check,
+        crate::routes::login::post::login,
+        crate::routes::login::two_factor::login_two_factor,
+        crate::routes::login::oidc::login_oidc,
+        crate::routes::login::oidc::login_oidc_callback,
+        crate::routes::signup::post::sign_up,
+        crate::routes::signup::confirm::confirm_sign_up,
+        crate::routes::users::confirm_email::confirm_email_change,
+        crate::routes::password_reset::post::request_password_reset,
+        crate::routes::password_reset::confirm::confirm_password_reset,
+        crate::debts::create_debt,
+        crate::debts::remind_debtor,
+        crate::debts::mark_debt_paid,
+        crate::debts::get_debts_by_user_id,
+        crate::routes::debts::export::export_debts,
+        crate::routes::debts::import::import_debts,
+        crate::routes::debts::splitwise::import_splitwise,
+        crate::routes::password::post::change_password,
+        crate::routes::two_factor::post::enroll_totp,
+        crate::routes::two_factor::confirm::confirm_totp,
+        crate::routes::tokens::get::get_api_tokens,
+        crate::routes::tokens::post::create_api_token,
+        crate::routes::tokens::delete::revoke_api_token,
+        crate::routes::users::get::get_user_info_by_id,
+        crate::routes::users::patch::update_user,
+        crate::routes::users::delete::delete_user,
+        crate::routes::users::export::export_user_data,
+        crate::routes::users::search::search_users,
+        crate::routes::events::get::stream_events,
+        crate::routes::statements::get::get_statement,
+        crate::routes::reports::timeseries::get_timeseries,
+        crate::routes::notifications::get::get_notifications,
+        crate::routes::notifications::read::mark_notifications_read,
+        crate::routes::webhooks::get::get_webhooks,
+        crate::routes::webhooks::post::create_webhook,
+        crate::routes::webhooks::delete::delete_webhook,
+        crate::routes::webhooks::deliveries::get_webhook_deliveries,
+        crate::routes::contacts::get::get_contacts,
+        crate::routes::contacts::delete::remove_contact,
+        crate::routes::contacts::requests::get_contact_requests,
+        crate::routes::contacts::requests::send_contact_request,
+        crate::routes::contacts::requests::accept_contact_request,
+        crate::routes::contacts::requests::decline_contact_request,
+        crate::routes::admin::users::list_users,
+        crate::routes::admin::users::disable_user,
+        crate::routes::admin::debts::get_debt,
+    ),
+    modifiers(&SecuritySchemes),
+    security(("session_cookie" = []), ("api_token" = [])),
+)]
+pub struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "session_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("id"))),
+        );
+        components.add_security_scheme(
+            "api_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Maintenance tasks run through the `debt-tracer` binary's subcommands
+//! rather than the HTTP API.
+use crate::authentication::{change_password, compute_password_hash};
+use crate::configuration::Settings;
+use crate::domain::{DebtStatus, NewPassword, UserRole, UserStatus};
+use crate::telemetry::spawn_blocking_with_tracing;
+use anyhow::Context;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use secrecy::{ExposeSecret, Secret};
+use sqlx::{Connection, PgConnection, PgPool};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "Running database migrations", skip(pool))]
+pub async fn migrate(pool: &PgPool) -> Result<(), anyhow::Error> {
+    sqlx::migrate!("./migrations")
+        .run(pool)
+        .await
+        .context("Failed to migrate the database.")
+}
+
+/// Users created by an operator skip the email confirmation.
+#[tracing::instrument(name = "Creating a user", skip(pool, password))]
+pub async fn create_user(
+    pool: &PgPool,
+    username: &str,
+    email: &str,
+    password: Secret<String>,
+    role: UserRole,
+) -> Result<Uuid, anyhow::Error> {
+    let password = NewPassword::parse(password).map_err(anyhow::Error::msg)?;
+    let password_hash =
+        spawn_blocking_with_tracing(move || compute_password_hash(password.inner()))
+            .await?
+            .context("Failed to hash password")?;
+    let user_id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO users (user_id, username, password_hash, email, status, role)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        user_id,
+        username,
+        password_hash.expose_secret(),
+        email,
+        UserStatus::Active.to_string(),
+        role.to_string()
+    )
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to create {}.", username))?;
+    Ok(user_id)
+}
+
+/// Existing sessions of the user are invalidated.
+#[tracing::instrument(name = "Resetting a password", skip(pool, password))]
+pub async fn reset_password(
+    pool: &PgPool,
+    username: &str,
+    password: Secret<String>,
+) -> Result<(), anyhow::Error> {
+    let password = NewPassword::parse(password).map_err(anyhow::Error::msg)?;
+    let user_id = sqlx::query_scalar!("SELECT user_id FROM users WHERE username = $1", username)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to look up the user.")?
+        .ok_or_else(|| anyhow::anyhow!("{} is not a known user.", username))?;
+    change_password(user_id, password.inner(), pool).await
+}
+
+/// Reach every service the configuration points at, reporting each one
+/// separately so a single run shows everything that needs fixing.
+pub async fn check_config(
+    configuration: &Settings,
+) -> Vec<(&'static str, Result<(), anyhow::Error>)> {
+    vec![
+        ("database", check_database(configuration).await),
+        ("redis", check_redis(configuration).await),
+        (
+            "application base URL",
+            check_url(&configuration.application.base_url),
+        ),
+        (
+            "frontend base URL",
+            check_url(&configuration.application.frontend_base_url),
+        ),
+        (
+            "email API base URL",
+            check_url(&configuration.email_client.base_url),
+        ),
+    ]
+}
+
+async fn check_database(configuration: &Settings) -> Result<(), anyhow::Error> {
+    let mut connection = PgConnection::connect_with(&configuration.database.with_db())
+        .await
+        .context("Failed to connect to Postgres.")?;
+    connection
+        .ping()
+        .await
+        .context("Failed to ping Postgres.")?;
+    Ok(())
+}
+
+async fn check_redis(configuration: &Settings) -> Result<(), anyhow::Error> {
+    let client = redis::Client::open(configuration.redis_uri.expose_secret().as_str())
+        .context("Failed to parse the Redis URI.")?;
+    let mut connection = client
+        .get_tokio_connection_manager()
+        .await
+        .context("Failed to connect to Redis.")?;
+    connection
+        .exists::<_, bool>("debt-tracer:check-config")
+        .await
+        .context("Failed to query Redis.")?;
+    Ok(())
+}
+
+fn check_url(url: &str) -> Result<(), anyhow::Error> {
+    reqwest::Url::parse(url).with_context(|| format!("{} is not an absolute URL.", url))?;
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UserBalance {
+    pub user_id: Uuid,
+    pub username: String,
+    pub currency: String,
+    /// Positive when the user is owed money overall.
+    pub net: Decimal,
+}
+
+/// Net balances of every user per currency, derived from the debts that are
+/// not paid yet. Debts a user recorded with themselves are left out.
+#[tracing::instrument(name = "Recomputing balances", skip(pool))]
+pub async fn recompute_balances(pool: &PgPool) -> Result<Vec<UserBalance>, anyhow::Error> {
+    let balances = sqlx::query!(
+        r#"
+        SELECT users.user_id, users.username, entries.currency AS "currency!", SUM(entries.amount) AS "net!"
+        FROM (
+            SELECT creditor_id AS user_id, currency, amount
+            FROM debts
+            WHERE status <> $1 AND creditor_id <> debtor_id
+            UNION ALL
+            SELECT debtor_id AS user_id, currency, -amount
+            FROM debts
+            WHERE status <> $1 AND creditor_id <> debtor_id
+        ) AS entries
+        JOIN users ON users.user_id = entries.user_id
+        GROUP BY users.user_id, users.username, entries.currency
+        HAVING SUM(entries.amount) <> 0
+        ORDER BY users.username, entries.currency
+        "#,
+        DebtStatus::Paid.to_string()
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to compute the balances.")?
+    .into_iter()
+    .map(|row| UserBalance {
+        user_id: row.user_id,
+        username: row.username,
+        currency: row.currency,
+        net: row.net,
+    })
+    .collect();
+    Ok(balances)
+}
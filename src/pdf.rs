@@ -0,0 +1,193 @@
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 50;
+const LINE_HEIGHT: u32 = 14;
+const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2 * MARGIN) / LINE_HEIGHT) as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Heading,
+    /// Monospaced, so columns line up.
+    Text,
+}
+
+/// A minimal PDF 1.4 writer for plain-text documents such as statements. It
+/// only uses the standard Helvetica and Courier fonts, which every reader
+/// ships, so nothing has to be embedded.
+#[derive(Default)]
+pub struct PdfDocument {
+    lines: Vec<(Style, String)>,
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn line(&mut self, style: Style, text: impl Into<String>) -> &mut Self {
+        self.lines.push((style, text.into()));
+        self
+    }
+
+    pub fn blank_line(&mut self) -> &mut Self {
+        self.line(Style::Text, "")
+    }
+
+    pub fn render(&self) -> Vec<u8> {
+        let pages: Vec<&[(Style, String)]> = if self.lines.is_empty() {
+            vec![&[]]
+        } else {
+            self.lines.chunks(LINES_PER_PAGE).collect()
+        };
+
+        // Objects 1 to 4 are the catalog, the page tree and the two fonts,
+        // followed by a page and its content stream for every page.
+        let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + 2 * i).collect();
+        let mut objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{} 0 R", id))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                pages.len()
+            )
+            .into_bytes(),
+            font("Helvetica-Bold"),
+            font("Courier"),
+        ];
+        for (page, page_id) in pages.iter().zip(&page_ids) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                    /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    page_id + 1
+                )
+                .into_bytes(),
+            );
+            let content = page_content(page);
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+            pdf.extend(object);
+            pdf.extend(b"\nendobj\n");
+        }
+        let xref_offset = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref_offset
+            )
+            .into_bytes(),
+        );
+        pdf
+    }
+}
+
+fn font(name: &str) -> Vec<u8> {
+    format!(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+        name
+    )
+    .into_bytes()
+}
+
+fn page_content(lines: &[(Style, String)]) -> Vec<u8> {
+    let mut content = format!(
+        "BT\n{} TL\n{} {} Td\n",
+        LINE_HEIGHT,
+        MARGIN,
+        PAGE_HEIGHT - MARGIN
+    )
+    .into_bytes();
+    for (style, text) in lines {
+        let font = match style {
+            Style::Heading => "/F1 14 Tf",
+            Style::Text => "/F2 10 Tf",
+        };
+        content.extend(format!("{}\n(", font).into_bytes());
+        content.extend(escape(text));
+        content.extend(b") Tj T*\n");
+    }
+    content.extend(b"ET");
+    content
+}
+
+/// Encode text as a PDF literal string. Characters outside Latin-1 have no
+/// glyph in the standard fonts and are replaced.
+fn escape(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                bytes.push(c as u8);
+            }
+            c if (c as u32) < 0x20 => bytes.push(b' '),
+            c if (c as u32) <= 0xFF => bytes.push(c as u32 as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    #[test]
+    fn xref_offsets_point_at_their_objects() {
+        let mut document = PdfDocument::new();
+        document.line(Style::Heading, "Statement");
+        let pdf = document.render();
+
+        let xref = find(&pdf, b"xref\n").unwrap();
+        let entries = String::from_utf8_lossy(&pdf[xref..]);
+        for (i, entry) in entries.lines().skip(3).take(6).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            let header = format!("{} 0 obj", i + 1);
+            assert!(pdf[offset..].starts_with(header.as_bytes()));
+        }
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+    }
+
+    #[test]
+    fn long_documents_are_split_into_pages() {
+        let mut document = PdfDocument::new();
+        for i in 0..LINES_PER_PAGE + 1 {
+            document.line(Style::Text, format!("line {}", i));
+        }
+        let pdf = document.render();
+
+        assert!(find(&pdf, b"/Count 2").is_some());
+    }
+
+    #[test]
+    fn parentheses_are_escaped() {
+        assert_eq!(escape("(a) \\ €"), b"\\(a\\) \\\\ ?".to_vec());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::configuration::Settings;
+use crate::domain::{DebtStatus, ReminderFrequency, UserStatus};
+use crate::email_client::EmailClient;
+use crate::startup::get_connection_pool;
+use crate::utils::escape_html;
+use anyhow::Context;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use uuid::Uuid;
+
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+struct ReminderTask {
+    user_id: Uuid,
+    email: String,
+    frequency: ReminderFrequency,
+}
+
+struct OutstandingDebt {
+    creditor_name: String,
+    amount: Decimal,
+    currency: String,
+    description: String,
+    due_date: Option<NaiveDate>,
+}
+
+pub async fn run_worker_until_stopped(configuration: Settings) -> Result<(), anyhow::Error> {
+    let connection_pool = get_connection_pool(&configuration.database);
+    let email_client = configuration.email_client.client();
+    worker_loop(connection_pool, email_client).await
+}
+
+async fn worker_loop(pool: PgPool, email_client: EmailClient) -> Result<(), anyhow::Error> {
+    loop {
+        match try_execute_task(&pool, &email_client).await {
+            Ok(ExecutionOutcome::EmptyQueue) => {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            Err(_) => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+        }
+    }
+}
+
+/// Send a digest to one user whose reminder is due. Weekly digests list every
+/// unpaid debt the user owes, overdue ones are sent at most once a day and only
+/// list debts past their due date.
+#[tracing::instrument(skip_all, fields(user_id=tracing::field::Empty), err)]
+pub async fn try_execute_task(
+    pool: &PgPool,
+    email_client: &EmailClient,
+) -> Result<ExecutionOutcome, anyhow::Error> {
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let task = match dequeue_task(&mut transaction).await? {
+        Some(task) => task,
+        None => return Ok(ExecutionOutcome::EmptyQueue),
+    };
+    tracing::Span::current().record("user_id", tracing::field::display(&task.user_id));
+
+    let debts = fetch_outstanding_debts(&mut transaction, &task).await?;
+    // A failed digest is not retried until the next one is due, so a broken
+    // email provider cannot turn the worker into a retry loop.
+    if let Err(e) = send_digest(email_client, &task.email, &debts).await {
+        tracing::error!(
+            error.cause_chain = ?e,
+            error.message = %e,
+            "Failed to send a reminder digest. Skipping.",
+        );
+    }
+    sqlx::query!(
+        "UPDATE users SET last_reminder_sent_at = now() WHERE user_id = $1",
+        task.user_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to record that a reminder was sent.")?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to record a reminder.")?;
+    Ok(ExecutionOutcome::TaskCompleted)
+}
+
+async fn dequeue_task(
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<Option<ReminderTask>, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT user_id, email, reminder_frequency
+        FROM users
+        WHERE status = $1
+            AND (
+                (reminder_frequency = $2
+                    AND (last_reminder_sent_at IS NULL
+                        OR last_reminder_sent_at <= now() - interval '7 days')
+                    AND EXISTS (
+                        SELECT 1 FROM debts
+                        WHERE debts.debtor_id = users.user_id AND debts.status <> $4
+                    ))
+                OR (reminder_frequency = $3
+                    AND (last_reminder_sent_at IS NULL
+                        OR last_reminder_sent_at <= now() - interval '1 day')
+                    AND EXISTS (
+                        SELECT 1 FROM debts
+                        WHERE debts.debtor_id = users.user_id AND debts.status <> $4
+                            AND debts.due_date < CURRENT_DATE
+                    ))
+            )
+        ORDER BY last_reminder_sent_at NULLS FIRST
+        FOR UPDATE
+        SKIP LOCKED
+        LIMIT 1
+        "#,
+        UserStatus::Active.to_string(),
+        ReminderFrequency::Weekly.to_string(),
+        ReminderFrequency::WhenOverdue.to_string(),
+        DebtStatus::Paid.to_string()
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to dequeue a reminder.")?;
+
+    row.map(|row| {
+        Ok(ReminderTask {
+            user_id: row.user_id,
+            email: row.email,
+            frequency: ReminderFrequency::parse(&row.reminder_frequency)
+                .map_err(anyhow::Error::msg)?,
+        })
+    })
+    .transpose()
+}
+
+async fn fetch_outstanding_debts(
+    transaction: &mut Transaction<'_, Postgres>,
+    task: &ReminderTask,
+) -> Result<Vec<OutstandingDebt>, anyhow::Error> {
+    let debts = sqlx::query!(
+        r#"
+        SELECT users.username AS creditor_name, debts.amount, debts.currency,
+            debts.description, debts.due_date
+        FROM debts
+        JOIN users ON users.user_id = debts.creditor_id
+        WHERE debts.debtor_id = $1 AND debts.status <> $2
+            AND ($3 = false OR debts.due_date < CURRENT_DATE)
+        ORDER BY debts.due_date NULLS LAST, debts.created_at
+        "#,
+        task.user_id,
+        DebtStatus::Paid.to_string(),
+        task.frequency == ReminderFrequency::WhenOverdue
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch outstanding debts.")?
+    .into_iter()
+    .map(|row| OutstandingDebt {
+        creditor_name: row.creditor_name,
+        amount: row.amount,
+        currency: row.currency,
+        description: row.description,
+        due_date: row.due_date,
+    })
+    .collect();
+    Ok(debts)
+}
+
+async fn send_digest(
+    email_client: &EmailClient,
+    recipient: &str,
+    debts: &[OutstandingDebt],
+) -> Result<(), reqwest::Error> {
+    let today = Utc::now().date_naive();
+    let lines = debts
+        .iter()
+        .map(|debt| {
+            let due = match debt.due_date {
+                Some(due_date) if due_date < today => format!(", overdue since {}", due_date),
+                Some(due_date) => format!(", due {}", due_date),
+                None => String::new(),
+            };
+            format!(
+                "{} {} to {} for {}{}",
+                debt.amount, debt.currency, debt.creditor_name, debt.description, due
+            )
+        })
+        .collect::<Vec<_>>();
+    let plain_body = format!(
+        "You have {} outstanding debt(s):\n{}",
+        lines.len(),
+        lines.join("\n")
+    );
+    let html_body = format!(
+        "You have {} outstanding debt(s):<ul>{}</ul>",
+        lines.len(),
+        lines
+            .iter()
+            .map(|line| format!("<li>{}</li>", escape_html(line)))
+            .collect::<String>()
+    );
+    email_client
+        .send_email(recipient, "Your outstanding debts", &html_body, &plain_body)
+        .await
+}
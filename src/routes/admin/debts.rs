@@ -0,0 +1,80 @@
+use crate::debts::GetDebtJSONResponse;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use rust_decimal::prelude::ToPrimitive;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(thiserror::Error)]
+pub enum GetDebtError {
+    #[error("The debt does not exist.")]
+    NotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetDebtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetDebtError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetDebtError::NotFound => StatusCode::NOT_FOUND,
+            GetDebtError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/debts/{debt_id}",
+    tag = "admin",
+    params(("debt_id" = Uuid, Path, description = "Debt ID")),
+    responses(
+        (status = 200, body = GetDebtJSONResponse),
+        (status = 401, description = "Not logged in"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "No such debt"),
+    ),
+)]
+#[tracing::instrument(name = "Getting any debt by ID", skip(db_pool))]
+pub async fn get_debt(
+    debt_id: web::Path<Uuid>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<GetDebtJSONResponse>, GetDebtError> {
+    let row = sqlx::query!(
+        "SELECT debt_id, users_1.user_id as creditor_id, users_1.username as creditor_name, \
+        users_2.user_id as debtor_id, users_2.username as debtor_name, amount, currency, description, debts.status, debts.due_date, debts.created_at \
+        FROM debts JOIN users users_1 ON debts.creditor_id = users_1.user_id \
+        JOIN users users_2 ON debts.debtor_id = users_2.user_id \
+        WHERE debt_id = $1",
+        *debt_id
+    )
+    .fetch_optional(db_pool.get_ref())
+    .await
+    .context("Failed to fetch the debt from the database.")?
+    .ok_or(GetDebtError::NotFound)?;
+
+    Ok(web::Json(GetDebtJSONResponse {
+        debt_id: row.debt_id.to_string(),
+        creditor_id: row.creditor_id.to_string(),
+        creditor_name: row.creditor_name,
+        debtor_id: row.debtor_id.to_string(),
+        debtor_name: row.debtor_name,
+        amount: row
+            .amount
+            .to_f64()
+            .context("Failed to convert the debt amount.")?,
+        currency: row.currency,
+        description: row.description,
+        status: row.status,
+        due_date: row.due_date.map(|due_date| due_date.to_string()),
+        created_at: row.created_at.to_string(),
+    }))
+}
@@ -0,0 +1,2 @@
+pub mod debts;
+pub mod users;
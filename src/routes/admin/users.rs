@@ -0,0 +1,126 @@
+use crate::authentication::UserId;
+use crate::domain::UserStatus;
+use crate::utils::{e500, error_chain_fmt};
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct AdminUserJSONResponse {
+    pub user_id: String,
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub status: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/users",
+    tag = "admin",
+    responses(
+        (status = 200, body = Vec<AdminUserJSONResponse>),
+        (status = 401, description = "Not logged in"),
+        (status = 403, description = "Not an admin"),
+    ),
+)]
+#[tracing::instrument(name = "Listing all users", skip(db_pool))]
+pub async fn list_users(
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<Vec<AdminUserJSONResponse>>, actix_web::Error> {
+    let users = sqlx::query!(
+        r#"
+        SELECT user_id, username, email, role, status
+        FROM users
+        ORDER BY username
+        "#
+    )
+    .fetch_all(db_pool.get_ref())
+    .await
+    .context("Failed to fetch users from the database.")
+    .map_err(e500)?
+    .into_iter()
+    .map(|row| AdminUserJSONResponse {
+        user_id: row.user_id.to_string(),
+        username: row.username,
+        email: row.email,
+        role: row.role,
+        status: row.status,
+    })
+    .collect::<Vec<_>>();
+    Ok(web::Json(users))
+}
+
+#[derive(thiserror::Error)]
+pub enum DisableUserError {
+    #[error("Admins cannot disable their own account.")]
+    SelfDisable,
+    #[error("The user does not exist.")]
+    NotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DisableUserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DisableUserError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DisableUserError::SelfDisable => StatusCode::BAD_REQUEST,
+            DisableUserError::NotFound => StatusCode::NOT_FOUND,
+            DisableUserError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/disable",
+    tag = "admin",
+    params(("user_id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "The user was disabled"),
+        (status = 400, description = "Admins cannot disable themselves"),
+        (status = 401, description = "Not logged in"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "No such user"),
+    ),
+)]
+#[tracing::instrument(name = "Disabling a user", skip(db_pool))]
+pub async fn disable_user(
+    target_user_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, DisableUserError> {
+    if *target_user_id == **user_id {
+        return Err(DisableUserError::SelfDisable);
+    }
+
+    // Bumping the session version logs the user out everywhere.
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET status = $1, session_version = session_version + 1
+        WHERE user_id = $2
+        "#,
+        UserStatus::Disabled.to_string(),
+        *target_user_id
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to disable the user.")?;
+
+    if result.rows_affected() == 0 {
+        return Err(DisableUserError::NotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
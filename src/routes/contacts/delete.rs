@@ -0,0 +1,71 @@
+use crate::authentication::UserId;
+use crate::domain::ContactStatus;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(thiserror::Error)]
+pub enum RemoveContactError {
+    #[error("The contact does not exist.")]
+    NotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RemoveContactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RemoveContactError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RemoveContactError::NotFound => StatusCode::NOT_FOUND,
+            RemoveContactError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Remove an accepted contact, or withdraw a request the user sent.
+#[utoipa::path(
+    delete,
+    path = "/contacts/{user_id}",
+    tag = "contacts",
+    params(("user_id" = Uuid, Path, description = "The contact's user ID")),
+    responses(
+        (status = 200, description = "The contact was removed"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such contact"),
+    ),
+)]
+#[tracing::instrument(name = "Removing a contact", skip(db_pool))]
+pub async fn remove_contact(
+    other_user_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, RemoveContactError> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM contacts
+        WHERE (requester_id = $1 AND addressee_id = $2)
+            OR (requester_id = $2 AND addressee_id = $1 AND status = $3)
+        "#,
+        **user_id,
+        *other_user_id,
+        ContactStatus::Accepted.to_string()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to remove the contact.")?;
+
+    if result.rows_affected() == 0 {
+        return Err(RemoveContactError::NotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
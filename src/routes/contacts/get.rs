@@ -0,0 +1,73 @@
+use crate::authentication::UserId;
+use crate::domain::ContactStatus;
+use crate::utils::e500;
+use actix_web::web;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct GetContactJSONResponse {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub since: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/contacts",
+    tag = "contacts",
+    responses(
+        (status = 200, body = Vec<GetContactJSONResponse>),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting list of contacts by User ID", skip(db_pool))]
+pub async fn get_contacts(
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<Vec<GetContactJSONResponse>>, actix_web::Error> {
+    let contacts = fetch_contacts(**user_id, db_pool.get_ref())
+        .await
+        .map_err(e500)?;
+    Ok(web::Json(contacts))
+}
+
+#[tracing::instrument(name = "Fetching contacts by User ID", skip(pool))]
+pub async fn fetch_contacts(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Vec<GetContactJSONResponse>, anyhow::Error> {
+    let contacts = sqlx::query!(
+        r#"
+        SELECT users.user_id, users.username, users.display_name, contacts.accepted_at
+        FROM contacts
+        JOIN users ON users.user_id = CASE
+            WHEN contacts.requester_id = $1 THEN contacts.addressee_id
+            ELSE contacts.requester_id
+        END
+        WHERE (contacts.requester_id = $1 OR contacts.addressee_id = $1)
+            AND contacts.status = $2
+        ORDER BY users.username
+        "#,
+        user_id,
+        ContactStatus::Accepted.to_string()
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch contacts from the database.")?
+    .into_iter()
+    .map(|row| GetContactJSONResponse {
+        user_id: row.user_id.to_string(),
+        username: row.username,
+        display_name: row.display_name,
+        since: row
+            .accepted_at
+            .map(|accepted_at| accepted_at.to_string())
+            .unwrap_or_default(),
+    })
+    .collect();
+    Ok(contacts)
+}
@@ -0,0 +1,32 @@
+pub mod delete;
+pub mod get;
+pub mod requests;
+
+use crate::domain::ContactStatus;
+use anyhow::Context;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "Check whether two users are contacts", skip(executor))]
+pub async fn are_contacts(
+    user_id: Uuid,
+    other_user_id: Uuid,
+    executor: impl PgExecutor<'_>,
+) -> Result<bool, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT 1 AS contact
+        FROM contacts
+        WHERE ((requester_id = $1 AND addressee_id = $2)
+            OR (requester_id = $2 AND addressee_id = $1))
+            AND status = $3
+        "#,
+        user_id,
+        other_user_id,
+        ContactStatus::Accepted.to_string()
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to check whether two users are contacts.")?;
+    Ok(row.is_some())
+}
@@ -0,0 +1,256 @@
+use crate::authentication::UserId;
+use crate::domain::{ContactStatus, UserReference};
+use crate::utils::{e500, error_chain_fmt};
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SendContactRequestJsonData {
+    user: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct ContactRequestJSONResponse {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub created_at: String,
+}
+
+/// A request the user sent. The addressee is shown as the user entered them
+/// until they accept, so that a request reveals nothing about who they are.
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct OutgoingContactRequestJSONResponse {
+    pub user: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct GetContactRequestsJSONResponse {
+    pub incoming: Vec<ContactRequestJSONResponse>,
+    pub outgoing: Vec<OutgoingContactRequestJSONResponse>,
+}
+
+#[derive(thiserror::Error)]
+pub enum ContactRequestError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("There is already a contact or a pending request between these users.")]
+    AlreadyExists,
+    #[error("The contact request does not exist.")]
+    RequestNotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ContactRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ContactRequestError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ContactRequestError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ContactRequestError::RequestNotFound => StatusCode::NOT_FOUND,
+            ContactRequestError::AlreadyExists => StatusCode::CONFLICT,
+            ContactRequestError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/contacts/requests",
+    tag = "contacts",
+    request_body = SendContactRequestJsonData,
+    responses(
+        (status = 200, description = "The request was sent, if the user exists"),
+        (status = 400, description = "Invalid user"),
+        (status = 401, description = "Not logged in"),
+        (status = 409, description = "Already a contact or already requested"),
+    ),
+)]
+/// Unknown users get the same answer as known ones rather than a 404.
+#[tracing::instrument(name = "Sending a contact request", skip(body, db_pool))]
+pub async fn send_contact_request(
+    body: web::Json<SendContactRequestJsonData>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ContactRequestError> {
+    let reference =
+        UserReference::parse(&body.user).map_err(ContactRequestError::ValidationError)?;
+    let addressee_id = match reference.resolve(db_pool.get_ref()).await? {
+        Some(addressee_id) => *addressee_id.as_ref(),
+        None => return Ok(HttpResponse::Ok().finish()),
+    };
+    if addressee_id == **user_id {
+        return Err(ContactRequestError::ValidationError(
+            "You cannot add yourself as a contact.".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO contacts (requester_id, addressee_id, status, created_at, requested_as)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        **user_id,
+        addressee_id,
+        ContactStatus::Pending.to_string(),
+        Utc::now(),
+        reference.to_string()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(e) if e.is_unique_violation() => ContactRequestError::AlreadyExists,
+        e => ContactRequestError::UnexpectedError(
+            anyhow::Error::new(e).context("Failed to insert the contact request."),
+        ),
+    })?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    post,
+    path = "/contacts/requests/{user_id}/accept",
+    tag = "contacts",
+    params(("user_id" = Uuid, Path, description = "The requester's user ID")),
+    responses(
+        (status = 200, description = "The request was accepted"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such request"),
+    ),
+)]
+#[tracing::instrument(name = "Accepting a contact request", skip(db_pool))]
+pub async fn accept_contact_request(
+    requester_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ContactRequestError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE contacts
+        SET status = $1, accepted_at = $2
+        WHERE requester_id = $3 AND addressee_id = $4 AND status = $5
+        "#,
+        ContactStatus::Accepted.to_string(),
+        Utc::now(),
+        *requester_id,
+        **user_id,
+        ContactStatus::Pending.to_string()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to accept the contact request.")?;
+
+    if result.rows_affected() == 0 {
+        return Err(ContactRequestError::RequestNotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    post,
+    path = "/contacts/requests/{user_id}/decline",
+    tag = "contacts",
+    params(("user_id" = Uuid, Path, description = "The requester's user ID")),
+    responses(
+        (status = 200, description = "The request was declined"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such request"),
+    ),
+)]
+#[tracing::instrument(name = "Declining a contact request", skip(db_pool))]
+pub async fn decline_contact_request(
+    requester_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ContactRequestError> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM contacts
+        WHERE requester_id = $1 AND addressee_id = $2 AND status = $3
+        "#,
+        *requester_id,
+        **user_id,
+        ContactStatus::Pending.to_string()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to decline the contact request.")?;
+
+    if result.rows_affected() == 0 {
+        return Err(ContactRequestError::RequestNotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/contacts/requests",
+    tag = "contacts",
+    responses(
+        (status = 200, body = GetContactRequestsJSONResponse),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting pending contact requests", skip(db_pool))]
+pub async fn get_contact_requests(
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<GetContactRequestsJSONResponse>, actix_web::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT contacts.requester_id, users.user_id, users.username, users.display_name,
+            COALESCE(contacts.requested_as, users.user_id::text) AS "requested_as!",
+            contacts.created_at
+        FROM contacts
+        JOIN users ON users.user_id = CASE
+            WHEN contacts.requester_id = $1 THEN contacts.addressee_id
+            ELSE contacts.requester_id
+        END
+        WHERE (contacts.requester_id = $1 OR contacts.addressee_id = $1)
+            AND contacts.status = $2
+        ORDER BY contacts.created_at
+        "#,
+        **user_id,
+        ContactStatus::Pending.to_string()
+    )
+    .fetch_all(db_pool.get_ref())
+    .await
+    .context("Failed to fetch contact requests from the database.")
+    .map_err(e500)?;
+
+    let mut requests = GetContactRequestsJSONResponse {
+        incoming: vec![],
+        outgoing: vec![],
+    };
+    for row in rows {
+        if row.requester_id == **user_id {
+            requests.outgoing.push(OutgoingContactRequestJSONResponse {
+                user: row.requested_as,
+                created_at: row.created_at.to_string(),
+            });
+        } else {
+            requests.incoming.push(ContactRequestJSONResponse {
+                user_id: row.user_id.to_string(),
+                username: row.username,
+                display_name: row.display_name,
+                created_at: row.created_at.to_string(),
+            });
+        }
+    }
+    Ok(web::Json(requests))
+}
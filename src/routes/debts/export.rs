@@ -0,0 +1,356 @@
+use crate::authentication::UserId;
+use crate::debts::GetDebtJSONResponse;
+use crate::domain::{DebtCurrency, DebtStatus};
+use crate::utils::{error_chain_fmt, escape_html};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::http::StatusCode;
+use actix_web::web::{self, Bytes};
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use rust_decimal::prelude::*;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// How many encoded rows may wait for a slow client before the query is paused.
+const CHANNEL_CAPACITY: usize = 32;
+const CSV_COLUMNS: [&str; 11] = [
+    "debt_id",
+    "creditor_id",
+    "creditor_name",
+    "debtor_id",
+    "debtor_name",
+    "amount",
+    "currency",
+    "description",
+    "status",
+    "due_date",
+    "created_at",
+];
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportDebtsQuery {
+    /// `csv`, `json` or `ofx`.
+    format: String,
+    status: Option<String>,
+    /// Required for `ofx`, since an OFX statement has a single currency.
+    currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Json,
+    Ofx,
+}
+
+impl ExportFormat {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "ofx" => Ok(Self::Ofx),
+            _ => Err(format!("{} is not a valid export format", s)),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Ofx => "ofx",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+            Self::Ofx => "application/x-ofx",
+        }
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum ExportDebtsError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ExportDebtsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ExportDebtsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ExportDebtsError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ExportDebtsError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+struct ExportFilter {
+    user_id: Uuid,
+    status: Option<DebtStatus>,
+    currency: Option<DebtCurrency>,
+}
+
+/// Stream the user's debts as CSV, JSON or OFX. Rows are encoded as they come
+/// out of Postgres and handed to the response through a bounded channel, so
+/// the export never holds more than a few rows in memory.
+#[utoipa::path(
+    get,
+    path = "/debts/export",
+    tag = "debts",
+    params(ExportDebtsQuery),
+    responses(
+        (
+            status = 200,
+            description = "The debts as CSV, JSON or OFX",
+            content(
+                (String = "text/csv"),
+                (Vec<GetDebtJSONResponse> = "application/json"),
+                (String = "application/x-ofx"),
+            ),
+        ),
+        (status = 400, description = "Invalid format or filter, or an OFX export without a currency"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Exporting debts", skip(query, db_pool))]
+pub async fn export_debts(
+    query: web::Query<ExportDebtsQuery>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ExportDebtsError> {
+    let query = query.into_inner();
+    let format = ExportFormat::parse(&query.format).map_err(ExportDebtsError::ValidationError)?;
+    let filter = ExportFilter {
+        user_id: **user_id,
+        status: query
+            .status
+            .as_deref()
+            .map(DebtStatus::parse)
+            .transpose()
+            .map_err(ExportDebtsError::ValidationError)?,
+        currency: query
+            .currency
+            .map(DebtCurrency::parse)
+            .transpose()
+            .map_err(ExportDebtsError::ValidationError)?,
+    };
+    if matches!(format, ExportFormat::Ofx) && filter.currency.is_none() {
+        return Err(ExportDebtsError::ValidationError(
+            "OFX exports need a currency.".into(),
+        ));
+    }
+    let currency = filter
+        .currency
+        .as_ref()
+        .map(|currency| currency.to_string())
+        .unwrap_or_default();
+    let encoder = Encoder::new(format, filter.user_id, currency);
+
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    let pool = db_pool.get_ref().clone();
+    tokio::spawn(async move {
+        if let Err(e) = write_export(&pool, &filter, encoder, &sender).await {
+            tracing::error!(error.cause_chain = ?e, "Failed to export debts.");
+            let _ = sender.send(Err(e)).await;
+        }
+    });
+    let body = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!(
+                "debts.{}",
+                format.extension()
+            ))],
+        })
+        .streaming(body))
+}
+
+/// Returns early without an error once the client has gone away.
+async fn write_export(
+    pool: &PgPool,
+    filter: &ExportFilter,
+    mut encoder: Encoder,
+    sender: &mpsc::Sender<Result<Bytes, anyhow::Error>>,
+) -> Result<(), anyhow::Error> {
+    if sender.send(Ok(encoder.header())).await.is_err() {
+        return Ok(());
+    }
+    let mut rows = sqlx::query!(
+        r#"
+        SELECT debt_id, users_1.user_id AS creditor_id, users_1.username AS creditor_name,
+            users_2.user_id AS debtor_id, users_2.username AS debtor_name, amount, currency,
+            description, debts.status, debts.due_date, debts.created_at
+        FROM debts
+        JOIN users users_1 ON debts.creditor_id = users_1.user_id
+        JOIN users users_2 ON debts.debtor_id = users_2.user_id
+        WHERE (creditor_id = $1 OR debtor_id = $1)
+            AND ($2::text IS NULL OR debts.status = $2)
+            AND ($3::text IS NULL OR debts.currency = $3)
+        ORDER BY debts.created_at
+        "#,
+        filter.user_id,
+        filter.status.as_ref().map(|status| status.to_string()),
+        filter
+            .currency
+            .as_ref()
+            .map(|currency| currency.to_string())
+    )
+    .fetch(pool);
+
+    while let Some(row) = rows
+        .try_next()
+        .await
+        .context("Failed to fetch debts from the database.")?
+    {
+        let debt = GetDebtJSONResponse {
+            debt_id: row.debt_id.to_string(),
+            creditor_id: row.creditor_id.to_string(),
+            creditor_name: row.creditor_name,
+            debtor_id: row.debtor_id.to_string(),
+            debtor_name: row.debtor_name,
+            amount: row
+                .amount
+                .to_f64()
+                .context("Failed to convert big decimal")?,
+            currency: row.currency,
+            description: row.description,
+            status: row.status,
+            due_date: row.due_date.map(|due_date| due_date.to_string()),
+            created_at: row.created_at.to_string(),
+        };
+        let chunk = encoder.row(&debt, row.amount, row.created_at)?;
+        if sender.send(Ok(chunk)).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    let _ = sender.send(Ok(encoder.footer())).await;
+    Ok(())
+}
+
+struct Encoder {
+    format: ExportFormat,
+    user_id: Uuid,
+    /// The currency of an OFX statement, which only lists debts in it.
+    currency: String,
+    rows: usize,
+    balance: Decimal,
+}
+
+impl Encoder {
+    fn new(format: ExportFormat, user_id: Uuid, currency: String) -> Self {
+        Self {
+            format,
+            user_id,
+            currency,
+            rows: 0,
+            balance: Decimal::ZERO,
+        }
+    }
+
+    fn header(&self) -> Bytes {
+        match self.format {
+            ExportFormat::Csv => Bytes::from(format!("{}\n", CSV_COLUMNS.join(","))),
+            ExportFormat::Json => Bytes::from_static(b"["),
+            ExportFormat::Ofx => Bytes::from(format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+                <?OFX OFXHEADER=\"200\" VERSION=\"220\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n\
+                <OFX>\n\
+                <SIGNONMSGSRSV1><SONRS>{status}<DTSERVER>{now}</DTSERVER><LANGUAGE>ENG</LANGUAGE></SONRS></SIGNONMSGSRSV1>\n\
+                <BANKMSGSRSV1><STMTTRNRS><TRNUID>0</TRNUID>{status}<STMTRS>\n\
+                <CURDEF>{currency}</CURDEF>\n\
+                <BANKACCTFROM><BANKID>debt-tracer</BANKID><ACCTID>{user_id}</ACCTID><ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>\n\
+                <BANKTRANLIST><DTSTART>19700101000000</DTSTART><DTEND>{now}</DTEND>\n",
+                status = "<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>",
+                now = ofx_date(Utc::now()),
+                currency = self.currency,
+                user_id = self.user_id,
+            )),
+        }
+    }
+
+    fn row(
+        &mut self,
+        debt: &GetDebtJSONResponse,
+        amount: Decimal,
+        created_at: DateTime<Utc>,
+    ) -> Result<Bytes, anyhow::Error> {
+        self.rows += 1;
+        let chunk = match self.format {
+            ExportFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(vec![]);
+                writer
+                    .serialize(debt)
+                    .context("Failed to encode a CSV row.")?;
+                writer.into_inner().context("Failed to encode a CSV row.")?
+            }
+            ExportFormat::Json => {
+                let mut chunk = if self.rows > 1 { b",".to_vec() } else { vec![] };
+                serde_json::to_writer(&mut chunk, debt).context("Failed to encode a debt.")?;
+                chunk
+            }
+            ExportFormat::Ofx => {
+                // Money owed to the user flows in, money they owe flows out.
+                let is_credit = debt.creditor_id == self.user_id.to_string();
+                let (transaction_type, amount, counterparty) = if is_credit {
+                    ("CREDIT", amount, &debt.debtor_name)
+                } else {
+                    ("DEBIT", -amount, &debt.creditor_name)
+                };
+                self.balance += amount;
+                format!(
+                    "<STMTTRN><TRNTYPE>{}</TRNTYPE><DTPOSTED>{}</DTPOSTED><TRNAMT>{}</TRNAMT>\
+                    <FITID>{}</FITID><NAME>{}</NAME><MEMO>{}</MEMO></STMTTRN>\n",
+                    transaction_type,
+                    ofx_date(created_at),
+                    amount,
+                    debt.debt_id,
+                    escape_html(counterparty),
+                    escape_html(&debt.description)
+                )
+                .into_bytes()
+            }
+        };
+        Ok(Bytes::from(chunk))
+    }
+
+    fn footer(&self) -> Bytes {
+        match self.format {
+            ExportFormat::Csv => Bytes::new(),
+            ExportFormat::Json => Bytes::from_static(b"]"),
+            ExportFormat::Ofx => Bytes::from(format!(
+                "</BANKTRANLIST>\n\
+                <LEDGERBAL><BALAMT>{}</BALAMT><DTASOF>{}</DTASOF></LEDGERBAL>\n\
+                </STMTRS></STMTTRNRS></BANKMSGSRSV1>\n\
+                </OFX>\n",
+                self.balance,
+                ofx_date(Utc::now())
+            )),
+        }
+    }
+}
+
+fn ofx_date(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%d%H%M%S").to_string()
+}
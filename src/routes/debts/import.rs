@@ -0,0 +1,230 @@
+use crate::authentication::UserId;
+use crate::debts::{ensure_counterparty_accepts, insert_debt, CreateDebtError};
+use crate::domain::{
+    DebtAmount, DebtCurrency, DebtDescription, DebtStatus, DebtUserId, NewDebt, UserReference,
+};
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+pub(super) const MAX_ROWS: usize = 1000;
+const COLUMNS: [&str; 6] = [
+    "counterparty",
+    "amount",
+    "currency",
+    "description",
+    "date",
+    "status",
+];
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportDebtsQuery {
+    /// Validate the rows without inserting anything.
+    pub(crate) dry_run: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct CsvRow {
+    counterparty: String,
+    amount: String,
+    currency: String,
+    description: String,
+    date: String,
+    status: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct ImportDebtsJSONResponse {
+    pub dry_run: bool,
+    pub valid_rows: usize,
+    pub errors: Vec<ImportRowErrorJSONResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct ImportRowErrorJSONResponse {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum ImportDebtsError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ImportDebtsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ImportDebtsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ImportDebtsError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ImportDebtsError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Import historical debts from a CSV with a header row. A positive amount is
+/// owed to the importer by the counterparty, a negative one by the importer.
+/// Valid rows are inserted in a single transaction unless `dry_run` is set,
+/// and every invalid row is reported by its 1-based position. Imported debts
+/// are history, so nobody is notified about them. A status in the importer's
+/// favour, `unpaid` when they are owed or `paid` when they owe, is imported as
+/// `pending` for the counterparty to confirm.
+#[utoipa::path(
+    post,
+    path = "/debts/import",
+    tag = "debts",
+    params(ImportDebtsQuery),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 200, body = ImportDebtsJSONResponse),
+        (status = 400, description = "Invalid CSV"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Importing debts", skip(body, query, db_pool))]
+pub async fn import_debts(
+    body: String,
+    query: web::Query<ImportDebtsQuery>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<ImportDebtsJSONResponse>, ImportDebtsError> {
+    let user_id = **user_id;
+    let dry_run = query.dry_run.unwrap_or(false);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| ImportDebtsError::ValidationError(format!("Invalid CSV header: {}", e)))?
+        .clone();
+    if let Some(column) = COLUMNS
+        .iter()
+        .find(|column| !headers.iter().any(|header| header == **column))
+    {
+        return Err(ImportDebtsError::ValidationError(format!(
+            "The CSV is missing the {} column.",
+            column
+        )));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let mut debts = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        if index >= MAX_ROWS {
+            return Err(ImportDebtsError::ValidationError(format!(
+                "At most {} rows can be imported at once.",
+                MAX_ROWS
+            )));
+        }
+        let row = record
+            .and_then(|record| record.deserialize::<CsvRow>(Some(&headers)))
+            .map_err(|e| CreateDebtError::ValidationError(format!("Invalid CSV row: {}", e)));
+        let debt = match row {
+            Ok(row) => parse_row(row, user_id, &mut transaction).await,
+            Err(e) => Err(e),
+        };
+        match debt {
+            Ok(debt) => debts.push(debt),
+            Err(CreateDebtError::UnexpectedError(e)) => return Err(e.into()),
+            Err(e) => errors.push(ImportRowErrorJSONResponse {
+                row: index + 1,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    if !dry_run {
+        for (new_debt, created_at) in &debts {
+            insert_debt(&mut transaction, Uuid::new_v4(), new_debt, *created_at).await?;
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to import debts.")?;
+    }
+
+    Ok(web::Json(ImportDebtsJSONResponse {
+        dry_run,
+        valid_rows: debts.len(),
+        errors,
+    }))
+}
+
+async fn parse_row(
+    row: CsvRow,
+    user_id: Uuid,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<(NewDebt, DateTime<Utc>), CreateDebtError> {
+    let counterparty =
+        UserReference::parse(&row.counterparty).map_err(CreateDebtError::ValidationError)?;
+    let counterparty_id = counterparty
+        .resolve(&mut **transaction)
+        .await?
+        .ok_or(CreateDebtError::NotAContact)?;
+    if *counterparty_id.as_ref() == user_id {
+        return Err(CreateDebtError::ValidationError(
+            "You cannot import a debt with yourself.".to_string(),
+        ));
+    }
+    ensure_counterparty_accepts(user_id, &counterparty_id, transaction).await?;
+
+    let amount = row.amount.parse::<f64>().map_err(|_| {
+        CreateDebtError::ValidationError(format!("{} is not a valid amount.", row.amount))
+    })?;
+    let importer_is_creditor = amount >= 0.0;
+    let (creditor_id, debtor_id) = if importer_is_creditor {
+        (DebtUserId::from(user_id), counterparty_id)
+    } else {
+        (counterparty_id, DebtUserId::from(user_id))
+    };
+    let amount = DebtAmount::parse(amount.abs()).map_err(CreateDebtError::ValidationError)?;
+    let currency = DebtCurrency::parse(row.currency).map_err(CreateDebtError::ValidationError)?;
+    let description =
+        DebtDescription::parse(row.description).map_err(CreateDebtError::ValidationError)?;
+    let created_at = parse_date(&row.date).map_err(CreateDebtError::ValidationError)?;
+    // The counterparty never saw these debts, so a status in the importer's
+    // favour is left for them to confirm.
+    let status = match DebtStatus::parse(&row.status).map_err(CreateDebtError::ValidationError)? {
+        DebtStatus::Unpaid if importer_is_creditor => DebtStatus::Pending,
+        DebtStatus::Paid if !importer_is_creditor => DebtStatus::Pending,
+        status => status,
+    };
+
+    let new_debt = NewDebt {
+        debtor_id,
+        creditor_id,
+        amount,
+        currency,
+        description,
+        due_date: None,
+        status,
+    };
+    Ok((new_debt, created_at))
+}
+
+/// Imported debts are dated at midnight UTC of the day they were recorded.
+pub(super) fn parse_date(s: &str) -> Result<DateTime<Utc>, String> {
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| format!("{} is not a valid date, expected YYYY-MM-DD", s))?;
+    Ok(date.and_time(NaiveTime::MIN).and_utc())
+}
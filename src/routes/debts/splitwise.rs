@@ -0,0 +1,344 @@
+use super::import::{
+    parse_date, ImportDebtsError, ImportDebtsQuery, ImportRowErrorJSONResponse, MAX_ROWS,
+};
+use crate::authentication::UserId;
+use crate::debts::{ensure_counterparty_accepts, insert_debt, resolve_user, CreateDebtError};
+use crate::domain::{DebtAmount, DebtCurrency, DebtDescription, DebtStatus, DebtUserId, NewDebt};
+use crate::email_client::EmailClient;
+use crate::routes::signup::invite::send_invite_email;
+use crate::startup::FrontendBaseUrl;
+use actix_web::web;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const FIXED_COLUMNS: [&str; 5] = ["Date", "Description", "Category", "Cost", "Currency"];
+const TOTAL_BALANCE: &str = "Total balance";
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SplitwiseImportJsonData {
+    csv: String,
+    /// Splitwise member names mapped to an email, username or user ID.
+    members: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct SplitwiseImportJSONResponse {
+    pub dry_run: bool,
+    pub debts: usize,
+    pub unmapped_members: Vec<String>,
+    pub unsupported_currencies: Vec<String>,
+    pub skipped: Vec<SplitwiseSkippedDebtJSONResponse>,
+    pub errors: Vec<ImportRowErrorJSONResponse>,
+}
+
+/// A debt between two other members, which is not imported.
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct SplitwiseSkippedDebtJSONResponse {
+    pub row: usize,
+    pub creditor: String,
+    pub debtor: String,
+    pub amount: f64,
+    pub currency: String,
+}
+
+/// Import a Splitwise group export. Every member column holds that member's
+/// net balance for the expense, so each expense becomes debts from the members
+/// with a negative balance to those with a positive one. Only the debts the
+/// importing user is a party to are imported; the others are reported as
+/// skipped. Members mapped to an unknown email are invited as placeholder
+/// users; unmapped members and unsupported currencies are reported, and so is
+/// every row they appear in.
+#[utoipa::path(
+    post,
+    path = "/debts/import/splitwise",
+    tag = "debts",
+    params(ImportDebtsQuery),
+    request_body = SplitwiseImportJsonData,
+    responses(
+        (status = 200, body = SplitwiseImportJSONResponse),
+        (status = 400, description = "Not a Splitwise export"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(
+    name = "Importing debts from Splitwise",
+    skip(body, query, db_pool, email_client, frontend_base_url)
+)]
+pub async fn import_splitwise(
+    body: web::Json<SplitwiseImportJsonData>,
+    query: web::Query<ImportDebtsQuery>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<EmailClient>,
+    frontend_base_url: web::Data<FrontendBaseUrl>,
+) -> Result<web::Json<SplitwiseImportJSONResponse>, ImportDebtsError> {
+    let user_id = **user_id;
+    let dry_run = query.dry_run.unwrap_or(false);
+    let body = body.into_inner();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(body.csv.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| ImportDebtsError::ValidationError(format!("Invalid CSV header: {}", e)))?
+        .clone();
+    if headers.len() < FIXED_COLUMNS.len() || headers.iter().zip(FIXED_COLUMNS).any(|(a, b)| a != b)
+    {
+        return Err(ImportDebtsError::ValidationError(
+            "The CSV is not a Splitwise export.".to_string(),
+        ));
+    }
+    let names: Vec<String> = headers
+        .iter()
+        .skip(FIXED_COLUMNS.len())
+        .map(str::to_string)
+        .collect();
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let mut invites = Vec::new();
+    let mut members = Vec::new();
+    let mut unmapped_members = Vec::new();
+    for name in &names {
+        let member = match body.members.get(name) {
+            Some(reference) => Some(
+                resolve_user(reference, &mut transaction, &mut invites)
+                    .await
+                    .map_err(|e| match e {
+                        CreateDebtError::UnexpectedError(e) => ImportDebtsError::UnexpectedError(e),
+                        e => ImportDebtsError::ValidationError(format!("{}: {}", name, e)),
+                    })?,
+            ),
+            None => {
+                unmapped_members.push(name.clone());
+                None
+            }
+        };
+        members.push(member);
+    }
+
+    let mut debts = Vec::new();
+    let mut unsupported_currencies = Vec::new();
+    let mut skipped = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        if index >= MAX_ROWS {
+            return Err(ImportDebtsError::ValidationError(format!(
+                "At most {} rows can be imported at once.",
+                MAX_ROWS
+            )));
+        }
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(ImportRowErrorJSONResponse {
+                    row: index + 1,
+                    message: format!("Invalid CSV row: {}", e),
+                });
+                continue;
+            }
+        };
+        if record.iter().all(str::is_empty) || record.get(1) == Some(TOTAL_BALANCE) {
+            continue;
+        }
+        let currency = record.get(4).unwrap_or_default();
+        if DebtCurrency::parse(currency.to_string()).is_err()
+            && !unsupported_currencies.iter().any(|c| c == currency)
+        {
+            unsupported_currencies.push(currency.to_string());
+        }
+        match convert_expense(&record, &names, &members, user_id, &mut transaction).await {
+            Ok((expense_debts, expense_skipped)) => {
+                debts.extend(expense_debts);
+                skipped.extend(
+                    expense_skipped
+                        .into_iter()
+                        .map(
+                            |(creditor, debtor, amount)| SplitwiseSkippedDebtJSONResponse {
+                                row: index + 1,
+                                creditor,
+                                debtor,
+                                amount: amount.to_f64().unwrap_or_default(),
+                                currency: currency.to_string(),
+                            },
+                        ),
+                );
+            }
+            Err(CreateDebtError::UnexpectedError(e)) => return Err(e.into()),
+            Err(e) => errors.push(ImportRowErrorJSONResponse {
+                row: index + 1,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    if !dry_run {
+        for (new_debt, created_at) in &debts {
+            insert_debt(&mut transaction, Uuid::new_v4(), new_debt, *created_at).await?;
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to import debts.")?;
+
+        for invite in &invites {
+            send_invite_email(&email_client, invite, &frontend_base_url.0)
+                .await
+                .context("Failed to send an invite email.")?;
+        }
+    }
+
+    Ok(web::Json(SplitwiseImportJSONResponse {
+        dry_run,
+        debts: debts.len(),
+        unmapped_members,
+        unsupported_currencies,
+        skipped,
+        errors,
+    }))
+}
+
+/// The debts of an expense, and the `(creditor, debtor, amount)` pairings
+/// between other members that were left out.
+type ExpenseDebts = (
+    Vec<(NewDebt, DateTime<Utc>)>,
+    Vec<(String, String, Decimal)>,
+);
+
+async fn convert_expense(
+    record: &csv::StringRecord,
+    names: &[String],
+    members: &[Option<DebtUserId>],
+    user_id: Uuid,
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<ExpenseDebts, CreateDebtError> {
+    let field = |i: usize| record.get(i).unwrap_or_default();
+    let created_at = parse_date(field(0)).map_err(CreateDebtError::ValidationError)?;
+    let description =
+        DebtDescription::parse(field(1).to_string()).map_err(CreateDebtError::ValidationError)?;
+    let currency =
+        DebtCurrency::parse(field(4).to_string()).map_err(CreateDebtError::ValidationError)?;
+
+    let mut creditors = Vec::new();
+    let mut debtors = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        let balance = field(FIXED_COLUMNS.len() + i);
+        if balance.is_empty() {
+            continue;
+        }
+        let balance = Decimal::from_str(balance).map_err(|_| {
+            CreateDebtError::ValidationError(format!("{} is not a valid amount.", balance))
+        })?;
+        if balance.is_zero() {
+            continue;
+        }
+        if members[i].is_none() {
+            return Err(CreateDebtError::ValidationError(format!(
+                "{} is not mapped to a user",
+                name
+            )));
+        }
+        if balance.is_sign_positive() {
+            creditors.push((i, balance));
+        } else {
+            debtors.push((i, -balance));
+        }
+    }
+
+    let mut debts = Vec::new();
+    let mut skipped = Vec::new();
+    for (creditor, debtor, amount) in settle(creditors, debtors) {
+        let (Some(creditor_id), Some(debtor_id)) = (&members[creditor], &members[debtor]) else {
+            continue;
+        };
+        if creditor_id.as_ref() == debtor_id.as_ref() {
+            continue;
+        }
+        if *creditor_id.as_ref() != user_id && *debtor_id.as_ref() != user_id {
+            skipped.push((names[creditor].clone(), names[debtor].clone(), amount));
+            continue;
+        }
+        for party in [creditor_id, debtor_id] {
+            ensure_counterparty_accepts(user_id, party, transaction).await?;
+        }
+        let amount = amount
+            .to_f64()
+            .ok_or_else(|| CreateDebtError::ValidationError(format!("{} is too large.", amount)))
+            .and_then(|amount| {
+                DebtAmount::parse(amount).map_err(CreateDebtError::ValidationError)
+            })?;
+        let new_debt = NewDebt {
+            debtor_id: debtor_id.clone(),
+            creditor_id: creditor_id.clone(),
+            amount,
+            currency: currency.clone(),
+            description: description.clone(),
+            due_date: None,
+            status: DebtStatus::Pending,
+        };
+        debts.push((new_debt, created_at));
+    }
+    Ok((debts, skipped))
+}
+
+/// Pair members who are owed money with members who owe it, largest first,
+/// returning `(creditor, debtor, amount)` triples. Whatever does not balance
+/// out, such as a rounding cent, is dropped.
+fn settle(
+    mut creditors: Vec<(usize, Decimal)>,
+    mut debtors: Vec<(usize, Decimal)>,
+) -> Vec<(usize, usize, Decimal)> {
+    creditors.sort_by_key(|creditor| std::cmp::Reverse(creditor.1));
+    debtors.sort_by_key(|debtor| std::cmp::Reverse(debtor.1));
+    let mut debts = Vec::new();
+    let (mut c, mut d) = (0, 0);
+    while c < creditors.len() && d < debtors.len() {
+        let amount = creditors[c].1.min(debtors[d].1);
+        debts.push((creditors[c].0, debtors[d].0, amount));
+        creditors[c].1 -= amount;
+        debtors[d].1 -= amount;
+        if creditors[c].1.is_zero() {
+            c += 1;
+        }
+        if debtors[d].1.is_zero() {
+            d += 1;
+        }
+    }
+    debts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn a_single_payer_is_owed_by_everyone_else() {
+        let debts = settle(vec![(0, dec!(40))], vec![(1, dec!(20)), (2, dec!(20))]);
+
+        assert_eq!(debts, vec![(0, 1, dec!(20)), (0, 2, dec!(20))]);
+    }
+
+    #[test]
+    fn several_payers_are_paid_back_in_full() {
+        let debts = settle(
+            vec![(0, dec!(10)), (1, dec!(30))],
+            vec![(2, dec!(25)), (3, dec!(15))],
+        );
+
+        assert_eq!(
+            debts,
+            vec![(1, 2, dec!(25)), (1, 3, dec!(5)), (0, 3, dec!(10))]
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use crate::authentication::UserId;
+use crate::event_bus::EventBus;
+use crate::utils::e500;
+use actix_web::web;
+use actix_web::Responder;
+use actix_web_lab::sse;
+use futures_util::StreamExt;
+use std::time::Duration;
+
+const KEEP_ALIVE_PERIOD: Duration = Duration::from_secs(15);
+
+/// Stream the user's events as they happen. Clients should fetch the state
+/// they care about after (re)connecting, since nothing is replayed.
+#[tracing::instrument(name = "Streaming events", skip(event_bus))]
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "users",
+    responses(
+        (status = 200, description = "Server-sent events for the user", content_type = "text/event-stream"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+pub async fn stream_events(
+    user_id: web::ReqData<UserId>,
+    event_bus: web::Data<EventBus>,
+) -> Result<impl Responder, actix_web::Error> {
+    let events = event_bus.subscribe(**user_id).await.map_err(e500)?;
+    let events = events
+        .filter_map(|event| async move { sse::Data::new_json(event).ok().map(sse::Event::from) });
+    Ok(sse::Sse::from_infallible_stream(events).with_keep_alive(KEEP_ALIVE_PERIOD))
+}
@@ -1,3 +1,5 @@
+pub mod oidc;
 pub mod post;
+pub mod two_factor;
 
 pub use post::JsonLoginData;
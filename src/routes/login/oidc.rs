@@ -0,0 +1,307 @@
+use super::post::{start_session, TwoFactorRequiredJSONResponse};
+use crate::authentication::{is_two_factor_enabled, AuthError, OidcClient, OidcIdentity, UserInfo};
+use crate::domain::UserStatus;
+use crate::session_state::TypedSession;
+use crate::utils::error_chain_fmt;
+use actix_web::http::{header, StatusCode};
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use rand::Rng;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(thiserror::Error)]
+pub enum OidcLoginError {
+    #[error("Authentication failed")]
+    AuthError(#[source] anyhow::Error),
+    #[error("The identity provider did not share a verified email address")]
+    UnverifiedEmail,
+    #[error("The account has been disabled")]
+    DisabledAccount,
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for OidcLoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for OidcLoginError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OidcLoginError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            OidcLoginError::UnverifiedEmail | OidcLoginError::DisabledAccount => {
+                StatusCode::FORBIDDEN
+            }
+            OidcLoginError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OidcCallbackQuery {
+    code: String,
+    state: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/login/oidc",
+    tag = "auth",
+    responses(
+        (status = 303, description = "Redirect to the identity provider"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(name = "Starting OIDC login", skip(oidc_client, session))]
+pub async fn login_oidc(
+    oidc_client: web::Data<OidcClient>,
+    session: TypedSession,
+) -> Result<HttpResponse, OidcLoginError> {
+    let (authorization_url, login_state) = oidc_client.authorization_url().await?;
+    session
+        .insert_oidc_login(login_state)
+        .context("Failed to store the OIDC login state in the session.")?;
+    Ok(HttpResponse::SeeOther()
+        .insert_header((header::LOCATION, authorization_url.to_string()))
+        .finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/login/oidc/callback",
+    tag = "auth",
+    params(OidcCallbackQuery),
+    responses(
+        (status = 200, description = "Logged in, the session cookie is set"),
+        (status = 202, body = TwoFactorRequiredJSONResponse, description = "A TOTP code is needed to finish logging in"),
+        (status = 401, description = "Invalid state or code"),
+        (status = 403, description = "The email is not verified or the account is disabled"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(
+    name = "Completing OIDC login",
+    skip(query, oidc_client, db_pool, session),
+    fields(username=tracing::field::Empty, user_id=tracing::field::Empty)
+)]
+pub async fn login_oidc_callback(
+    query: web::Query<OidcCallbackQuery>,
+    oidc_client: web::Data<OidcClient>,
+    db_pool: web::Data<PgPool>,
+    session: TypedSession,
+) -> Result<HttpResponse, OidcLoginError> {
+    let login_state = session
+        .take_oidc_login()
+        .context("Failed to read the OIDC login state from the session.")?
+        .ok_or_else(|| {
+            OidcLoginError::AuthError(anyhow::anyhow!("There is no OIDC login in progress."))
+        })?;
+    if login_state.state != query.state {
+        return Err(OidcLoginError::AuthError(anyhow::anyhow!(
+            "The OIDC state does not match."
+        )));
+    }
+
+    let identity = oidc_client
+        .authenticate(&query.code, &login_state)
+        .await
+        .map_err(|e| match e {
+            AuthError::InvalidCredentials(_) => OidcLoginError::AuthError(e.into()),
+            _ => OidcLoginError::UnexpectedError(e.into()),
+        })?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let UserInfo { user_id, username } = find_or_create_user(&mut transaction, &identity).await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to link an identity.")?;
+    tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+    tracing::Span::current().record("username", tracing::field::display(&username));
+
+    // The identity provider replaces the password, not the second factor.
+    if is_two_factor_enabled(user_id, &db_pool).await? {
+        session.clear();
+        session.renew();
+        session
+            .insert_pending_two_factor(user_id, username)
+            .context("Failed to store the pending login in the session.")?;
+        return Ok(
+            HttpResponse::Accepted().json(TwoFactorRequiredJSONResponse {
+                two_factor_required: true,
+            }),
+        );
+    }
+
+    start_session(&session, user_id, username, &db_pool).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Resolve the local account for an identity, linking or creating one on first login.
+#[tracing::instrument(name = "Find or create user for identity", skip(transaction, identity))]
+async fn find_or_create_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    identity: &OidcIdentity,
+) -> Result<UserInfo, OidcLoginError> {
+    let linked_user = sqlx::query!(
+        r#"
+        SELECT users.user_id, users.username, users.status
+        FROM user_identities
+        JOIN users ON users.user_id = user_identities.user_id
+        WHERE user_identities.issuer = $1 AND user_identities.subject = $2
+        "#,
+        identity.issuer,
+        identity.subject
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to perform a query to retrieve a linked identity.")?;
+    if let Some(user) = linked_user {
+        if user.status == UserStatus::Disabled.to_string() {
+            return Err(OidcLoginError::DisabledAccount);
+        }
+        return Ok(UserInfo {
+            user_id: user.user_id,
+            username: user.username,
+        });
+    }
+
+    // Matching on an unverified email would let anyone claim an existing account.
+    let email = match &identity.email {
+        Some(email) if identity.email_verified => email,
+        _ => return Err(OidcLoginError::UnverifiedEmail),
+    };
+
+    let existing_user = sqlx::query!(
+        r#"
+        SELECT user_id, username, status
+        FROM users
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to perform a query to retrieve a user by email.")?;
+
+    let user_info = match existing_user {
+        Some(user) => {
+            if user.status == UserStatus::Disabled.to_string() {
+                return Err(OidcLoginError::DisabledAccount);
+            }
+            // The identity provider vouches for the email address. Whoever
+            // signed up with it before proved nothing, so their password goes.
+            if user.status == UserStatus::PendingConfirmation.to_string()
+                || user.status == UserStatus::Invited.to_string()
+            {
+                activate_user(transaction, user.user_id).await?;
+            }
+            UserInfo {
+                user_id: user.user_id,
+                username: user.username,
+            }
+        }
+        None => {
+            let username = available_username(transaction, identity, email).await?;
+            let user_id = Uuid::new_v4();
+            insert_passwordless_user(transaction, user_id, &username, email).await?;
+            UserInfo { user_id, username }
+        }
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_identities (issuer, subject, user_id, email, created_at)
+        VALUES ($1, $2, $3, $4, now())
+        "#,
+        identity.issuer,
+        identity.subject,
+        user_info.user_id,
+        email
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to link the identity to the user.")?;
+
+    Ok(user_info)
+}
+
+#[tracing::instrument(name = "Activate user", skip(transaction))]
+async fn activate_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET status = $1, password_hash = NULL, session_version = session_version + 1
+        WHERE user_id = $2
+        "#,
+        UserStatus::Active.to_string(),
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to activate the user.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Insert passwordless user", skip(transaction))]
+async fn insert_passwordless_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    username: &str,
+    email: &str,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO users (user_id, username, email, status)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        username,
+        email,
+        UserStatus::Active.to_string()
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert new user into the database")?;
+    Ok(())
+}
+
+/// Derive a username from the identity, adding a numeric suffix until it is unique.
+async fn available_username(
+    transaction: &mut Transaction<'_, Postgres>,
+    identity: &OidcIdentity,
+    email: &str,
+) -> Result<String, anyhow::Error> {
+    let base = identity
+        .preferred_username
+        .clone()
+        .filter(|username| !username.trim().is_empty())
+        .unwrap_or_else(|| email.split('@').next().unwrap_or_default().to_string());
+
+    let mut candidate = base.clone();
+    loop {
+        let taken = sqlx::query!(
+            "SELECT 1 AS taken FROM users WHERE username = $1",
+            candidate
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+        .context("Failed to check whether a username is taken.")?
+        .is_some();
+        if !taken {
+            return Ok(candidate);
+        }
+        candidate = format!("{}-{}", base, rand::thread_rng().gen_range(1000..10000));
+    }
+}
@@ -1,17 +1,28 @@
-use crate::authentication::{validate_credentials, AuthError, Credentials, UserInfo};
+use crate::authentication::{
+    get_session_version, get_user_role, is_two_factor_enabled, validate_credentials, AuthError,
+    Credentials, LoginThrottle, UserInfo,
+};
 use crate::session_state::TypedSession;
 use crate::utils::error_chain_fmt;
 use actix_web::error::InternalError;
-use actix_web::http::StatusCode;
+use actix_web::http::{header, StatusCode};
 use actix_web::web;
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use chrono::Utc;
 use secrecy::Secret;
 use sqlx::PgPool;
+use uuid::Uuid;
 
 #[derive(thiserror::Error)]
 pub enum LoginError {
     #[error("Authentication failed")]
     AuthError(#[source] anyhow::Error),
+    #[error("The account has not been confirmed yet")]
+    UnconfirmedAccount(#[source] anyhow::Error),
+    #[error("The account has been disabled")]
+    DisabledAccount(#[source] anyhow::Error),
+    #[error("Too many failed login attempts, retry in {0} seconds")]
+    TooManyAttempts(u64),
     #[error("Something went wrong")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -26,53 +37,99 @@ impl ResponseError for LoginError {
     fn status_code(&self) -> StatusCode {
         match self {
             LoginError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            LoginError::UnconfirmedAccount(_) | LoginError::DisabledAccount(_) => {
+                StatusCode::FORBIDDEN
+            }
+            LoginError::TooManyAttempts(_) => StatusCode::TOO_MANY_REQUESTS,
             LoginError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
-#[derive(serde::Deserialize)]
-pub struct JsonLoginData {
-    username: String,
-    password: Secret<String>,
-}
+pub use debt_tracer_types::{JsonLoginData, TwoFactorRequiredJSONResponse};
 
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "auth",
+    request_body = JsonLoginData,
+    responses(
+        (status = 200, description = "Logged in, the session cookie is set"),
+        (status = 202, body = TwoFactorRequiredJSONResponse, description = "A TOTP code is needed to finish logging in"),
+        (status = 401, description = "Invalid credentials"),
+        (status = 403, description = "The account is not confirmed or is disabled"),
+        (status = 429, description = "Too many failed attempts"),
+    ),
+    security(()),
+)]
 #[tracing::instrument(
-    skip(body, db_pool, session),
-    fields(username=tracing::field::Empty, user_id=tracing::field::Empty)
+    skip(body, db_pool, session, throttle, request),
+    fields(username=tracing::field::Empty, user_id=tracing::field::Empty, ip=tracing::field::Empty)
 )]
 pub async fn login(
     body: web::Json<JsonLoginData>,
     db_pool: web::Data<PgPool>,
     session: TypedSession,
+    throttle: web::Data<LoginThrottle>,
+    request: HttpRequest,
 ) -> Result<HttpResponse, InternalError<LoginError>> {
     let credentials = Credentials {
         username: body.username.clone(),
-        password: body.password.clone(),
+        password: Secret::new(body.password.clone()),
     };
+    let ip = throttle.client_ip(&request);
     tracing::Span::current().record("username", tracing::field::display(&credentials.username));
+    if let Some(ip) = &ip {
+        tracing::Span::current().record("ip", tracing::field::display(ip));
+    }
+
+    reject_locked_out_clients(&throttle, &credentials.username, ip.as_deref()).await?;
+
+    let attempted_username = credentials.username.clone();
     match validate_credentials(credentials, &db_pool).await {
         Ok(UserInfo { user_id, username }) => {
             tracing::Span::current().record("user_id", tracing::field::display(&user_id));
-            tracing::Span::current().record("ussername", tracing::field::display(&username));
-            session.renew();
-            session.insert_user_id(user_id).map_err(|e| {
-                InternalError::from_response(
-                    LoginError::UnexpectedError(e.into()),
-                    HttpResponse::InternalServerError().finish(),
-                )
-            })?;
-            session.insert_username(username).map_err(|e| {
-                InternalError::from_response(
-                    LoginError::UnexpectedError(e.into()),
-                    HttpResponse::InternalServerError().finish(),
-                )
-            })?;
+            tracing::Span::current().record("username", tracing::field::display(&username));
+
+            let two_factor_enabled = is_two_factor_enabled(user_id, &db_pool)
+                .await
+                .map_err(unexpected_error)?;
+            if two_factor_enabled {
+                // Drop any previous identity: the failure count is only reset, and the
+                // session only authenticated, once the second factor is checked.
+                session.clear();
+                session.renew();
+                session
+                    .insert_pending_two_factor(user_id, username)
+                    .map_err(|e| unexpected_error(e.into()))?;
+                return Ok(
+                    HttpResponse::Accepted().json(TwoFactorRequiredJSONResponse {
+                        two_factor_required: true,
+                    }),
+                );
+            }
+
+            throttle
+                .record_success(&username)
+                .await
+                .map_err(unexpected_error)?;
+            start_session(&session, user_id, username, &db_pool)
+                .await
+                .map_err(unexpected_error)?;
             Ok(HttpResponse::Ok().finish())
         }
         Err(e) => {
+            if let AuthError::InvalidCredentials(_) = e {
+                tracing::warn!("Failed login attempt.");
+                throttle
+                    .record_failure(&attempted_username, ip.as_deref())
+                    .await
+                    .map_err(unexpected_error)?;
+            }
             let e = match e {
                 AuthError::InvalidCredentials(_) => LoginError::AuthError(e.into()),
+                AuthError::UnconfirmedAccount => LoginError::UnconfirmedAccount(e.into()),
+                AuthError::DisabledAccount => LoginError::DisabledAccount(e.into()),
                 AuthError::UnexpectedError(_) => LoginError::UnexpectedError(e.into()),
             };
             let status_code = e.status_code();
@@ -83,3 +140,55 @@ pub async fn login(
         }
     }
 }
+
+pub async fn reject_locked_out_clients(
+    throttle: &LoginThrottle,
+    username: &str,
+    ip: Option<&str>,
+) -> Result<(), InternalError<LoginError>> {
+    let retry_after = throttle
+        .retry_after(username, ip)
+        .await
+        .map_err(unexpected_error)?;
+    if let Some(retry_after) = retry_after {
+        tracing::warn!(
+            retry_after,
+            "Rejected a login attempt from a locked out client."
+        );
+        return Err(InternalError::from_response(
+            LoginError::TooManyAttempts(retry_after),
+            HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .finish(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn start_session(
+    session: &TypedSession,
+    user_id: Uuid,
+    username: String,
+    db_pool: &PgPool,
+) -> Result<(), anyhow::Error> {
+    let session_version = get_session_version(user_id, db_pool)
+        .await?
+        .unwrap_or_default();
+    let role = get_user_role(user_id, db_pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("The user does not exist."))?;
+    session.renew();
+    session.insert_user_id(user_id)?;
+    session.insert_username(username)?;
+    session.insert_session_version(session_version)?;
+    session.insert_role(role)?;
+    session.insert_logged_in_at(Utc::now())?;
+    Ok(())
+}
+
+pub fn unexpected_error(e: anyhow::Error) -> InternalError<LoginError> {
+    InternalError::from_response(
+        LoginError::UnexpectedError(e),
+        HttpResponse::InternalServerError().finish(),
+    )
+}
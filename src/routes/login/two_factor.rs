@@ -0,0 +1,83 @@
+use super::post::{reject_locked_out_clients, start_session, unexpected_error, LoginError};
+use crate::authentication::{validate_second_factor, LoginThrottle};
+use crate::session_state::TypedSession;
+use actix_web::error::InternalError;
+use actix_web::web;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use sqlx::PgPool;
+
+pub use debt_tracer_types::JsonTwoFactorData;
+
+#[utoipa::path(
+    post,
+    path = "/login/2fa",
+    tag = "auth",
+    request_body = JsonTwoFactorData,
+    responses(
+        (status = 200, description = "Logged in, the session cookie is set"),
+        (status = 401, description = "Invalid code"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(
+    name = "Verifying second factor",
+    skip(body, db_pool, session, throttle, request),
+    fields(username=tracing::field::Empty, user_id=tracing::field::Empty)
+)]
+pub async fn login_two_factor(
+    body: web::Json<JsonTwoFactorData>,
+    db_pool: web::Data<PgPool>,
+    session: TypedSession,
+    throttle: web::Data<LoginThrottle>,
+    request: HttpRequest,
+) -> Result<HttpResponse, InternalError<LoginError>> {
+    let (user_id, username) = match session
+        .get_pending_two_factor()
+        .map_err(|e| unexpected_error(e.into()))?
+    {
+        Some(pending) => pending,
+        None => {
+            let e = LoginError::AuthError(anyhow::anyhow!(
+                "There is no login waiting for a second factor."
+            ));
+            let status_code = e.status_code();
+            return Err(InternalError::from_response(
+                e,
+                HttpResponse::build(status_code).finish(),
+            ));
+        }
+    };
+    tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+    tracing::Span::current().record("username", tracing::field::display(&username));
+
+    let ip = throttle.client_ip(&request);
+    reject_locked_out_clients(&throttle, &username, ip.as_deref()).await?;
+
+    let is_valid = validate_second_factor(user_id, &username, &body.code, &db_pool)
+        .await
+        .map_err(unexpected_error)?;
+
+    if !is_valid {
+        tracing::warn!("Failed second factor attempt.");
+        throttle
+            .record_failure(&username, ip.as_deref())
+            .await
+            .map_err(unexpected_error)?;
+        let e = LoginError::AuthError(anyhow::anyhow!("Invalid second factor."));
+        let status_code = e.status_code();
+        return Err(InternalError::from_response(
+            e,
+            HttpResponse::build(status_code).finish(),
+        ));
+    }
+
+    throttle
+        .record_success(&username)
+        .await
+        .map_err(unexpected_error)?;
+    session.remove_pending_two_factor();
+    start_session(&session, user_id, username, &db_pool)
+        .await
+        .map_err(unexpected_error)?;
+    Ok(HttpResponse::Ok().finish())
+}
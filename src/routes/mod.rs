@@ -1,7 +1,54 @@
+pub mod admin;
+pub mod contacts;
+pub mod debts;
+pub mod events;
 pub mod login;
+pub mod notifications;
+pub mod password;
+pub mod password_reset;
+pub mod reports;
 pub mod signup;
+pub mod statements;
+pub mod tokens;
+pub mod two_factor;
 pub mod users;
+pub mod webhooks;
 
+pub use admin::debts::get_debt;
+pub use admin::users::{disable_user, list_users};
+pub use contacts::delete::remove_contact;
+pub use contacts::get::get_contacts;
+pub use contacts::requests::{
+    accept_contact_request, decline_contact_request, get_contact_requests, send_contact_request,
+};
+pub use debts::export::export_debts;
+pub use debts::import::import_debts;
+pub use debts::splitwise::import_splitwise;
+pub use events::get::stream_events;
+pub use login::oidc::{login_oidc, login_oidc_callback};
 pub use login::post::login;
+pub use login::two_factor::login_two_factor;
+pub use notifications::get::get_notifications;
+pub use notifications::read::mark_notifications_read;
+pub use password::post::change_password;
+pub use password_reset::confirm::confirm_password_reset;
+pub use password_reset::post::request_password_reset;
+pub use reports::timeseries::get_timeseries;
+pub use signup::confirm::confirm_sign_up;
 pub use signup::post::sign_up;
+pub use statements::get::get_statement;
+pub use tokens::delete::revoke_api_token;
+pub use tokens::get::get_api_tokens;
+pub use tokens::post::create_api_token;
+pub use two_factor::confirm::confirm_totp;
+pub use two_factor::post::enroll_totp;
+pub use users::confirm_email::confirm_email_change;
+pub use users::delete::delete_user;
+pub use users::export::export_user_data;
 pub use users::get::get_user_info_by_id;
+pub use users::patch::update_user;
+pub use users::search::search_users;
+pub use webhooks::delete::delete_webhook;
+pub use webhooks::deliveries::get_webhook_deliveries;
+pub use webhooks::get::get_webhooks;
+pub use webhooks::post::create_webhook;
@@ -0,0 +1,126 @@
+use crate::authentication::UserId;
+use crate::utils::{e400, e500};
+use actix_web::web;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Deserialize, Debug, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetNotificationsParameters {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct NotificationJSONResponse {
+    pub notification_id: String,
+    pub kind: String,
+    pub debt_id: String,
+    pub actor_id: String,
+    pub actor_name: String,
+    pub created_at: String,
+    pub read: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct GetNotificationsJSONResponse {
+    pub notifications: Vec<NotificationJSONResponse>,
+    pub unread_count: i64,
+}
+
+/// Newest first. The unread count covers every notification, not just the page.
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    tag = "notifications",
+    params(GetNotificationsParameters),
+    responses(
+        (status = 200, body = GetNotificationsJSONResponse),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting notifications", skip(db_pool))]
+pub async fn get_notifications(
+    parameters: web::Query<GetNotificationsParameters>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<GetNotificationsJSONResponse>, actix_web::Error> {
+    let limit = parameters.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(e400(format!(
+            "The limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    let offset = parameters.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(e400("The offset cannot be negative"));
+    }
+
+    let notifications = fetch_notifications(**user_id, Some(limit), offset, db_pool.get_ref())
+        .await
+        .map_err(e500)?;
+
+    let unread_count = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "unread_count!"
+        FROM notifications
+        WHERE user_id = $1 AND read_at IS NULL
+        "#,
+        **user_id
+    )
+    .fetch_one(db_pool.get_ref())
+    .await
+    .context("Failed to count unread notifications.")
+    .map_err(e500)?
+    .unread_count;
+
+    Ok(web::Json(GetNotificationsJSONResponse {
+        notifications,
+        unread_count,
+    }))
+}
+
+/// Newest first; every notification when `limit` is `None`.
+pub async fn fetch_notifications(
+    user_id: Uuid,
+    limit: Option<i64>,
+    offset: i64,
+    pool: &PgPool,
+) -> Result<Vec<NotificationJSONResponse>, anyhow::Error> {
+    let notifications = sqlx::query!(
+        r#"
+        SELECT notifications.notification_id, notifications.kind, notifications.debt_id,
+            notifications.actor_id, users.username AS actor_name, notifications.created_at,
+            notifications.read_at
+        FROM notifications
+        JOIN users ON users.user_id = notifications.actor_id
+        WHERE notifications.user_id = $1
+        ORDER BY notifications.created_at DESC, notifications.notification_id
+        LIMIT $2 OFFSET $3
+        "#,
+        user_id,
+        limit,
+        offset
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch notifications from the database.")?
+    .into_iter()
+    .map(|row| NotificationJSONResponse {
+        notification_id: row.notification_id.to_string(),
+        kind: row.kind,
+        debt_id: row.debt_id.to_string(),
+        actor_id: row.actor_id.to_string(),
+        actor_name: row.actor_name,
+        created_at: row.created_at.to_string(),
+        read: row.read_at.is_some(),
+    })
+    .collect();
+    Ok(notifications)
+}
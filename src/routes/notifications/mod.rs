@@ -0,0 +1,49 @@
+pub mod get;
+pub mod read;
+
+use crate::domain::NotificationKind;
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub struct Notification {
+    pub notification_id: Uuid,
+    pub user_id: Uuid,
+}
+
+/// Notify the parties of a debt about something `actor_id` did to it. The actor
+/// is never notified of their own actions.
+#[tracing::instrument(name = "Notify debt parties", skip(transaction))]
+pub async fn notify_debt_parties(
+    transaction: &mut Transaction<'_, Postgres>,
+    kind: NotificationKind,
+    debt_id: Uuid,
+    actor_id: Uuid,
+) -> Result<Vec<Notification>, anyhow::Error> {
+    let notifications = sqlx::query!(
+        r#"
+        INSERT INTO notifications (notification_id, user_id, kind, debt_id, actor_id, created_at)
+        SELECT gen_random_uuid(), party.user_id, $1, debts.debt_id, $3, $4
+        FROM debts
+        CROSS JOIN LATERAL (VALUES (debts.creditor_id), (debts.debtor_id)) AS party(user_id)
+        WHERE debts.debt_id = $2 AND party.user_id <> $3
+        GROUP BY party.user_id, debts.debt_id
+        RETURNING notification_id, user_id
+        "#,
+        kind.to_string(),
+        debt_id,
+        actor_id,
+        Utc::now()
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to insert notifications into the database.")?
+    .into_iter()
+    .map(|row| Notification {
+        notification_id: row.notification_id,
+        user_id: row.user_id,
+    })
+    .collect();
+    Ok(notifications)
+}
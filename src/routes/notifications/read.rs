@@ -0,0 +1,50 @@
+use crate::authentication::UserId;
+use crate::utils::e500;
+use actix_web::web;
+use actix_web::HttpResponse;
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct MarkNotificationsReadJsonData {
+    notification_ids: Option<Vec<Uuid>>,
+}
+
+/// Mark the given notifications as read, or all of them when none are given.
+#[utoipa::path(
+    post,
+    path = "/notifications/read",
+    tag = "notifications",
+    request_body = MarkNotificationsReadJsonData,
+    responses(
+        (status = 200, description = "The notifications were marked as read"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Marking notifications as read", skip(body, db_pool))]
+pub async fn mark_notifications_read(
+    body: web::Json<MarkNotificationsReadJsonData>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE notifications
+        SET read_at = $1
+        WHERE user_id = $2
+            AND read_at IS NULL
+            AND ($3::uuid[] IS NULL OR notification_id = ANY($3))
+        "#,
+        Utc::now(),
+        **user_id,
+        body.notification_ids.as_deref()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to mark notifications as read.")
+    .map_err(e500)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,101 @@
+use crate::authentication::{self, validate_credentials, AuthError, Credentials, UserId, Username};
+use crate::domain::NewPassword;
+use crate::session_state::TypedSession;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use secrecy::{ExposeSecret, Secret};
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct ChangePasswordJsonData {
+    #[schema(value_type = String)]
+    current_password: Secret<String>,
+    #[schema(value_type = String)]
+    new_password: Secret<String>,
+    #[schema(value_type = String)]
+    new_password_check: Secret<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum ChangePasswordError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("The current password is incorrect.")]
+    AuthError(#[source] anyhow::Error),
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ChangePasswordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ChangePasswordError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ChangePasswordError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ChangePasswordError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            ChangePasswordError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/password",
+    tag = "auth",
+    request_body = ChangePasswordJsonData,
+    responses(
+        (status = 200, description = "The password was changed"),
+        (status = 400, description = "Invalid new password"),
+        (status = 401, description = "Not logged in or wrong current password"),
+    ),
+)]
+#[tracing::instrument(
+    name = "Changing password",
+    skip(body, db_pool, session, username),
+    fields(user_id = %*user_id)
+)]
+pub async fn change_password(
+    body: web::Json<ChangePasswordJsonData>,
+    db_pool: web::Data<PgPool>,
+    user_id: web::ReqData<UserId>,
+    username: web::ReqData<Username>,
+    session: TypedSession,
+) -> Result<HttpResponse, ChangePasswordError> {
+    let body = body.into_inner();
+
+    if body.new_password.expose_secret() != body.new_password_check.expose_secret() {
+        return Err(ChangePasswordError::ValidationError(
+            "You entered two different new passwords - the field values must match.".to_string(),
+        ));
+    }
+
+    let new_password =
+        NewPassword::parse(body.new_password).map_err(ChangePasswordError::ValidationError)?;
+
+    let credentials = Credentials {
+        username: username.into_inner().to_string(),
+        password: body.current_password,
+    };
+
+    if let Err(e) = validate_credentials(credentials, &db_pool).await {
+        return match e {
+            AuthError::InvalidCredentials(_)
+            | AuthError::UnconfirmedAccount
+            | AuthError::DisabledAccount => Err(ChangePasswordError::AuthError(e.into())),
+            AuthError::UnexpectedError(_) => Err(ChangePasswordError::UnexpectedError(e.into())),
+        };
+    }
+
+    authentication::change_password(**user_id, new_password.inner(), db_pool.get_ref()).await?;
+
+    // Changing the password invalidates every session, including this one.
+    session.log_out();
+
+    Ok(HttpResponse::Ok().finish())
+}
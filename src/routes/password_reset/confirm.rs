@@ -0,0 +1,113 @@
+use crate::authentication::{self, hash_token};
+use crate::domain::NewPassword;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use secrecy::{ExposeSecret, Secret};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct ConfirmPasswordResetJsonData {
+    token: String,
+    #[schema(value_type = String)]
+    new_password: Secret<String>,
+    #[schema(value_type = String)]
+    new_password_check: Secret<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum ConfirmPasswordResetError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("The password reset token is invalid or has expired.")]
+    InvalidToken,
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ConfirmPasswordResetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ConfirmPasswordResetError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConfirmPasswordResetError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ConfirmPasswordResetError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ConfirmPasswordResetError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/password/reset/confirm",
+    tag = "auth",
+    request_body = ConfirmPasswordResetJsonData,
+    responses(
+        (status = 200, description = "The password was reset"),
+        (status = 400, description = "Invalid new password"),
+        (status = 401, description = "Invalid token"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(name = "Confirming a password reset", skip(body, db_pool))]
+pub async fn confirm_password_reset(
+    body: web::Json<ConfirmPasswordResetJsonData>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ConfirmPasswordResetError> {
+    let body = body.into_inner();
+
+    if body.new_password.expose_secret() != body.new_password_check.expose_secret() {
+        return Err(ConfirmPasswordResetError::ValidationError(
+            "You entered two different new passwords - the field values must match.".to_string(),
+        ));
+    }
+
+    let new_password = NewPassword::parse(body.new_password)
+        .map_err(ConfirmPasswordResetError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let user_id = consume_password_reset_token(&mut transaction, &body.token)
+        .await?
+        .ok_or(ConfirmPasswordResetError::InvalidToken)?;
+
+    authentication::change_password(user_id, new_password.inner(), &mut *transaction).await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to reset a password.")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Consume password reset token", skip(transaction, token))]
+async fn consume_password_reset_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &str,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let user_id = sqlx::query!(
+        r#"
+        UPDATE password_reset_tokens
+        SET used_at = now()
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > now()
+        RETURNING user_id
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to consume the password reset token.")?
+    .map(|row| row.user_id);
+    Ok(user_id)
+}
@@ -0,0 +1,145 @@
+use crate::authentication::{generate_token, hash_token};
+use crate::email_client::EmailClient;
+use crate::startup::FrontendBaseUrl;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct PasswordResetJsonData {
+    email: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum PasswordResetError {
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for PasswordResetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for PasswordResetError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/password/reset",
+    tag = "auth",
+    request_body = PasswordResetJsonData,
+    responses(
+        (status = 200, description = "A reset email was sent if the address is known"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(
+    name = "Requesting a password reset",
+    skip(body, db_pool, email_client, frontend_base_url),
+    fields(email = %body.email)
+)]
+pub async fn request_password_reset(
+    body: web::Json<PasswordResetJsonData>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<EmailClient>,
+    frontend_base_url: web::Data<FrontendBaseUrl>,
+) -> Result<HttpResponse, PasswordResetError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    // Unknown emails get the same response so that accounts cannot be enumerated.
+    let user_id = match get_user_id_by_email(&mut transaction, &body.email).await? {
+        Some(user_id) => user_id,
+        None => return Ok(HttpResponse::Ok().finish()),
+    };
+
+    let token = generate_token();
+    store_password_reset_token(&mut transaction, user_id, &token).await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a password reset token.")?;
+
+    send_password_reset_email(&email_client, &body.email, &frontend_base_url.0, &token)
+        .await
+        .context("Failed to send a password reset email.")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Get user id by email", skip(transaction, email))]
+async fn get_user_id_by_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    email: &str,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let user_id = sqlx::query!(
+        r#"
+        SELECT user_id
+        FROM users
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to retrieve the user associated with the provided email.")?
+    .map(|row| row.user_id);
+    Ok(user_id)
+}
+
+#[tracing::instrument(name = "Store password reset token", skip(transaction, token))]
+async fn store_password_reset_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    token: &str,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO password_reset_tokens (token_hash, user_id, expires_at)
+        VALUES ($1, $2, now() + interval '1 hour')
+        "#,
+        hash_token(token),
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert the password reset token into the database.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Send a password reset email", skip(email_client, token))]
+async fn send_password_reset_email(
+    email_client: &EmailClient,
+    recipient: &str,
+    frontend_base_url: &str,
+    token: &str,
+) -> Result<(), reqwest::Error> {
+    let reset_link = format!("{}/password/reset?token={}", frontend_base_url, token);
+    let plain_body = format!(
+        "We received a request to reset your password.\n\
+        Visit {} to choose a new one. The link expires in one hour.\n\
+        If you did not request a reset, you can ignore this email.",
+        reset_link
+    );
+    let html_body = format!(
+        "We received a request to reset your password.<br />\
+        Click <a href=\"{}\">here</a> to choose a new one. The link expires in one hour.<br />\
+        If you did not request a reset, you can ignore this email.",
+        reset_link
+    );
+    email_client
+        .send_email(recipient, "Reset your password", &html_body, &plain_body)
+        .await
+}
@@ -0,0 +1,169 @@
+use crate::authentication::UserId;
+use crate::domain::DebtCurrency;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::{web, ResponseError};
+use anyhow::Context;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TimeseriesQuery {
+    /// `day`, `week` or `month`.
+    bucket: String,
+    /// Defaults to the user's default currency.
+    currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Bucket {
+    Day,
+    Week,
+    Month,
+}
+
+impl Bucket {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            _ => Err(format!("{} is not a valid bucket", s)),
+        }
+    }
+
+    /// The field name `date_trunc` expects.
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct TimeseriesJSONResponse {
+    pub bucket: String,
+    pub currency: String,
+    pub points: Vec<TimeseriesPointJSONResponse>,
+}
+
+/// `start` is the first day of the bucket in the user's timezone; weeks start
+/// on Monday. Net is what the user lent minus what they borrowed.
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct TimeseriesPointJSONResponse {
+    pub start: String,
+    pub lent: Decimal,
+    pub borrowed: Decimal,
+    pub net: Decimal,
+}
+
+#[derive(thiserror::Error)]
+pub enum TimeseriesError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for TimeseriesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for TimeseriesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TimeseriesError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            TimeseriesError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Lent, borrowed and net totals of the user's debts in a single currency,
+/// grouped by when they were created. The currency defaults to the user's
+/// default currency, and buckets without debts are left out.
+#[utoipa::path(
+    get,
+    path = "/reports/timeseries",
+    tag = "reports",
+    params(TimeseriesQuery),
+    responses(
+        (status = 200, body = TimeseriesJSONResponse),
+        (status = 400, description = "Invalid bucket or currency"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting a time-series report", skip(query, db_pool))]
+pub async fn get_timeseries(
+    query: web::Query<TimeseriesQuery>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<TimeseriesJSONResponse>, TimeseriesError> {
+    let query = query.into_inner();
+    let bucket = Bucket::parse(&query.bucket).map_err(TimeseriesError::ValidationError)?;
+    let currency = match query.currency {
+        Some(currency) => DebtCurrency::parse(currency)
+            .map_err(TimeseriesError::ValidationError)?
+            .to_string(),
+        None => {
+            sqlx::query!(
+                "SELECT default_currency FROM users WHERE user_id = $1",
+                **user_id
+            )
+            .fetch_one(db_pool.get_ref())
+            .await
+            .context("Failed to fetch the default currency.")?
+            .default_currency
+        }
+    };
+    let points = fetch_points(**user_id, bucket, &currency, &db_pool).await?;
+    Ok(web::Json(TimeseriesJSONResponse {
+        bucket: bucket.as_str().to_string(),
+        currency,
+        points,
+    }))
+}
+
+#[tracing::instrument(name = "Fetching time-series totals", skip(pool))]
+async fn fetch_points(
+    user_id: Uuid,
+    bucket: Bucket,
+    currency: &str,
+    pool: &PgPool,
+) -> Result<Vec<TimeseriesPointJSONResponse>, anyhow::Error> {
+    let points = sqlx::query!(
+        r#"
+        SELECT date_trunc($2, debts.created_at AT TIME ZONE users.timezone)::date AS "start!",
+            COALESCE(SUM(debts.amount) FILTER (WHERE debts.creditor_id = $1), 0) AS "lent!",
+            COALESCE(SUM(debts.amount) FILTER (WHERE debts.debtor_id = $1), 0) AS "borrowed!"
+        FROM debts
+        JOIN users ON users.user_id = $1
+        WHERE (debts.creditor_id = $1 OR debts.debtor_id = $1)
+            AND debts.creditor_id <> debts.debtor_id
+            AND debts.currency = $3
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        user_id,
+        bucket.as_str(),
+        currency
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to compute the time series.")?
+    .into_iter()
+    .map(|row| TimeseriesPointJSONResponse {
+        start: row.start.to_string(),
+        lent: row.lent,
+        borrowed: row.borrowed,
+        net: row.lent - row.borrowed,
+    })
+    .collect();
+    Ok(points)
+}
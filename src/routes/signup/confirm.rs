@@ -0,0 +1,119 @@
+use crate::authentication::hash_token;
+use crate::domain::UserStatus;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConfirmSignUpParameters {
+    token: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum ConfirmSignUpError {
+    #[error("The confirmation token is invalid.")]
+    InvalidToken,
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ConfirmSignUpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ConfirmSignUpError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConfirmSignUpError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ConfirmSignUpError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/sign_up/confirm",
+    tag = "auth",
+    params(ConfirmSignUpParameters),
+    responses(
+        (status = 200, description = "The account is confirmed"),
+        (status = 401, description = "Invalid token"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(name = "Confirming a new user", skip(parameters, db_pool))]
+pub async fn confirm_sign_up(
+    parameters: web::Query<ConfirmSignUpParameters>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ConfirmSignUpError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let user_id = consume_confirmation_token(&mut transaction, &parameters.token)
+        .await?
+        .ok_or(ConfirmSignUpError::InvalidToken)?;
+
+    if !activate_user(&mut transaction, user_id).await? {
+        return Err(ConfirmSignUpError::InvalidToken);
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to confirm a new user.")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Consume confirmation token", skip(transaction, token))]
+async fn consume_confirmation_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &str,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let user_id = sqlx::query!(
+        r#"
+        DELETE FROM user_tokens
+        WHERE token_hash = $1 AND expires_at > now()
+        RETURNING user_id
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to consume the confirmation token.")?
+    .map(|row| row.user_id);
+    Ok(user_id)
+}
+
+/// Only accounts still waiting for confirmation are activated, so an old link
+/// cannot re-enable a disabled account.
+#[tracing::instrument(name = "Mark user as active", skip(transaction))]
+async fn activate_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<bool, anyhow::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET status = $1
+        WHERE user_id = $2 AND status IN ($3, $4)
+        "#,
+        UserStatus::Active.to_string(),
+        user_id,
+        UserStatus::PendingConfirmation.to_string(),
+        UserStatus::Invited.to_string()
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to mark the user as active.")?;
+    Ok(result.rows_affected() == 1)
+}
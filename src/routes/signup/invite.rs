@@ -0,0 +1,93 @@
+use crate::authentication::{generate_token, hash_token};
+use crate::domain::UserStatus;
+use crate::email_client::EmailClient;
+use anyhow::Context;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// An invitation that still has to be emailed once the transaction that
+/// created it has been committed.
+pub struct Invite {
+    pub email: String,
+    pub token: String,
+}
+
+/// Create a placeholder user for someone who has not signed up yet. It has no
+/// password, so nobody can log in as it until it is claimed through `sign_up`.
+#[tracing::instrument(name = "Invite user by email", skip(transaction))]
+pub async fn invite_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    email: &str,
+) -> Result<(Uuid, Invite), anyhow::Error> {
+    let user_id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO users (user_id, username, email, status)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        format!("invited-{}", user_id),
+        email,
+        UserStatus::Invited.to_string()
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert the placeholder user into the database.")?;
+
+    let invite = create_invite(transaction, user_id, email).await?;
+    Ok((user_id, invite))
+}
+
+/// Issue a new invite to an existing placeholder user. Earlier invites stay
+/// valid until they expire.
+#[tracing::instrument(name = "Create invite token", skip(transaction))]
+pub async fn create_invite(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    email: &str,
+) -> Result<Invite, anyhow::Error> {
+    let token = generate_token();
+    sqlx::query!(
+        r#"
+        INSERT INTO invite_tokens (token_hash, user_id, expires_at)
+        VALUES ($1, $2, now() + interval '30 days')
+        "#,
+        hash_token(&token),
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert the invite token into the database.")?;
+
+    Ok(Invite {
+        email: email.to_string(),
+        token,
+    })
+}
+
+#[tracing::instrument(name = "Send an invite email", skip(email_client, invite))]
+pub async fn send_invite_email(
+    email_client: &EmailClient,
+    invite: &Invite,
+    frontend_base_url: &str,
+) -> Result<(), reqwest::Error> {
+    let invite_link = format!("{}/sign_up?token={}", frontend_base_url, invite.token);
+    let plain_body = format!(
+        "Someone recorded a debt with you on debt-tracer.\n\
+        Visit {} to sign up and see it. The link expires in 30 days.",
+        invite_link
+    );
+    let html_body = format!(
+        "Someone recorded a debt with you on debt-tracer.<br />\
+        Click <a href=\"{}\">here</a> to sign up and see it. The link expires in 30 days.",
+        invite_link
+    );
+    email_client
+        .send_email(
+            &invite.email,
+            "You have been invited to debt-tracer",
+            &html_body,
+            &plain_body,
+        )
+        .await
+}
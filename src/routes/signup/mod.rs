@@ -1 +1,3 @@
+pub mod confirm;
+pub mod invite;
 pub mod post;
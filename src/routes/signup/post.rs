@@ -1,18 +1,18 @@
-use crate::domain::NewUser;
+use crate::authentication::{generate_token, hash_token};
+use crate::domain::{NewUser, UserStatus};
+use crate::email_client::EmailClient;
+use crate::routes::signup::invite::{create_invite, send_invite_email};
+use crate::startup::{ApplicationBaseUrl, FrontendBaseUrl};
 use crate::utils::error_chain_fmt;
 use actix_web::http::StatusCode;
 use actix_web::web;
 use actix_web::HttpResponse;
 use actix_web::ResponseError;
 use anyhow::Context;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
 
-#[derive(serde::Deserialize)]
-pub struct SignUpJsonRequestBody {
-    username: String,
-    password: String,
-    email: String,
-}
+pub use debt_tracer_types::SignUpJsonRequestBody;
 
 impl TryFrom<SignUpJsonRequestBody> for NewUser {
     type Error = anyhow::Error;
@@ -28,13 +28,21 @@ impl TryFrom<SignUpJsonRequestBody> for NewUser {
 
 #[derive(thiserror::Error)]
 pub enum SignUpError {
+    #[error("The invite token is invalid.")]
+    InvalidInviteToken,
+    #[error("This email address was invited. Sign up through the link in the invite; a new one was sent.")]
+    InvitePending,
     #[error("Something went wrong")]
     UnexpectedError(#[from] anyhow::Error),
 }
 
 impl ResponseError for SignUpError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::INTERNAL_SERVER_ERROR
+        match self {
+            SignUpError::InvalidInviteToken => StatusCode::UNAUTHORIZED,
+            SignUpError::InvitePending => StatusCode::CONFLICT,
+            SignUpError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
     }
 }
 
@@ -44,9 +52,22 @@ impl std::fmt::Debug for SignUpError {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/sign_up",
+    tag = "auth",
+    request_body = SignUpJsonRequestBody,
+    responses(
+        (status = 200, description = "A confirmation email was sent"),
+        (status = 400, description = "Invalid sign-up data"),
+        (status = 401, description = "Invalid invite token"),
+        (status = 409, description = "The email was invited; a new invite was sent"),
+    ),
+    security(()),
+)]
 #[tracing::instrument(
     name = "Signing up for user",
-    skip(body, db_pool),
+    skip(body, db_pool, email_client, base_url, frontend_base_url),
     fields(
         username = %body.username,
         email = %body.email
@@ -55,22 +76,230 @@ impl std::fmt::Debug for SignUpError {
 pub async fn sign_up(
     body: web::Json<SignUpJsonRequestBody>,
     db_pool: web::Data<PgPool>,
+    email_client: web::Data<EmailClient>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    frontend_base_url: web::Data<FrontendBaseUrl>,
 ) -> Result<HttpResponse, SignUpError> {
-    let new_user: NewUser = body.0.try_into().map_err(SignUpError::UnexpectedError)?;
+    let invite_token = body.0.invite_token.clone();
+    let mut new_user: NewUser = body.0.try_into().map_err(SignUpError::UnexpectedError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    match invite_token {
+        Some(invite_token) => {
+            let placeholder = consume_invite_token(&mut transaction, &invite_token)
+                .await?
+                .ok_or(SignUpError::InvalidInviteToken)?;
+            // Following the invite link proves ownership of the invited address.
+            if placeholder.email == new_user.email {
+                new_user.status = UserStatus::Active;
+            }
+            new_user.user_id = placeholder.user_id;
+            claim_placeholder_user(&mut transaction, &new_user).await?;
+        }
+        None => {
+            if let Some(placeholder_id) =
+                find_placeholder_user(&mut transaction, &new_user.email).await?
+            {
+                let invite =
+                    create_invite(&mut transaction, placeholder_id, &new_user.email).await?;
+                transaction
+                    .commit()
+                    .await
+                    .context("Failed to commit SQL transaction to store an invite.")?;
+                send_invite_email(&email_client, &invite, &frontend_base_url.0)
+                    .await
+                    .context("Failed to send an invite email.")?;
+                return Err(SignUpError::InvitePending);
+            }
+            insert_user(&mut transaction, &new_user).await?
+        }
+    }
+
+    let token = match new_user.status {
+        UserStatus::Active => None,
+        _ => {
+            let token = generate_token();
+            store_token(&mut transaction, new_user.user_id, &token).await?;
+            Some(token)
+        }
+    };
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new user.")?;
+
+    if let Some(token) = token {
+        send_confirmation_email(&email_client, &new_user.email, &base_url.0, &token)
+            .await
+            .context("Failed to send a confirmation email.")?;
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+struct Placeholder {
+    user_id: Uuid,
+    email: String,
+}
+
+/// Consume every invite sent to the placeholder, not just the one being used.
+/// Expired invites are not accepted.
+#[tracing::instrument(name = "Consume invite token", skip(transaction, token))]
+async fn consume_invite_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &str,
+) -> Result<Option<Placeholder>, anyhow::Error> {
+    let placeholder = sqlx::query!(
+        r#"
+        SELECT users.user_id, users.email
+        FROM invite_tokens
+        JOIN users ON users.user_id = invite_tokens.user_id
+        WHERE invite_tokens.token_hash = $1
+            AND invite_tokens.expires_at > now()
+            AND users.status = $2
+        FOR UPDATE
+        "#,
+        hash_token(token),
+        UserStatus::Invited.to_string()
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to look up the invite token.")?
+    .map(|row| Placeholder {
+        user_id: row.user_id,
+        email: row.email,
+    });
+
+    if let Some(placeholder) = &placeholder {
+        sqlx::query!(
+            "DELETE FROM invite_tokens WHERE user_id = $1",
+            placeholder.user_id
+        )
+        .execute(&mut **transaction)
+        .await
+        .context("Failed to consume the invite tokens.")?;
+    }
+    Ok(placeholder)
+}
+
+/// Invited addresses have to sign up through an invite so the debts recorded
+/// against them carry over.
+#[tracing::instrument(name = "Find placeholder user", skip(transaction))]
+async fn find_placeholder_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    email: &str,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let user_id = sqlx::query_scalar!(
+        "SELECT user_id FROM users WHERE email = $1 AND status = $2 FOR UPDATE",
+        email,
+        UserStatus::Invited.to_string()
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to look up invited users.")?;
+    Ok(user_id)
+}
 
+/// The placeholder keeps its ID, so the debts recorded against it carry over.
+#[tracing::instrument(name = "Claim placeholder user", skip(transaction, new_user))]
+async fn claim_placeholder_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    new_user: &NewUser,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET username = $1, password_hash = $2, email = $3, status = $4
+        WHERE user_id = $5
+        "#,
+        new_user.username,
+        new_user.password_hash,
+        new_user.email,
+        new_user.status.to_string(),
+        new_user.user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to claim the placeholder user.")?;
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Saving new user details in the database",
+    skip(transaction, new_user)
+)]
+async fn insert_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    new_user: &NewUser,
+) -> Result<(), anyhow::Error> {
     sqlx::query!(
         r#"
-        INSERT INTO users (user_id, username, password_hash, email)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO users (user_id, username, password_hash, email, status)
+        VALUES ($1, $2, $3, $4, $5)
         "#,
         new_user.user_id,
         new_user.username,
         new_user.password_hash,
-        new_user.email
+        new_user.email,
+        new_user.status.to_string()
     )
-    .execute(db_pool.get_ref())
+    .execute(&mut **transaction)
     .await
     .context("Failed to insert new user into the database")?;
+    Ok(())
+}
 
-    Ok(HttpResponse::Ok().finish())
+#[tracing::instrument(
+    name = "Store confirmation token in the database",
+    skip(transaction, token)
+)]
+async fn store_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    token: &str,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_tokens (token_hash, user_id, expires_at)
+        VALUES ($1, $2, now() + interval '1 day')
+        "#,
+        hash_token(token),
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert the confirmation token into the database.")?;
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Send a confirmation email to a new user",
+    skip(email_client, token)
+)]
+async fn send_confirmation_email(
+    email_client: &EmailClient,
+    recipient: &str,
+    base_url: &str,
+    token: &str,
+) -> Result<(), reqwest::Error> {
+    let confirmation_link = format!("{}/sign_up/confirm?token={}", base_url, token);
+    let plain_body = format!(
+        "Welcome to debt-tracer!\nVisit {} to confirm your account.\n\
+        The link expires in one day.",
+        confirmation_link
+    );
+    let html_body = format!(
+        "Welcome to debt-tracer!<br />\
+        Click <a href=\"{}\">here</a> to confirm your account.<br />\
+        The link expires in one day.",
+        confirmation_link
+    );
+    email_client
+        .send_email(recipient, "Confirm your account", &html_body, &plain_body)
+        .await
 }
@@ -0,0 +1,233 @@
+use crate::authentication::UserId;
+use crate::pdf::{PdfDocument, Style};
+use crate::utils::error_chain_fmt;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::{Month, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatementQuery {
+    /// `pdf`, the default, or `json`.
+    format: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct StatementJSONResponse {
+    pub year: i32,
+    pub month: u32,
+    pub lines: Vec<StatementLineJSONResponse>,
+}
+
+/// Amounts are signed from the user's point of view: positive when the
+/// counterparty owes the user. The closing balance is the opening balance plus
+/// new debts minus payments.
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct StatementLineJSONResponse {
+    pub counterparty_id: String,
+    pub counterparty_name: String,
+    pub currency: String,
+    pub opening_balance: Decimal,
+    pub new_debts: Decimal,
+    pub payments: Decimal,
+    pub closing_balance: Decimal,
+}
+
+#[derive(thiserror::Error)]
+pub enum StatementError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for StatementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for StatementError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            StatementError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            StatementError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Monthly statement per counterparty and currency, as a PDF unless
+/// `format=json` is asked for. The month follows the user's timezone.
+#[utoipa::path(
+    get,
+    path = "/statements/{year}/{month}",
+    tag = "reports",
+    params(("year" = i32, Path), ("month" = u32, Path, description = "1 to 12"), StatementQuery),
+    responses(
+        (
+            status = 200,
+            content(
+                (StatementJSONResponse = "application/json"),
+                (String = "application/pdf"),
+            ),
+        ),
+        (status = 400, description = "Invalid month or format"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting a monthly statement", skip(query, db_pool))]
+pub async fn get_statement(
+    path: web::Path<(i32, u32)>,
+    query: web::Query<StatementQuery>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, StatementError> {
+    let (year, month) = path.into_inner();
+    if year < 1 || NaiveDate::from_ymd_opt(year, month, 1).is_none() {
+        return Err(StatementError::ValidationError(format!(
+            "{}/{} is not a valid month",
+            year, month
+        )));
+    }
+    let lines = fetch_statement_lines(**user_id, year, month, &db_pool).await?;
+    let statement = StatementJSONResponse { year, month, lines };
+
+    match query.format.as_deref().unwrap_or("pdf") {
+        "json" => Ok(HttpResponse::Ok().json(statement)),
+        "pdf" => {
+            let username = sqlx::query!("SELECT username FROM users WHERE user_id = $1", **user_id)
+                .fetch_one(db_pool.get_ref())
+                .await
+                .context("Failed to fetch the username.")?
+                .username;
+            Ok(HttpResponse::Ok()
+                .content_type("application/pdf")
+                .insert_header(ContentDisposition {
+                    disposition: DispositionType::Attachment,
+                    parameters: vec![DispositionParam::Filename(format!(
+                        "statement-{}-{:02}.pdf",
+                        year, month
+                    ))],
+                })
+                .body(render_statement(&username, &statement)))
+        }
+        format => Err(StatementError::ValidationError(format!(
+            "{} is not a valid statement format",
+            format
+        ))),
+    }
+}
+
+#[tracing::instrument(name = "Fetching statement lines", skip(pool))]
+async fn fetch_statement_lines(
+    user_id: Uuid,
+    year: i32,
+    month: u32,
+    pool: &PgPool,
+) -> Result<Vec<StatementLineJSONResponse>, anyhow::Error> {
+    let lines = sqlx::query!(
+        r#"
+        WITH period AS (
+            SELECT make_date($2, $3, 1)::timestamp AT TIME ZONE timezone AS period_start,
+                (make_date($2, $3, 1) + interval '1 month') AT TIME ZONE timezone AS period_end
+            FROM users
+            WHERE user_id = $1
+        ), entries AS (
+            SELECT
+                CASE WHEN creditor_id = $1 THEN debtor_id ELSE creditor_id END AS counterparty_id,
+                currency,
+                CASE WHEN creditor_id = $1 THEN amount ELSE -amount END AS amount,
+                created_at,
+                paid_at
+            FROM debts
+            WHERE (creditor_id = $1 OR debtor_id = $1) AND creditor_id <> debtor_id
+        )
+        SELECT entries.counterparty_id AS "counterparty_id!", users.username, entries.currency,
+            COALESCE(SUM(entries.amount) FILTER (
+                WHERE entries.created_at < period_start
+                    AND (entries.paid_at IS NULL OR entries.paid_at >= period_start)
+            ), 0) AS "opening_balance!",
+            COALESCE(SUM(entries.amount) FILTER (
+                WHERE entries.created_at >= period_start AND entries.created_at < period_end
+            ), 0) AS "new_debts!",
+            COALESCE(SUM(entries.amount) FILTER (
+                WHERE entries.paid_at >= period_start AND entries.paid_at < period_end
+            ), 0) AS "payments!",
+            COALESCE(SUM(entries.amount) FILTER (
+                WHERE entries.created_at < period_end
+                    AND (entries.paid_at IS NULL OR entries.paid_at >= period_end)
+            ), 0) AS "closing_balance!"
+        FROM entries
+        CROSS JOIN period
+        JOIN users ON users.user_id = entries.counterparty_id
+        WHERE entries.created_at < period_end
+            AND (entries.paid_at IS NULL OR entries.paid_at >= period_start)
+        GROUP BY entries.counterparty_id, users.username, entries.currency
+        ORDER BY users.username, entries.currency
+        "#,
+        user_id,
+        year,
+        month as i32
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to compute the statement.")?
+    .into_iter()
+    .map(|row| StatementLineJSONResponse {
+        counterparty_id: row.counterparty_id.to_string(),
+        counterparty_name: row.username,
+        currency: row.currency,
+        opening_balance: row.opening_balance,
+        new_debts: row.new_debts,
+        payments: row.payments,
+        closing_balance: row.closing_balance,
+    })
+    .collect();
+    Ok(lines)
+}
+
+fn render_statement(username: &str, statement: &StatementJSONResponse) -> Vec<u8> {
+    let month = u8::try_from(statement.month)
+        .ok()
+        .and_then(|month| Month::try_from(month).ok())
+        .map(|month| month.name())
+        .unwrap_or_default();
+    let mut document = PdfDocument::new();
+    document
+        .line(Style::Heading, format!("Statement for {}", username))
+        .line(Style::Text, format!("{} {}", month, statement.year))
+        .blank_line();
+    if statement.lines.is_empty() {
+        document.line(Style::Text, "No debts in this period.");
+        return document.render();
+    }
+    document.line(
+        Style::Text,
+        format!(
+            "{:<20} {:<4} {:>12} {:>12} {:>12} {:>12}",
+            "Counterparty", "Cur.", "Opening", "New debts", "Payments", "Closing"
+        ),
+    );
+    for line in &statement.lines {
+        let name: String = line.counterparty_name.chars().take(20).collect();
+        document.line(
+            Style::Text,
+            format!(
+                "{:<20} {:<4} {:>12} {:>12} {:>12} {:>12}",
+                name,
+                line.currency,
+                line.opening_balance,
+                line.new_debts,
+                line.payments,
+                line.closing_balance
+            ),
+        );
+    }
+    document.render()
+}
@@ -0,0 +1,68 @@
+use crate::authentication::UserId;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(thiserror::Error)]
+pub enum RevokeApiTokenError {
+    #[error("The API token does not exist.")]
+    NotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RevokeApiTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RevokeApiTokenError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RevokeApiTokenError::NotFound => StatusCode::NOT_FOUND,
+            RevokeApiTokenError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tokens/{token_id}",
+    tag = "tokens",
+    params(("token_id" = Uuid, Path, description = "API token ID")),
+    responses(
+        (status = 200, description = "The token was revoked"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such token"),
+    ),
+)]
+#[tracing::instrument(name = "Revoking an API token", skip(db_pool))]
+pub async fn revoke_api_token(
+    token_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, RevokeApiTokenError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET revoked_at = now()
+        WHERE token_id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+        *token_id,
+        **user_id
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to revoke the API token.")?;
+
+    if result.rows_affected() == 0 {
+        return Err(RevokeApiTokenError::NotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,67 @@
+use crate::authentication::UserId;
+use crate::utils::e500;
+use actix_web::web;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct GetApiTokenJSONResponse {
+    pub token_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/tokens",
+    tag = "tokens",
+    responses(
+        (status = 200, body = Vec<GetApiTokenJSONResponse>),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting list of API tokens by User ID", skip(db_pool))]
+pub async fn get_api_tokens(
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<Vec<GetApiTokenJSONResponse>>, actix_web::Error> {
+    let tokens = fetch_api_tokens(**user_id, db_pool.get_ref())
+        .await
+        .map_err(e500)?;
+    Ok(web::Json(tokens))
+}
+
+#[tracing::instrument(name = "Fetching API tokens by User ID", skip(pool))]
+pub async fn fetch_api_tokens(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Vec<GetApiTokenJSONResponse>, anyhow::Error> {
+    let tokens = sqlx::query!(
+        r#"
+        SELECT token_id, name, scopes, created_at, last_used_at
+        FROM api_tokens
+        WHERE user_id = $1 AND revoked_at IS NULL
+        ORDER BY created_at
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch API tokens from the database.")?
+    .into_iter()
+    .map(|row| GetApiTokenJSONResponse {
+        token_id: row.token_id.to_string(),
+        name: row.name,
+        scopes: row.scopes,
+        created_at: row.created_at.to_string(),
+        last_used_at: row
+            .last_used_at
+            .map(|last_used_at| last_used_at.to_string()),
+    })
+    .collect();
+    Ok(tokens)
+}
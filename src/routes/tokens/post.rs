@@ -0,0 +1,113 @@
+use crate::authentication::{generate_token, hash_token, UserId};
+use crate::domain::ApiTokenScope;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use unicode_segmentation::UnicodeSegmentation;
+use uuid::Uuid;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateApiTokenJsonData {
+    name: String,
+    scopes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct CreateApiTokenJSONResponse {
+    pub token_id: String,
+    pub token: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum CreateApiTokenError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateApiTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateApiTokenError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateApiTokenError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            CreateApiTokenError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/tokens",
+    tag = "tokens",
+    request_body = CreateApiTokenJsonData,
+    responses(
+        (status = 200, body = CreateApiTokenJSONResponse),
+        (status = 400, description = "Invalid name or scopes"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(
+    name = "Creating an API token",
+    skip(body, db_pool),
+    fields(name = %body.name, scopes = ?body.scopes)
+)]
+pub async fn create_api_token(
+    body: web::Json<CreateApiTokenJsonData>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<CreateApiTokenJSONResponse>, CreateApiTokenError> {
+    let body = body.into_inner();
+
+    let name = body.name.trim();
+    if name.is_empty() || name.graphemes(true).count() > 100 {
+        return Err(CreateApiTokenError::ValidationError(
+            "The token name must be between 1 and 100 characters long.".to_string(),
+        ));
+    }
+    if body.scopes.is_empty() {
+        return Err(CreateApiTokenError::ValidationError(
+            "At least one scope is required.".to_string(),
+        ));
+    }
+    let scopes = body
+        .scopes
+        .iter()
+        .map(|scope| ApiTokenScope::parse(scope).map(|scope| scope.to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(CreateApiTokenError::ValidationError)?;
+
+    let token_id = Uuid::new_v4();
+    let token = generate_token();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO api_tokens (token_id, user_id, name, token_hash, scopes, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        token_id,
+        **user_id,
+        name,
+        hash_token(&token),
+        &scopes,
+        Utc::now()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to insert new API token into the database.")?;
+
+    Ok(web::Json(CreateApiTokenJSONResponse {
+        token_id: token_id.to_string(),
+        token,
+    }))
+}
@@ -0,0 +1,113 @@
+use crate::authentication::{
+    generate_recovery_codes, get_totp, record_totp_step, replace_recovery_codes, verify_totp_code,
+    UserId, Username,
+};
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ConfirmTotpJsonData {
+    #[schema(value_type = String)]
+    code: Secret<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct ConfirmTotpJSONResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum ConfirmTotpError {
+    #[error("TOTP enrollment has not been started.")]
+    NotStarted,
+    #[error("Two-factor authentication is already enabled.")]
+    AlreadyEnabled,
+    #[error("The code is invalid.")]
+    InvalidCode,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ConfirmTotpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ConfirmTotpError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConfirmTotpError::NotStarted => StatusCode::BAD_REQUEST,
+            ConfirmTotpError::AlreadyEnabled => StatusCode::CONFLICT,
+            ConfirmTotpError::InvalidCode => StatusCode::UNAUTHORIZED,
+            ConfirmTotpError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/2fa/totp/confirm",
+    tag = "auth",
+    request_body = ConfirmTotpJsonData,
+    responses(
+        (status = 200, body = ConfirmTotpJSONResponse),
+        (status = 400, description = "Enrollment was not started"),
+        (status = 401, description = "Not logged in or invalid code"),
+        (status = 409, description = "Two-factor authentication is already enabled"),
+    ),
+)]
+#[tracing::instrument(name = "Confirming TOTP enrollment", skip(body, db_pool, username))]
+pub async fn confirm_totp(
+    body: web::Json<ConfirmTotpJsonData>,
+    user_id: web::ReqData<UserId>,
+    username: web::ReqData<Username>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<ConfirmTotpJSONResponse>, ConfirmTotpError> {
+    let user_id = **user_id;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let totp = get_totp(user_id, &mut *transaction)
+        .await?
+        .ok_or(ConfirmTotpError::NotStarted)?;
+    if totp.confirmed {
+        return Err(ConfirmTotpError::AlreadyEnabled);
+    }
+
+    let step = verify_totp_code(
+        &totp.secret,
+        &username,
+        body.code.expose_secret().trim(),
+        totp.last_used_step,
+    )?
+    .ok_or(ConfirmTotpError::InvalidCode)?;
+    record_totp_step(user_id, step, &mut *transaction).await?;
+
+    sqlx::query!(
+        "UPDATE totp_secrets SET confirmed_at = now() WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to confirm the TOTP secret.")?;
+
+    let recovery_codes = generate_recovery_codes();
+    replace_recovery_codes(&mut transaction, user_id, &recovery_codes).await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to confirm TOTP enrollment.")?;
+
+    Ok(web::Json(ConfirmTotpJSONResponse { recovery_codes }))
+}
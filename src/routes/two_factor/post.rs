@@ -0,0 +1,85 @@
+use crate::authentication::{generate_totp_secret, totp_uri, UserId, Username};
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct EnrollTotpJSONResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum EnrollTotpError {
+    #[error("Two-factor authentication is already enabled.")]
+    AlreadyEnabled,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for EnrollTotpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for EnrollTotpError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EnrollTotpError::AlreadyEnabled => StatusCode::CONFLICT,
+            EnrollTotpError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Enrollment only takes effect once a code generated from the secret is confirmed.
+#[utoipa::path(
+    post,
+    path = "/2fa/totp",
+    tag = "auth",
+    responses(
+        (status = 200, body = EnrollTotpJSONResponse),
+        (status = 401, description = "Not logged in"),
+        (status = 409, description = "Two-factor authentication is already enabled"),
+    ),
+)]
+#[tracing::instrument(name = "Starting TOTP enrollment", skip(db_pool, username))]
+pub async fn enroll_totp(
+    user_id: web::ReqData<UserId>,
+    username: web::ReqData<Username>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<EnrollTotpJSONResponse>, EnrollTotpError> {
+    let user_id = **user_id;
+    let secret = generate_totp_secret();
+    let otpauth_uri = totp_uri(&secret, &username)?;
+
+    let stored = sqlx::query!(
+        r#"
+        INSERT INTO totp_secrets (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, confirmed_at = NULL, last_used_step = NULL
+        WHERE totp_secrets.confirmed_at IS NULL
+        "#,
+        user_id,
+        secret.expose_secret()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to store the TOTP secret.")?
+    .rows_affected();
+    // A confirmed secret is left alone, even if it was confirmed concurrently.
+    if stored == 0 {
+        return Err(EnrollTotpError::AlreadyEnabled);
+    }
+
+    Ok(web::Json(EnrollTotpJSONResponse {
+        secret: secret.expose_secret().clone(),
+        otpauth_uri,
+    }))
+}
@@ -0,0 +1,115 @@
+use crate::authentication::hash_token;
+use crate::domain::UserStatus;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConfirmEmailChangeParameters {
+    token: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum ConfirmEmailChangeError {
+    #[error("The confirmation token is invalid.")]
+    InvalidToken,
+    #[error("The email address is already in use.")]
+    EmailTaken,
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ConfirmEmailChangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ConfirmEmailChangeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConfirmEmailChangeError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ConfirmEmailChangeError::EmailTaken => StatusCode::CONFLICT,
+            ConfirmEmailChangeError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/user/email/confirm",
+    tag = "users",
+    params(ConfirmEmailChangeParameters),
+    responses(
+        (status = 200, description = "The new email is confirmed"),
+        (status = 401, description = "Invalid token"),
+        (status = 409, description = "The email is taken"),
+    ),
+    security(()),
+)]
+#[tracing::instrument(name = "Confirming an email change", skip(parameters, db_pool))]
+pub async fn confirm_email_change(
+    parameters: web::Query<ConfirmEmailChangeParameters>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ConfirmEmailChangeError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let (user_id, new_email) = consume_email_change_token(&mut transaction, &parameters.token)
+        .await?
+        .ok_or(ConfirmEmailChangeError::InvalidToken)?;
+
+    sqlx::query!(
+        "UPDATE users SET email = $1 WHERE user_id = $2",
+        new_email,
+        user_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(e) if e.is_unique_violation() => ConfirmEmailChangeError::EmailTaken,
+        e => ConfirmEmailChangeError::UnexpectedError(
+            anyhow::Error::new(e).context("Failed to update the email address."),
+        ),
+    })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to confirm an email change.")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Links sent before the account was deleted or disabled are void.
+#[tracing::instrument(name = "Consume email change token", skip(transaction, token))]
+async fn consume_email_change_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &str,
+) -> Result<Option<(Uuid, String)>, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM email_change_tokens
+        USING users
+        WHERE email_change_tokens.token_hash = $1
+            AND email_change_tokens.expires_at > now()
+            AND users.user_id = email_change_tokens.user_id
+            AND users.status = $2
+        RETURNING email_change_tokens.user_id, email_change_tokens.new_email
+        "#,
+        hash_token(token),
+        UserStatus::Active.to_string()
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to consume the email change token.")?
+    .map(|row| (row.user_id, row.new_email));
+    Ok(row)
+}
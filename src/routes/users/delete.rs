@@ -0,0 +1,220 @@
+use crate::authentication::{
+    validate_credentials, validate_second_factor, AuthError, Credentials, UserId, Username,
+};
+use crate::domain::{UserRole, UserStatus};
+use crate::session_state::TypedSession;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::Utc;
+use secrecy::Secret;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// How recently an account without a password must have logged in to be
+/// deleted without a second factor code.
+const RECENT_LOGIN: chrono::Duration = chrono::Duration::minutes(5);
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct DeleteUserJsonData {
+    /// Required for accounts with a password.
+    #[schema(value_type = Option<String>)]
+    password: Option<Secret<String>>,
+    /// A TOTP or recovery code, for accounts without a password.
+    code: Option<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum DeleteUserError {
+    #[error("Failed to confirm your identity.")]
+    AuthError(#[source] anyhow::Error),
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DeleteUserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeleteUserError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeleteUserError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            DeleteUserError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/user",
+    tag = "users",
+    request_body = DeleteUserJsonData,
+    responses(
+        (status = 200, description = "The account was deleted"),
+        (status = 401, description = "Not logged in, or the identity could not be confirmed"),
+    ),
+)]
+#[tracing::instrument(
+    name = "Deleting user",
+    skip(body, db_pool, session, username),
+    fields(user_id = %*user_id)
+)]
+pub async fn delete_user(
+    body: web::Json<DeleteUserJsonData>,
+    db_pool: web::Data<PgPool>,
+    user_id: web::ReqData<UserId>,
+    username: web::ReqData<Username>,
+    session: TypedSession,
+) -> Result<HttpResponse, DeleteUserError> {
+    reauthenticate(
+        body.into_inner(),
+        **user_id,
+        username.into_inner().to_string(),
+        &session,
+        &db_pool,
+    )
+    .await?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    delete_credentials(&mut transaction, **user_id).await?;
+    anonymise_user(&mut transaction, **user_id).await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to delete a user.")?;
+
+    session.log_out();
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Confirm identity before deletion",
+    skip(body, username, session, db_pool)
+)]
+async fn reauthenticate(
+    body: DeleteUserJsonData,
+    user_id: Uuid,
+    username: String,
+    session: &TypedSession,
+    db_pool: &PgPool,
+) -> Result<(), DeleteUserError> {
+    let has_password = sqlx::query_scalar!(
+        r#"SELECT password_hash IS NOT NULL AS "has_password!" FROM users WHERE user_id = $1"#,
+        user_id
+    )
+    .fetch_one(db_pool)
+    .await
+    .context("Failed to check whether the user has a password.")?;
+
+    if has_password {
+        let password = body
+            .password
+            .ok_or_else(|| DeleteUserError::AuthError(anyhow::anyhow!("No password given.")))?;
+        let credentials = Credentials { username, password };
+        return match validate_credentials(credentials, db_pool).await {
+            Ok(_) => Ok(()),
+            Err(e @ AuthError::UnexpectedError(_)) => {
+                Err(DeleteUserError::UnexpectedError(e.into()))
+            }
+            Err(e) => Err(DeleteUserError::AuthError(e.into())),
+        };
+    }
+
+    if let Some(code) = body.code {
+        return match validate_second_factor(user_id, &username, &code, db_pool).await? {
+            true => Ok(()),
+            false => Err(DeleteUserError::AuthError(anyhow::anyhow!(
+                "Invalid second factor code."
+            ))),
+        };
+    }
+    let logged_in_at = session
+        .get_logged_in_at()
+        .context("Failed to read the login time from the session.")?;
+    match logged_in_at {
+        Some(logged_in_at) if Utc::now() - logged_in_at < RECENT_LOGIN => Ok(()),
+        _ => Err(DeleteUserError::AuthError(anyhow::anyhow!(
+            "The last login is not recent enough."
+        ))),
+    }
+}
+
+#[tracing::instrument(name = "Delete user credentials", skip(transaction))]
+async fn delete_credentials(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    for query in [
+        sqlx::query!("DELETE FROM api_tokens WHERE user_id = $1", user_id),
+        sqlx::query!(
+            "DELETE FROM contacts WHERE requester_id = $1 OR addressee_id = $1",
+            user_id
+        ),
+        sqlx::query!(
+            "DELETE FROM email_change_tokens WHERE user_id = $1",
+            user_id
+        ),
+        sqlx::query!("DELETE FROM invite_tokens WHERE user_id = $1", user_id),
+        // Other users' notifications about what this user did go too.
+        sqlx::query!(
+            "DELETE FROM notifications WHERE user_id = $1 OR actor_id = $1",
+            user_id
+        ),
+        sqlx::query!(
+            "DELETE FROM password_reset_tokens WHERE user_id = $1",
+            user_id
+        ),
+        sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id),
+        sqlx::query!("DELETE FROM totp_secrets WHERE user_id = $1", user_id),
+        sqlx::query!("DELETE FROM user_identities WHERE user_id = $1", user_id),
+        sqlx::query!("DELETE FROM user_tokens WHERE user_id = $1", user_id),
+        sqlx::query!("DELETE FROM webhooks WHERE user_id = $1", user_id),
+    ] {
+        query
+            .execute(&mut **transaction)
+            .await
+            .context("Failed to delete the user's credentials.")?;
+    }
+    Ok(())
+}
+
+// The row itself stays so that the counterparties' debts keep pointing at it,
+// but nothing in it identifies the person any more. Bumping the session version
+// logs out every other session.
+#[tracing::instrument(name = "Anonymise user", skip(transaction))]
+async fn anonymise_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    let placeholder = format!("deleted-{}", user_id);
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET username = $1,
+            email = $2,
+            display_name = NULL,
+            password_hash = NULL,
+            role = $3,
+            status = $4,
+            session_version = session_version + 1
+        WHERE user_id = $5
+        "#,
+        placeholder,
+        format!("{}@deleted.invalid", placeholder),
+        UserRole::User.to_string(),
+        UserStatus::Deleted.to_string(),
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to anonymise the user.")?;
+    Ok(())
+}
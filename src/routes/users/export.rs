@@ -0,0 +1,142 @@
+use crate::authentication::UserId;
+use crate::debts::{fetch_debts_by_user_id, GetDebtJSONResponse};
+use crate::routes::contacts::get::{fetch_contacts, GetContactJSONResponse};
+use crate::routes::notifications::get::{fetch_notifications, NotificationJSONResponse};
+use crate::routes::tokens::get::{fetch_api_tokens, GetApiTokenJSONResponse};
+use crate::routes::webhooks::get::{fetch_webhooks, GetWebhookJSONResponse};
+use crate::utils::e500;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web;
+use actix_web::HttpResponse;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UserExportJSONResponse {
+    pub profile: ExportedProfile,
+    pub debts: Vec<GetDebtJSONResponse>,
+    pub contacts: Vec<GetContactJSONResponse>,
+    pub api_tokens: Vec<GetApiTokenJSONResponse>,
+    pub identities: Vec<ExportedIdentity>,
+    pub notifications: Vec<NotificationJSONResponse>,
+    pub webhooks: Vec<GetWebhookJSONResponse>,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportedProfile {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: String,
+    pub role: String,
+    pub status: String,
+    pub default_currency: String,
+    pub timezone: String,
+    pub accept_debts_from_strangers: bool,
+    pub reminder_frequency: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportedIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/user/export",
+    tag = "users",
+    responses(
+        (status = 200, body = UserExportJSONResponse),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Exporting user data", skip(db_pool))]
+pub async fn export_user_data(
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = **user_id;
+    let pool = db_pool.get_ref();
+
+    let export = UserExportJSONResponse {
+        profile: fetch_profile(user_id, pool).await.map_err(e500)?,
+        debts: fetch_debts_by_user_id(user_id, pool).await.map_err(e500)?,
+        contacts: fetch_contacts(user_id, pool).await.map_err(e500)?,
+        api_tokens: fetch_api_tokens(user_id, pool).await.map_err(e500)?,
+        identities: fetch_identities(user_id, pool).await.map_err(e500)?,
+        notifications: fetch_notifications(user_id, None, 0, pool)
+            .await
+            .map_err(e500)?,
+        webhooks: fetch_webhooks(user_id, pool).await.map_err(e500)?,
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(
+                "debt-tracer-export.json".to_string(),
+            )],
+        })
+        .json(export))
+}
+
+async fn fetch_profile(user_id: Uuid, pool: &PgPool) -> Result<ExportedProfile, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT user_id, username, display_name, email, role, status, default_currency,
+            timezone, accept_debts_from_strangers, reminder_frequency, created_at
+        FROM users
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch the user profile from the database.")?;
+    Ok(ExportedProfile {
+        user_id: row.user_id.to_string(),
+        username: row.username,
+        display_name: row.display_name,
+        email: row.email,
+        role: row.role,
+        status: row.status,
+        default_currency: row.default_currency,
+        timezone: row.timezone,
+        accept_debts_from_strangers: row.accept_debts_from_strangers,
+        reminder_frequency: row.reminder_frequency,
+        created_at: row.created_at.to_string(),
+    })
+}
+
+async fn fetch_identities(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Vec<ExportedIdentity>, anyhow::Error> {
+    let identities = sqlx::query!(
+        r#"
+        SELECT issuer, subject, email, created_at
+        FROM user_identities
+        WHERE user_id = $1
+        ORDER BY created_at
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch linked identities from the database.")?
+    .into_iter()
+    .map(|row| ExportedIdentity {
+        issuer: row.issuer,
+        subject: row.subject,
+        email: row.email,
+        created_at: row.created_at.to_string(),
+    })
+    .collect();
+    Ok(identities)
+}
@@ -1,13 +1,58 @@
-use crate::authentication::{UserId, UserInfo, Username};
+use crate::authentication::UserId;
+use crate::utils::e500;
 use actix_web::web;
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
 
-#[tracing::instrument(name = "Getting user info by User ID")]
+pub use debt_tracer_types::GetUserJSONResponse;
+
+#[utoipa::path(
+    get,
+    path = "/user",
+    tag = "users",
+    responses(
+        (status = 200, body = GetUserJSONResponse),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting user info by User ID", skip(db_pool))]
 pub async fn get_user_info_by_id(
     user_id: web::ReqData<UserId>,
-    username: web::ReqData<Username>,
-) -> Result<web::Json<UserInfo>, actix_web::Error> {
-    Ok(web::Json(UserInfo {
-        user_id: *user_id.into_inner(),
-        username: username.into_inner().to_string(),
-    }))
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<GetUserJSONResponse>, actix_web::Error> {
+    let user = fetch_user_profile(**user_id, db_pool.get_ref())
+        .await
+        .map_err(e500)?;
+    Ok(web::Json(user))
+}
+
+#[tracing::instrument(name = "Fetching user profile", skip(pool))]
+pub async fn fetch_user_profile(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<GetUserJSONResponse, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT user_id, username, display_name, email, default_currency, timezone,
+            accept_debts_from_strangers, reminder_frequency, created_at
+        FROM users
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch the user profile from the database.")?;
+    Ok(GetUserJSONResponse {
+        user_id: row.user_id.to_string(),
+        username: row.username,
+        display_name: row.display_name,
+        email: row.email,
+        default_currency: row.default_currency,
+        timezone: row.timezone,
+        accept_debts_from_strangers: row.accept_debts_from_strangers,
+        reminder_frequency: row.reminder_frequency,
+        created_at: row.created_at.to_string(),
+    })
 }
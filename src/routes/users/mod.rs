@@ -1 +1,6 @@
+pub mod confirm_email;
+pub mod delete;
+pub mod export;
 pub mod get;
+pub mod patch;
+pub mod search;
@@ -0,0 +1,231 @@
+use super::get::{fetch_user_profile, GetUserJSONResponse};
+use crate::authentication::{generate_token, hash_token, UserId};
+use crate::domain::{DebtCurrency, DisplayName, ReminderFrequency, UserEmail, UserTimezone};
+use crate::email_client::EmailClient;
+use crate::startup::ApplicationBaseUrl;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct UpdateUserJsonData {
+    display_name: Option<String>,
+    email: Option<String>,
+    default_currency: Option<String>,
+    timezone: Option<String>,
+    accept_debts_from_strangers: Option<bool>,
+    reminder_frequency: Option<String>,
+}
+
+struct UserUpdate {
+    display_name: Option<DisplayName>,
+    email: Option<UserEmail>,
+    default_currency: Option<DebtCurrency>,
+    timezone: Option<UserTimezone>,
+    accept_debts_from_strangers: Option<bool>,
+    reminder_frequency: Option<ReminderFrequency>,
+}
+
+impl TryFrom<UpdateUserJsonData> for UserUpdate {
+    type Error = String;
+
+    fn try_from(json_data: UpdateUserJsonData) -> Result<Self, Self::Error> {
+        let display_name = json_data.display_name.map(DisplayName::parse).transpose()?;
+        let email = json_data.email.map(UserEmail::parse).transpose()?;
+        let default_currency = json_data
+            .default_currency
+            .map(DebtCurrency::parse)
+            .transpose()?;
+        let timezone = json_data.timezone.map(UserTimezone::parse).transpose()?;
+        let reminder_frequency = json_data
+            .reminder_frequency
+            .as_deref()
+            .map(ReminderFrequency::parse)
+            .transpose()?;
+
+        Ok(Self {
+            display_name,
+            email,
+            default_currency,
+            timezone,
+            accept_debts_from_strangers: json_data.accept_debts_from_strangers,
+            reminder_frequency,
+        })
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum UpdateUserError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for UpdateUserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UpdateUserError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UpdateUserError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            UpdateUserError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Update the profile. A new email only replaces the current one once the link
+/// sent to it has been followed.
+#[utoipa::path(
+    patch,
+    path = "/user",
+    tag = "users",
+    request_body = UpdateUserJsonData,
+    responses(
+        (status = 200, body = GetUserJSONResponse),
+        (status = 400, description = "Invalid profile data"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(
+    name = "Updating user profile",
+    skip(body, db_pool, email_client, base_url),
+    fields(user_id = %*user_id)
+)]
+pub async fn update_user(
+    body: web::Json<UpdateUserJsonData>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<EmailClient>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    user_id: web::ReqData<UserId>,
+) -> Result<web::Json<GetUserJSONResponse>, UpdateUserError> {
+    let update: UserUpdate = body
+        .into_inner()
+        .try_into()
+        .map_err(UpdateUserError::ValidationError)?;
+    let user_id = **user_id;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    update_profile(&mut transaction, user_id, &update).await?;
+
+    let email_change_token = match &update.email {
+        Some(email) => {
+            let token = generate_token();
+            store_email_change_token(&mut transaction, user_id, email.as_ref(), &token).await?;
+            Some((email, token))
+        }
+        None => None,
+    };
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update a user profile.")?;
+
+    if let Some((email, token)) = email_change_token {
+        send_email_change_email(&email_client, email.as_ref(), &base_url.0, &token)
+            .await
+            .context("Failed to send an email change confirmation.")?;
+    }
+
+    let profile = fetch_user_profile(user_id, &db_pool).await?;
+    Ok(web::Json(profile))
+}
+
+#[tracing::instrument(name = "Update user profile", skip(transaction, update))]
+async fn update_profile(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    update: &UserUpdate,
+) -> Result<(), UpdateUserError> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET display_name = COALESCE($1, display_name),
+            default_currency = COALESCE($2, default_currency),
+            timezone = COALESCE($3, timezone),
+            accept_debts_from_strangers = COALESCE($4, accept_debts_from_strangers),
+            reminder_frequency = COALESCE($5, reminder_frequency)
+        WHERE user_id = $6
+        "#,
+        update.display_name.as_ref().map(|name| name.as_ref()),
+        update
+            .default_currency
+            .as_ref()
+            .map(|currency| currency.to_string()),
+        update
+            .timezone
+            .as_ref()
+            .map(|timezone| timezone.to_string()),
+        update.accept_debts_from_strangers,
+        update
+            .reminder_frequency
+            .map(|frequency| frequency.to_string()),
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to update the user profile.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Store email change token", skip(transaction, token))]
+async fn store_email_change_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    new_email: &str,
+    token: &str,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO email_change_tokens (token_hash, user_id, new_email, expires_at)
+        VALUES ($1, $2, $3, now() + interval '1 day')
+        "#,
+        hash_token(token),
+        user_id,
+        new_email
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert the email change token into the database.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Send an email change confirmation", skip(email_client, token))]
+async fn send_email_change_email(
+    email_client: &EmailClient,
+    recipient: &str,
+    base_url: &str,
+    token: &str,
+) -> Result<(), reqwest::Error> {
+    let confirmation_link = format!("{}/user/email/confirm?token={}", base_url, token);
+    let plain_body = format!(
+        "Visit {} to use this address for your debt-tracer account.\n\
+        The link expires in one day.",
+        confirmation_link
+    );
+    let html_body = format!(
+        "Click <a href=\"{}\">here</a> to use this address for your debt-tracer account.<br />\
+        The link expires in one day.",
+        confirmation_link
+    );
+    email_client
+        .send_email(
+            recipient,
+            "Confirm your new email address",
+            &html_body,
+            &plain_body,
+        )
+        .await
+}
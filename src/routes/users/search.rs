@@ -0,0 +1,102 @@
+use crate::authentication::UserId;
+use crate::domain::{ContactStatus, UserStatus};
+use crate::utils::{e400, e500};
+use actix_web::web;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+const MAX_SEARCH_RESULTS: i64 = 20;
+
+#[derive(Deserialize, Debug, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchUsersParameters {
+    q: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct SearchUserJSONResponse {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+}
+
+/// Prefix matches are limited to the user's contacts so that the endpoint cannot
+/// be used to enumerate accounts. Anyone else has to be found by exact username.
+#[utoipa::path(
+    get,
+    path = "/users/search",
+    tag = "users",
+    params(SearchUsersParameters),
+    responses(
+        (status = 200, body = Vec<SearchUserJSONResponse>),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Searching users", skip(db_pool))]
+pub async fn search_users(
+    parameters: web::Query<SearchUsersParameters>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<Vec<SearchUserJSONResponse>>, actix_web::Error> {
+    let query = parameters.q.trim();
+    if query.is_empty() {
+        return Err(e400("The search query cannot be empty"));
+    }
+
+    let users = sqlx::query!(
+        r#"
+        SELECT user_id, username, display_name
+        FROM users
+        WHERE status = $1
+            AND user_id <> $2
+            AND (
+                username = $3
+                OR (
+                    username ILIKE $4
+                    AND user_id IN (
+                        SELECT addressee_id FROM contacts WHERE requester_id = $2 AND status = $6
+                        UNION
+                        SELECT requester_id FROM contacts WHERE addressee_id = $2 AND status = $6
+                    )
+                )
+            )
+        ORDER BY username
+        LIMIT $5
+        "#,
+        UserStatus::Active.to_string(),
+        **user_id,
+        query,
+        format!("{}%", escape_like_pattern(query)),
+        MAX_SEARCH_RESULTS,
+        ContactStatus::Accepted.to_string()
+    )
+    .fetch_all(db_pool.get_ref())
+    .await
+    .context("Failed to search users in the database.")
+    .map_err(e500)?
+    .into_iter()
+    .map(|row| SearchUserJSONResponse {
+        user_id: row.user_id.to_string(),
+        username: row.username,
+        display_name: row.display_name,
+    })
+    .collect();
+    Ok(web::Json(users))
+}
+
+fn escape_like_pattern(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_wildcards_are_escaped() {
+        assert_eq!(escape_like_pattern(r"a%b_c\d"), r"a\%b\_c\\d");
+    }
+}
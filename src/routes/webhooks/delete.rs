@@ -0,0 +1,65 @@
+use crate::authentication::UserId;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::{HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(thiserror::Error)]
+pub enum DeleteWebhookError {
+    #[error("The webhook does not exist.")]
+    NotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DeleteWebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeleteWebhookError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeleteWebhookError::NotFound => StatusCode::NOT_FOUND,
+            DeleteWebhookError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Pending deliveries are dropped along with the webhook.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(("webhook_id" = Uuid, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "The webhook was deleted"),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such webhook"),
+    ),
+)]
+#[tracing::instrument(name = "Deleting a webhook", skip(db_pool))]
+pub async fn delete_webhook(
+    webhook_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, DeleteWebhookError> {
+    let result = sqlx::query!(
+        "DELETE FROM webhooks WHERE webhook_id = $1 AND user_id = $2",
+        *webhook_id,
+        **user_id
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to delete the webhook.")?;
+
+    if result.rows_affected() == 0 {
+        return Err(DeleteWebhookError::NotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,108 @@
+use crate::authentication::UserId;
+use crate::utils::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_DELIVERIES: i64 = 100;
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct WebhookDeliveryJSONResponse {
+    pub delivery_id: String,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub last_attempt_at: Option<String>,
+    pub next_attempt_at: String,
+    pub delivered_at: Option<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum GetWebhookDeliveriesError {
+    #[error("The webhook does not exist.")]
+    NotFound,
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetWebhookDeliveriesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetWebhookDeliveriesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetWebhookDeliveriesError::NotFound => StatusCode::NOT_FOUND,
+            GetWebhookDeliveriesError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// The most recent deliveries of a webhook, newest first.
+#[utoipa::path(
+    get,
+    path = "/webhooks/{webhook_id}/deliveries",
+    tag = "webhooks",
+    params(("webhook_id" = Uuid, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, body = Vec<WebhookDeliveryJSONResponse>),
+        (status = 401, description = "Not logged in"),
+        (status = 404, description = "No such webhook"),
+    ),
+)]
+#[tracing::instrument(name = "Getting webhook deliveries", skip(db_pool))]
+pub async fn get_webhook_deliveries(
+    webhook_id: web::Path<Uuid>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<Vec<WebhookDeliveryJSONResponse>>, GetWebhookDeliveriesError> {
+    sqlx::query!(
+        "SELECT 1 AS found FROM webhooks WHERE webhook_id = $1 AND user_id = $2",
+        *webhook_id,
+        **user_id
+    )
+    .fetch_optional(db_pool.get_ref())
+    .await
+    .context("Failed to look up the webhook.")?
+    .ok_or(GetWebhookDeliveriesError::NotFound)?;
+
+    let deliveries = sqlx::query!(
+        r#"
+        SELECT delivery_id, event_type, status, attempts, response_status, last_error,
+            created_at, last_attempt_at, next_attempt_at, delivered_at
+        FROM webhook_deliveries
+        WHERE webhook_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        *webhook_id,
+        MAX_DELIVERIES
+    )
+    .fetch_all(db_pool.get_ref())
+    .await
+    .context("Failed to fetch webhook deliveries from the database.")?
+    .into_iter()
+    .map(|row| WebhookDeliveryJSONResponse {
+        delivery_id: row.delivery_id.to_string(),
+        event_type: row.event_type,
+        status: row.status,
+        attempts: row.attempts,
+        response_status: row.response_status,
+        last_error: row.last_error,
+        created_at: row.created_at.to_string(),
+        last_attempt_at: row.last_attempt_at.map(|at| at.to_string()),
+        next_attempt_at: row.next_attempt_at.to_string(),
+        delivered_at: row.delivered_at.map(|at| at.to_string()),
+    })
+    .collect();
+    Ok(web::Json(deliveries))
+}
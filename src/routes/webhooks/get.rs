@@ -0,0 +1,62 @@
+use crate::authentication::UserId;
+use crate::utils::e500;
+use actix_web::web;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct GetWebhookJSONResponse {
+    pub webhook_id: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub created_at: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, body = Vec<GetWebhookJSONResponse>),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(name = "Getting list of webhooks by User ID", skip(db_pool))]
+pub async fn get_webhooks(
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+) -> Result<web::Json<Vec<GetWebhookJSONResponse>>, actix_web::Error> {
+    let webhooks = fetch_webhooks(**user_id, db_pool.get_ref())
+        .await
+        .map_err(e500)?;
+    Ok(web::Json(webhooks))
+}
+
+pub async fn fetch_webhooks(
+    user_id: Uuid,
+    pool: &PgPool,
+) -> Result<Vec<GetWebhookJSONResponse>, anyhow::Error> {
+    let webhooks = sqlx::query!(
+        r#"
+        SELECT webhook_id, url, event_types, created_at
+        FROM webhooks
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch webhooks from the database.")?
+    .into_iter()
+    .map(|row| GetWebhookJSONResponse {
+        webhook_id: row.webhook_id.to_string(),
+        url: row.url,
+        event_types: row.event_types,
+        created_at: row.created_at.to_string(),
+    })
+    .collect();
+    Ok(webhooks)
+}
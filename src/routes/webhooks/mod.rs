@@ -0,0 +1,41 @@
+pub mod delete;
+pub mod deliveries;
+pub mod get;
+pub mod post;
+
+use crate::domain::{WebhookDeliveryStatus, WebhookEventType};
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Queue a delivery of `payload` to every webhook of `user_ids` subscribed to
+/// `event_type`. The delivery worker picks them up once the transaction commits.
+#[tracing::instrument(name = "Enqueue webhook deliveries", skip(transaction, payload))]
+pub async fn enqueue_webhook_deliveries(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_type: WebhookEventType,
+    user_ids: &[Uuid],
+    payload: &serde_json::Value,
+) -> Result<(), anyhow::Error> {
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        INSERT INTO webhook_deliveries (
+            delivery_id, webhook_id, event_type, payload, status, next_attempt_at, created_at
+        )
+        SELECT gen_random_uuid(), webhook_id, $1, $2, $3, $4, $4
+        FROM webhooks
+        WHERE user_id = ANY($5) AND $1 = ANY(event_types)
+        "#,
+        event_type.to_string(),
+        payload.to_string(),
+        WebhookDeliveryStatus::Pending.to_string(),
+        now,
+        user_ids
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to enqueue webhook deliveries.")?;
+    Ok(())
+}
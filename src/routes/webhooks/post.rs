@@ -0,0 +1,126 @@
+use crate::authentication::{generate_token, UserId};
+use crate::configuration::WebhookSettings;
+use crate::domain::WebhookEventType;
+use crate::utils::error_chain_fmt;
+use crate::webhook_delivery_worker::ip_host;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::ResponseError;
+use anyhow::Context;
+use chrono::Utc;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateWebhookJsonData {
+    url: String,
+    event_types: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct CreateWebhookJSONResponse {
+    pub webhook_id: String,
+    pub secret: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum CreateWebhookError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Internal Server Error")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateWebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateWebhookError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateWebhookError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            CreateWebhookError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Register a webhook. The signing secret is only ever returned here.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookJsonData,
+    responses(
+        (status = 200, body = CreateWebhookJSONResponse),
+        (status = 400, description = "Invalid URL or event types"),
+        (status = 401, description = "Not logged in"),
+    ),
+)]
+#[tracing::instrument(
+    name = "Creating a webhook",
+    skip(body, db_pool, webhook_settings),
+    fields(url = %body.url, event_types = ?body.event_types)
+)]
+pub async fn create_webhook(
+    body: web::Json<CreateWebhookJsonData>,
+    user_id: web::ReqData<UserId>,
+    db_pool: web::Data<PgPool>,
+    webhook_settings: web::Data<WebhookSettings>,
+) -> Result<web::Json<CreateWebhookJSONResponse>, CreateWebhookError> {
+    let body = body.into_inner();
+
+    let url = Url::parse(&body.url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .ok_or_else(|| {
+            CreateWebhookError::ValidationError(
+                "The webhook URL must be an absolute http or https URL.".to_string(),
+            )
+        })?;
+    if !webhook_settings.allow_private_targets && ip_host(&url).is_some() {
+        return Err(CreateWebhookError::ValidationError(
+            "The webhook URL must use a host name rather than an IP address.".to_string(),
+        ));
+    }
+    if body.event_types.is_empty() {
+        return Err(CreateWebhookError::ValidationError(
+            "At least one event type is required.".to_string(),
+        ));
+    }
+    let event_types = body
+        .event_types
+        .iter()
+        .map(|event_type| {
+            WebhookEventType::parse(event_type).map(|event_type| event_type.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(CreateWebhookError::ValidationError)?;
+
+    let webhook_id = Uuid::new_v4();
+    let secret = generate_token();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO webhooks (webhook_id, user_id, url, secret, event_types, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        webhook_id,
+        **user_id,
+        url.as_str(),
+        secret,
+        &event_types,
+        Utc::now()
+    )
+    .execute(db_pool.get_ref())
+    .await
+    .context("Failed to insert new webhook into the database.")?;
+
+    Ok(web::Json(CreateWebhookJSONResponse {
+        webhook_id: webhook_id.to_string(),
+        secret,
+    }))
+}
@@ -1,7 +1,10 @@
+use crate::authentication::OidcLoginState;
+use crate::domain::UserRole;
 use actix_session::SessionExt;
 use actix_session::{Session, SessionGetError, SessionInsertError};
 use actix_web::dev::Payload;
 use actix_web::{FromRequest, HttpRequest};
+use chrono::{DateTime, Utc};
 use std::future::{ready, Ready};
 use uuid::Uuid;
 
@@ -10,11 +13,21 @@ pub struct TypedSession(Session);
 impl TypedSession {
     const USER_ID_KEY: &'static str = "user_id";
     const USERNAME_KEY: &'static str = "username";
+    const SESSION_VERSION_KEY: &'static str = "session_version";
+    const ROLE_KEY: &'static str = "role";
+    const LOGGED_IN_AT_KEY: &'static str = "logged_in_at";
+    const PENDING_TWO_FACTOR_USER_ID_KEY: &'static str = "pending_two_factor_user_id";
+    const PENDING_TWO_FACTOR_USERNAME_KEY: &'static str = "pending_two_factor_username";
+    const OIDC_LOGIN_KEY: &'static str = "oidc_login";
 
     pub fn renew(&self) {
         self.0.renew();
     }
 
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+
     pub fn insert_user_id(&self, user_id: Uuid) -> Result<(), SessionInsertError> {
         self.0.insert(Self::USER_ID_KEY, user_id)
     }
@@ -31,6 +44,72 @@ impl TypedSession {
         self.0.get(Self::USERNAME_KEY)
     }
 
+    pub fn insert_session_version(&self, session_version: i32) -> Result<(), SessionInsertError> {
+        self.0.insert(Self::SESSION_VERSION_KEY, session_version)
+    }
+
+    pub fn get_session_version(&self) -> Result<Option<i32>, SessionGetError> {
+        self.0.get(Self::SESSION_VERSION_KEY)
+    }
+
+    pub fn insert_role(&self, role: UserRole) -> Result<(), SessionInsertError> {
+        self.0.insert(Self::ROLE_KEY, role.to_string())
+    }
+
+    pub fn get_role(&self) -> Result<Option<UserRole>, anyhow::Error> {
+        self.0
+            .get::<String>(Self::ROLE_KEY)?
+            .map(|role| UserRole::parse(&role).map_err(anyhow::Error::msg))
+            .transpose()
+    }
+
+    pub fn insert_logged_in_at(
+        &self,
+        logged_in_at: DateTime<Utc>,
+    ) -> Result<(), SessionInsertError> {
+        self.0
+            .insert(Self::LOGGED_IN_AT_KEY, logged_in_at.timestamp())
+    }
+
+    pub fn get_logged_in_at(&self) -> Result<Option<DateTime<Utc>>, SessionGetError> {
+        let timestamp: Option<i64> = self.0.get(Self::LOGGED_IN_AT_KEY)?;
+        Ok(timestamp.and_then(|timestamp| DateTime::from_timestamp(timestamp, 0)))
+    }
+
+    // A user who passed the password check but still has to submit a second factor.
+    pub fn insert_pending_two_factor(
+        &self,
+        user_id: Uuid,
+        username: String,
+    ) -> Result<(), SessionInsertError> {
+        self.0
+            .insert(Self::PENDING_TWO_FACTOR_USER_ID_KEY, user_id)?;
+        self.0
+            .insert(Self::PENDING_TWO_FACTOR_USERNAME_KEY, username)
+    }
+
+    pub fn get_pending_two_factor(&self) -> Result<Option<(Uuid, String)>, SessionGetError> {
+        let user_id = self.0.get(Self::PENDING_TWO_FACTOR_USER_ID_KEY)?;
+        let username = self.0.get(Self::PENDING_TWO_FACTOR_USERNAME_KEY)?;
+        Ok(user_id.zip(username))
+    }
+
+    pub fn remove_pending_two_factor(&self) {
+        self.0.remove(Self::PENDING_TWO_FACTOR_USER_ID_KEY);
+        self.0.remove(Self::PENDING_TWO_FACTOR_USERNAME_KEY);
+    }
+
+    pub fn insert_oidc_login(&self, login_state: OidcLoginState) -> Result<(), SessionInsertError> {
+        self.0.insert(Self::OIDC_LOGIN_KEY, login_state)
+    }
+
+    // The login state is single use, whether or not the callback succeeds.
+    pub fn take_oidc_login(&self) -> Result<Option<OidcLoginState>, SessionGetError> {
+        let login_state = self.0.get(Self::OIDC_LOGIN_KEY)?;
+        self.0.remove(Self::OIDC_LOGIN_KEY);
+        Ok(login_state)
+    }
+
     pub fn log_out(self) {
         self.0.purge()
     }
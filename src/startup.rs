@@ -1,20 +1,42 @@
-use crate::authentication::reject_anonymous_users;
+use crate::authentication::{reject_anonymous_users, reject_non_admin_users, LoginThrottle};
 use crate::configuration::{DatabaseSettings, Settings};
-use crate::debts::{create_debt, get_debts_by_user_id};
-use crate::routes::{get_user_info_by_id, login, sign_up};
+use crate::debts::{create_debt, get_debts_by_user_id, mark_debt_paid, remind_debtor};
+use crate::event_bus::EventBus;
+use crate::openapi::ApiDoc;
+use crate::routes::{
+    accept_contact_request, change_password, confirm_email_change, confirm_password_reset,
+    confirm_sign_up, confirm_totp, create_api_token, create_webhook, decline_contact_request,
+    delete_user, delete_webhook, disable_user, enroll_totp, export_debts, export_user_data,
+    get_api_tokens, get_contact_requests, get_contacts, get_debt, get_notifications, get_statement,
+    get_timeseries, get_user_info_by_id, get_webhook_deliveries, get_webhooks, import_debts,
+    import_splitwise, list_users, login, login_oidc, login_oidc_callback, login_two_factor,
+    mark_notifications_read, remove_contact, request_password_reset, revoke_api_token,
+    search_users, send_contact_request, sign_up, stream_events, update_user,
+};
 use actix_session::storage::RedisSessionStore;
 use actix_session::SessionMiddleware;
 use actix_web::cookie::Key;
 use actix_web::dev::Server;
 use actix_web::{web, App, HttpResponse, HttpServer, Responder};
 use actix_web_lab::middleware::from_fn;
-use secrecy::{ExposeSecret, Secret};
+use secrecy::ExposeSecret;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use std::net::TcpListener;
 use tracing_actix_web::TracingLogger;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
-async fn health_check() -> impl Responder {
+#[utoipa::path(
+    get,
+    path = "/health_check",
+    tag = "health",
+    responses(
+        (status = 200, description = "The service is up"),
+    ),
+    security(()),
+)]
+pub(crate) async fn health_check() -> impl Responder {
     HttpResponse::Ok()
 }
 
@@ -35,13 +57,7 @@ impl Application {
         let listener = TcpListener::bind(address)?;
         let port = listener.local_addr().unwrap().port();
 
-        let server = run(
-            listener,
-            db_pool,
-            configuration.application.hmac_secret,
-            configuration.redis_uri,
-        )
-        .await?;
+        let server = run(listener, db_pool, configuration).await?;
 
         Ok(Self { port, server })
     }
@@ -55,15 +71,143 @@ impl Application {
     }
 }
 
+/// Declare a group of routes once, as both the function registering them and
+/// the `(method, path)` pairs they serve, with `prefix` being the path of the
+/// scope the group is mounted in.
+macro_rules! route_group {
+    ($configure:ident, $list:ident, $prefix:literal, [$(($method:ident, $path:literal, $handler:path)),* $(,)?]) => {
+        fn $configure(cfg: &mut web::ServiceConfig) {
+            cfg$(.route($path, web::$method().to($handler)))*;
+        }
+
+        const $list: &[(&str, &str)] = &[$((stringify!($method), concat!($prefix, $path))),*];
+    };
+}
+
+route_group!(
+    public_routes,
+    PUBLIC_ROUTES,
+    "",
+    [
+        (get, "/health_check", health_check),
+        (post, "/login", login),
+        (post, "/login/2fa", login_two_factor),
+        (post, "/sign_up", sign_up),
+        (get, "/sign_up/confirm", confirm_sign_up),
+        (get, "/user/email/confirm", confirm_email_change),
+        (post, "/password/reset", request_password_reset),
+        (post, "/password/reset/confirm", confirm_password_reset),
+    ]
+);
+
+route_group!(
+    oidc_routes,
+    OIDC_ROUTES,
+    "",
+    [
+        (get, "/login/oidc", login_oidc),
+        (get, "/login/oidc/callback", login_oidc_callback),
+    ]
+);
+
+route_group!(
+    user_routes,
+    USER_ROUTES,
+    "",
+    [
+        (post, "/debt", create_debt),
+        (post, "/debt/{debt_id}/remind", remind_debtor),
+        (post, "/debt/{debt_id}/paid", mark_debt_paid),
+        (get, "/debts", get_debts_by_user_id),
+        (get, "/debts/export", export_debts),
+        (post, "/debts/import", import_debts),
+        (post, "/debts/import/splitwise", import_splitwise),
+        (post, "/password", change_password),
+        (post, "/2fa/totp", enroll_totp),
+        (post, "/2fa/totp/confirm", confirm_totp),
+        (get, "/tokens", get_api_tokens),
+        (post, "/tokens", create_api_token),
+        (delete, "/tokens/{token_id}", revoke_api_token),
+        (get, "/user", get_user_info_by_id),
+        (patch, "/user", update_user),
+        (delete, "/user", delete_user),
+        (get, "/user/export", export_user_data),
+        (get, "/users/search", search_users),
+        (get, "/events", stream_events),
+        (get, "/statements/{year}/{month}", get_statement),
+        (get, "/reports/timeseries", get_timeseries),
+        (get, "/notifications", get_notifications),
+        (post, "/notifications/read", mark_notifications_read),
+        (get, "/webhooks", get_webhooks),
+        (post, "/webhooks", create_webhook),
+        (delete, "/webhooks/{webhook_id}", delete_webhook),
+        (
+            get,
+            "/webhooks/{webhook_id}/deliveries",
+            get_webhook_deliveries
+        ),
+        (get, "/contacts", get_contacts),
+        (delete, "/contacts/{user_id}", remove_contact),
+        (get, "/contacts/requests", get_contact_requests),
+        (post, "/contacts/requests", send_contact_request),
+        (
+            post,
+            "/contacts/requests/{user_id}/accept",
+            accept_contact_request
+        ),
+        (
+            post,
+            "/contacts/requests/{user_id}/decline",
+            decline_contact_request
+        ),
+    ]
+);
+
+route_group!(
+    admin_routes,
+    ADMIN_ROUTES,
+    "/admin",
+    [
+        (get, "/users", list_users),
+        (post, "/users/{user_id}/disable", disable_user),
+        (get, "/debts/{debt_id}", get_debt),
+    ]
+);
+
+/// Every `(method, path)` pair the API serves, including the single sign-on
+/// routes that are only registered when an identity provider is configured.
+pub fn routes() -> Vec<(&'static str, &'static str)> {
+    [PUBLIC_ROUTES, OIDC_ROUTES, USER_ROUTES, ADMIN_ROUTES].concat()
+}
+
+pub struct ApplicationBaseUrl(pub String);
+
+pub struct FrontendBaseUrl(pub String);
+
 pub async fn run(
     listener: TcpListener,
     db_pool: PgPool,
-    hmac_secret: Secret<String>,
-    redis_uri: Secret<String>,
+    configuration: Settings,
 ) -> Result<Server, anyhow::Error> {
     let db_pool = web::Data::new(db_pool);
-    let secret_key = Key::from(hmac_secret.expose_secret().as_bytes());
-    let redis_store = RedisSessionStore::new(redis_uri.expose_secret()).await?;
+    let email_client = web::Data::new(configuration.email_client.client());
+    let login_throttle = web::Data::new(
+        LoginThrottle::new(&configuration.redis_uri, configuration.login_throttle).await?,
+    );
+    let event_bus = web::Data::new(EventBus::new(&configuration.redis_uri).await?);
+    let oidc_client = configuration.oidc.map(|oidc| web::Data::new(oidc.client()));
+    let base_url = web::Data::new(ApplicationBaseUrl(configuration.application.base_url));
+    let frontend_base_url =
+        web::Data::new(FrontendBaseUrl(configuration.application.frontend_base_url));
+    let webhook_settings = web::Data::new(configuration.webhooks);
+    let secret_key = Key::from(
+        configuration
+            .application
+            .hmac_secret
+            .expose_secret()
+            .as_bytes(),
+    );
+    let redis_store = RedisSessionStore::new(configuration.redis_uri.expose_secret()).await?;
     let server = HttpServer::new(move || {
         App::new()
             .wrap(SessionMiddleware::new(
@@ -71,17 +215,30 @@ pub async fn run(
                 secret_key.clone(),
             ))
             .wrap(TracingLogger::default())
-            .route("/health_check", web::get().to(health_check))
-            .route("/login", web::post().to(login))
-            .route("/sign_up", web::post().to(sign_up))
+            .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/openapi.json", ApiDoc::openapi()))
+            .configure(public_routes)
+            .configure(|cfg| {
+                if let Some(oidc_client) = &oidc_client {
+                    cfg.app_data(oidc_client.clone()).configure(oidc_routes);
+                }
+            })
             .service(
                 web::scope("")
                     .wrap(from_fn(reject_anonymous_users))
-                    .route("/debt", web::post().to(create_debt))
-                    .route("/debts", web::get().to(get_debts_by_user_id))
-                    .route("/user", web::get().to(get_user_info_by_id)),
+                    .configure(user_routes)
+                    .service(
+                        web::scope("/admin")
+                            .wrap(from_fn(reject_non_admin_users))
+                            .configure(admin_routes),
+                    ),
             )
             .app_data(db_pool.clone())
+            .app_data(email_client.clone())
+            .app_data(login_throttle.clone())
+            .app_data(base_url.clone())
+            .app_data(frontend_base_url.clone())
+            .app_data(event_bus.clone())
+            .app_data(webhook_settings.clone())
     })
     .listen(listener)?
     .run();
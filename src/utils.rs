@@ -12,6 +12,13 @@ where
     actix_web::error::ErrorUnauthorized(e)
 }
 
+pub fn e403<T>(e: T) -> actix_web::Error
+where
+    T: std::fmt::Debug + std::fmt::Display + 'static,
+{
+    actix_web::error::ErrorForbidden(e)
+}
+
 pub fn e500<T>(e: T) -> actix_web::Error
 where
     T: std::fmt::Debug + std::fmt::Display + 'static,
@@ -31,3 +38,11 @@ pub fn error_chain_fmt(
     }
     Ok(())
 }
+
+/// Escape user input before it is interpolated into an HTML email.
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,347 @@
+use crate::configuration::{Settings, WebhookSettings};
+use crate::domain::WebhookDeliveryStatus;
+use crate::startup::get_connection_pool;
+use anyhow::Context;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use uuid::Uuid;
+
+pub const SIGNATURE_HEADER: &str = "X-Debt-Tracer-Signature";
+pub const EVENT_HEADER: &str = "X-Debt-Tracer-Event";
+pub const DELIVERY_HEADER: &str = "X-Debt-Tracer-Delivery";
+
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+struct DeliveryTask {
+    delivery_id: Uuid,
+    event_type: String,
+    payload: String,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+enum DeliveryResult {
+    Delivered(u16),
+    Rejected(u16),
+    Unreachable(String),
+}
+
+pub async fn run_worker_until_stopped(configuration: Settings) -> Result<(), anyhow::Error> {
+    let connection_pool = get_connection_pool(&configuration.database);
+    let http_client = configuration.webhooks.client();
+    worker_loop(connection_pool, http_client, configuration.webhooks).await
+}
+
+async fn worker_loop(
+    pool: PgPool,
+    http_client: reqwest::Client,
+    settings: WebhookSettings,
+) -> Result<(), anyhow::Error> {
+    loop {
+        match try_execute_task(&pool, &http_client, &settings).await {
+            Ok(ExecutionOutcome::EmptyQueue) => {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            Err(_) => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+        }
+    }
+}
+
+/// Attempt the oldest delivery that is due. The row stays locked while the
+/// request is in flight, so several workers can share the queue.
+#[tracing::instrument(
+    skip_all,
+    fields(delivery_id=tracing::field::Empty, url=tracing::field::Empty),
+    err
+)]
+pub async fn try_execute_task(
+    pool: &PgPool,
+    http_client: &reqwest::Client,
+    settings: &WebhookSettings,
+) -> Result<ExecutionOutcome, anyhow::Error> {
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let task = match dequeue_task(&mut transaction).await? {
+        Some(task) => task,
+        None => return Ok(ExecutionOutcome::EmptyQueue),
+    };
+    tracing::Span::current()
+        .record("delivery_id", tracing::field::display(&task.delivery_id))
+        .record("url", tracing::field::display(&task.url));
+
+    let result = if settings.allow_private_targets || !has_private_ip_host(&task.url) {
+        deliver(http_client, &task).await
+    } else {
+        DeliveryResult::Unreachable("The webhook URL points at a private address.".to_string())
+    };
+    record_attempt(&mut transaction, &task, result, settings).await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to record a webhook delivery.")?;
+    Ok(ExecutionOutcome::TaskCompleted)
+}
+
+async fn dequeue_task(
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<Option<DeliveryTask>, anyhow::Error> {
+    let task = sqlx::query!(
+        r#"
+        SELECT webhook_deliveries.delivery_id, webhook_deliveries.event_type,
+            webhook_deliveries.payload, webhook_deliveries.attempts, webhooks.url, webhooks.secret
+        FROM webhook_deliveries
+        JOIN webhooks ON webhooks.webhook_id = webhook_deliveries.webhook_id
+        WHERE webhook_deliveries.status = $1 AND webhook_deliveries.next_attempt_at <= now()
+        ORDER BY webhook_deliveries.next_attempt_at
+        FOR UPDATE OF webhook_deliveries
+        SKIP LOCKED
+        LIMIT 1
+        "#,
+        WebhookDeliveryStatus::Pending.to_string()
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to dequeue a webhook delivery.")?
+    .map(|row| DeliveryTask {
+        delivery_id: row.delivery_id,
+        event_type: row.event_type,
+        payload: row.payload,
+        attempts: row.attempts,
+        url: row.url,
+        secret: row.secret,
+    });
+    Ok(task)
+}
+
+async fn deliver(http_client: &reqwest::Client, task: &DeliveryTask) -> DeliveryResult {
+    let response = http_client
+        .post(&task.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, sign_payload(&task.secret, &task.payload))
+        .header(EVENT_HEADER, &task.event_type)
+        .header(DELIVERY_HEADER, task.delivery_id.to_string())
+        .body(task.payload.clone())
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => {
+            DeliveryResult::Delivered(response.status().as_u16())
+        }
+        Ok(response) => DeliveryResult::Rejected(response.status().as_u16()),
+        Err(e) => DeliveryResult::Unreachable(e.to_string()),
+    }
+}
+
+async fn record_attempt(
+    transaction: &mut Transaction<'_, Postgres>,
+    task: &DeliveryTask,
+    result: DeliveryResult,
+    settings: &WebhookSettings,
+) -> Result<(), anyhow::Error> {
+    let attempts = task.attempts + 1;
+    let now = Utc::now();
+    let (status, response_status, last_error) = match result {
+        DeliveryResult::Delivered(status) => (WebhookDeliveryStatus::Delivered, Some(status), None),
+        DeliveryResult::Rejected(status) => (
+            WebhookDeliveryStatus::Pending,
+            Some(status),
+            Some(format!("The receiver responded with {}.", status)),
+        ),
+        DeliveryResult::Unreachable(e) => (WebhookDeliveryStatus::Pending, None, Some(e)),
+    };
+    let status = if status == WebhookDeliveryStatus::Pending && attempts >= settings.max_attempts {
+        tracing::warn!(attempts, "Giving up on a webhook delivery.");
+        WebhookDeliveryStatus::Failed
+    } else {
+        status
+    };
+    let next_attempt_at = now + backoff(settings.base_backoff_seconds, attempts);
+    let delivered_at = (status == WebhookDeliveryStatus::Delivered).then_some(now);
+
+    sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = $1,
+            attempts = $2,
+            last_attempt_at = $3,
+            next_attempt_at = $4,
+            response_status = $5,
+            last_error = $6,
+            delivered_at = $7
+        WHERE delivery_id = $8
+        "#,
+        status.to_string(),
+        attempts,
+        now,
+        next_attempt_at,
+        response_status.map(i32::from),
+        last_error,
+        delivered_at,
+        task.delivery_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to record a webhook delivery attempt.")?;
+    Ok(())
+}
+
+/// The client only resolves host names through `PublicAddressResolver`, which
+/// IP literals bypass, so they are checked here. Webhooks registered before IP
+/// literals were rejected may still use one.
+fn has_private_ip_host(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| ip_host(&url))
+        .is_some_and(|ip| !is_public_address(ip))
+}
+
+/// The host of `url`, if it is an IP address rather than a name.
+pub fn ip_host(url: &reqwest::Url) -> Option<IpAddr> {
+    url.host_str()?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// Whether `ip` is routed on the public internet, as opposed to loopback,
+/// private, link-local (including cloud metadata services) or otherwise
+/// reserved networks.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || first == 0
+                // Carrier-grade NAT, 100.64.0.0/10.
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local, fc00::/7.
+                    || first & 0xfe00 == 0xfc00
+                    // Link-local, fe80::/10.
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolves host names to their public addresses only. Checking the addresses
+/// the request actually connects to, rather than the URL at registration,
+/// also covers host names whose DNS records change later.
+pub struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| is_public_address(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            let addresses: reqwest::dns::Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+/// Wait `base * 2^(attempts - 1)` before the next attempt.
+fn backoff(base_backoff_seconds: u64, attempts: i32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let seconds = base_backoff_seconds.saturating_mul(2u64.pow(exponent));
+    chrono::Duration::seconds(seconds.min(i64::MAX as u64) as i64)
+}
+
+/// Receivers verify deliveries by computing the same HMAC over the raw body
+/// with the secret they were given when registering the webhook.
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_matches_the_rfc_4231_test_case() {
+        // Test case 2 of RFC 4231.
+        let signature = sign_payload("Jefe", "what do ya want for nothing?");
+
+        assert_eq!(
+            signature,
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_with_every_attempt() {
+        assert_eq!(backoff(30, 1), chrono::Duration::seconds(30));
+        assert_eq!(backoff(30, 2), chrono::Duration::seconds(60));
+        assert_eq!(backoff(30, 4), chrono::Duration::seconds(240));
+    }
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        for ip in ["93.184.216.34", "2606:2800:220:1::1"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn ip_literal_hosts_are_detected() {
+        let host = |url: &str| ip_host(&reqwest::Url::parse(url).unwrap());
+
+        assert_eq!(
+            host("http://169.254.169.254/latest"),
+            Some("169.254.169.254".parse().unwrap())
+        );
+        assert_eq!(host("https://[::1]:8080/"), Some("::1".parse().unwrap()));
+        assert_eq!(host("https://hooks.example.com/"), None);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::helpers::spawn_app;
+use debt_tracer::debts::GetDebtJSONResponse;
+use debt_tracer::routes::admin::users::AdminUserJSONResponse;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_access_the_admin_endpoints() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_admin_users().await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn regular_users_cannot_access_the_admin_endpoints() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_admin_users().await;
+
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn admins_can_list_all_users() {
+    let test_app = spawn_app().await;
+    test_app
+        .set_role(test_app.test_creditor.user_id, "admin")
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_admin_users().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let users: Vec<AdminUserJSONResponse> = response.json().await.unwrap();
+    let usernames = users
+        .iter()
+        .map(|u| u.username.as_str())
+        .collect::<Vec<_>>();
+    assert!(usernames.contains(&"admin"));
+    assert!(usernames.contains(&test_app.test_debtor.username.as_str()));
+    let creditor = users
+        .iter()
+        .find(|u| u.username == test_app.test_creditor.username)
+        .unwrap();
+    assert_eq!(creditor.role, "admin");
+}
+
+#[tokio::test]
+async fn auditors_can_read_but_not_disable_users() {
+    let test_app = spawn_app().await;
+    test_app
+        .set_role(test_app.test_creditor.user_id, "auditor")
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    assert_eq!(200, test_app.get_admin_users().await.status().as_u16());
+    let response = test_app
+        .post_disable_user(test_app.test_debtor.user_id)
+        .await;
+
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn disabled_users_are_logged_out_and_cannot_log_back_in() {
+    let test_app = spawn_app().await;
+    let debtor_client = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let login_body = serde_json::json!({
+        "username": &test_app.test_debtor.username,
+        "password": &test_app.test_debtor.password,
+    });
+    let login = |client: &reqwest::Client| {
+        client
+            .post(format!("{}/login", &test_app.address))
+            .json(&login_body)
+            .send()
+    };
+    assert_eq!(200, login(&debtor_client).await.unwrap().status().as_u16());
+    test_app
+        .set_role(test_app.test_creditor.user_id, "admin")
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_disable_user(test_app.test_debtor.user_id)
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let response = debtor_client
+        .get(format!("{}/user", &test_app.address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(401, response.status().as_u16());
+    assert_eq!(403, login(&debtor_client).await.unwrap().status().as_u16());
+}
+
+#[tokio::test]
+async fn admins_cannot_disable_themselves() {
+    let test_app = spawn_app().await;
+    test_app
+        .set_role(test_app.test_creditor.user_id, "admin")
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_disable_user(test_app.test_creditor.user_id)
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn admins_can_view_any_debt() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let debt: serde_json::Value = test_app
+        .post_debt(12.5, "USD", "Lunch")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let debt_id = debt["debt_id"].as_str().unwrap();
+    // Roles are read at login: log in again as an auditor who is not party to the debt.
+    let auditor = crate::helpers::TestUser::generate();
+    auditor.store(&test_app.db_pool).await;
+    test_app.set_role(auditor.user_id, "auditor").await;
+    test_app
+        .post_login(&serde_json::json!({
+            "username": &auditor.username,
+            "password": &auditor.password,
+        }))
+        .await;
+
+    let response = test_app.get_admin_debt(debt_id).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let debt: GetDebtJSONResponse = response.json().await.unwrap();
+    assert_eq!(debt.creditor_id, test_app.test_creditor.user_id.to_string());
+    assert_eq!(debt.description, "Lunch");
+}
+
+#[tokio::test]
+async fn viewing_an_unknown_debt_returns_404() {
+    let test_app = spawn_app().await;
+    test_app
+        .set_role(test_app.test_creditor.user_id, "admin")
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .get_admin_debt(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    assert_eq!(404, response.status().as_u16());
+}
@@ -0,0 +1,150 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::routes::tokens::get::GetApiTokenJSONResponse;
+use debt_tracer::routes::tokens::post::CreateApiTokenJSONResponse;
+use debt_tracer_client::types::{GetDebtJSONResponse, JsonData};
+use debt_tracer_client::StatusCode;
+
+async fn create_token(test_app: &TestApp, scopes: &[&str]) -> CreateApiTokenJSONResponse {
+    let response = test_app
+        .post_api_token(&serde_json::json!({
+            "name": "cli",
+            "scopes": scopes,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    response.json().await.unwrap()
+}
+
+fn debt_body(test_app: &TestApp) -> JsonData {
+    JsonData {
+        debtor_id: test_app.test_debtor.user_id.to_string(),
+        creditor_id: test_app.test_creditor.user_id.to_string(),
+        amount: 10.0,
+        currency: "USD".to_string(),
+        description: "created with a token".to_string(),
+        due_date: None,
+    }
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_create_a_token() {
+    let test_app = spawn_app().await;
+
+    let response = test_app
+        .post_api_token(&serde_json::json!({
+            "name": "cli",
+            "scopes": ["debts:read"],
+        }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn creating_a_token_with_an_unknown_scope_is_rejected() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_api_token(&serde_json::json!({
+            "name": "cli",
+            "scopes": ["everything"],
+        }))
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn created_tokens_are_listed_without_their_secret() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let created = create_token(&test_app, &["debts:read", "debts:write"]).await;
+
+    let response = test_app.get_api_tokens().await;
+    assert_eq!(200, response.status().as_u16());
+    let body = response.text().await.unwrap();
+    assert!(!body.contains(&created.token));
+
+    let tokens: Vec<GetApiTokenJSONResponse> = serde_json::from_str(&body).unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, created.token_id);
+    assert_eq!(tokens[0].scopes, vec!["debts:read", "debts:write"]);
+}
+
+#[tokio::test]
+async fn a_read_token_can_list_debts_but_not_create_them() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let created = create_token(&test_app, &["debts:read"]).await;
+    let client = test_app.token_client(&created.token);
+
+    client.get_debts().await.unwrap();
+    let user = client.get_user().await.unwrap();
+    assert_eq!(user.username, test_app.test_creditor.username);
+
+    let error = client.create_debt(&debt_body(&test_app)).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::FORBIDDEN));
+}
+
+#[tokio::test]
+async fn a_write_token_can_create_debts() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let created = create_token(&test_app, &["debts:write"]).await;
+
+    let client = test_app.token_client(&created.token);
+
+    let debt = client.create_debt(&debt_body(&test_app)).await.unwrap();
+
+    let debts: Vec<GetDebtJSONResponse> = test_app
+        .get_debts_as_test_creditor()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(debts[0].debt_id, debt.debt_id);
+}
+
+#[tokio::test]
+async fn tokens_cannot_be_used_to_manage_tokens() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let created = create_token(&test_app, &["debts:read", "debts:write"]).await;
+
+    let response = test_app
+        .token_client(&created.token)
+        .get("/tokens")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn unknown_and_revoked_tokens_are_rejected() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let created = create_token(&test_app, &["debts:read"]).await;
+    let error = test_app
+        .token_client("not-a-real-token")
+        .get_debts()
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+
+    let response = test_app.delete_api_token(&created.token_id).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let error = test_app
+        .token_client(&created.token)
+        .get_debts()
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+
+    let response = test_app.delete_api_token(&created.token_id).await;
+    assert_eq!(404, response.status().as_u16());
+}
@@ -0,0 +1,151 @@
+use crate::helpers::spawn_app;
+use debt_tracer_client::StatusCode;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_change_your_password() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    let response = test_app
+        .post_change_password(&serde_json::json!({
+            "current_password": Uuid::new_v4().to_string(),
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn new_password_fields_must_match() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_change_password(&serde_json::json!({
+            "current_password": &test_app.test_creditor.password,
+            "new_password": Uuid::new_v4().to_string(),
+            "new_password_check": Uuid::new_v4().to_string(),
+        }))
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn new_password_must_be_long_enough() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_change_password(&serde_json::json!({
+            "current_password": &test_app.test_creditor.password,
+            "new_password": "short",
+            "new_password_check": "short",
+        }))
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn current_password_must_be_valid() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    let response = test_app
+        .post_change_password(&serde_json::json!({
+            "current_password": Uuid::new_v4().to_string(),
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn changing_password_works_and_invalidates_sessions() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    // A second client holds another session for the same user
+    let other_client = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let response = other_client
+        .post(format!("{}/login", &test_app.address))
+        .json(&serde_json::json!({
+            "username": &test_app.test_creditor.username,
+            "password": &test_app.test_creditor.password,
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(200, response.status().as_u16());
+
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_change_password(&serde_json::json!({
+            "current_password": &test_app.test_creditor.password,
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Both sessions have been invalidated
+    let response = test_app.get_user().await;
+    assert_eq!(401, response.status().as_u16());
+    let response = other_client
+        .get(format!("{}/user", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(401, response.status().as_u16());
+
+    // The old password no longer works, the new one does
+    let response = test_app.post_login_as_test_creditor().await;
+    assert_eq!(401, response.status().as_u16());
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": &test_app.test_creditor.username,
+            "password": &new_password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn changing_password_revokes_api_tokens() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app
+        .post_api_token(&serde_json::json!({
+            "name": "cli",
+            "scopes": ["debts:read"],
+        }))
+        .await;
+    let token = response.json::<serde_json::Value>().await.unwrap()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = test_app
+        .post_change_password(&serde_json::json!({
+            "current_password": &test_app.test_creditor.password,
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let error = test_app.token_client(&token).get_debts().await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+}
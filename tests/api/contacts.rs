@@ -0,0 +1,175 @@
+use crate::helpers::{spawn_app, TestApp, TestUser};
+use debt_tracer::routes::contacts::get::GetContactJSONResponse;
+use debt_tracer::routes::contacts::requests::GetContactRequestsJSONResponse;
+
+async fn log_in_as(test_app: &TestApp, user: &TestUser) {
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": &user.username,
+            "password": &user.password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+async fn store_user(test_app: &TestApp) -> TestUser {
+    let user = TestUser::generate();
+    user.store(&test_app.db_pool).await;
+    user
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_your_contacts() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_contacts().await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn contacts_are_listed_for_both_users() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let contacts: Vec<GetContactJSONResponse> = test_app.get_contacts().await.json().await.unwrap();
+
+    assert_eq!(contacts.len(), 1);
+    assert_eq!(
+        contacts[0].user_id,
+        test_app.test_debtor.user_id.to_string()
+    );
+
+    test_app.post_login_as_test_debtor().await;
+    let contacts: Vec<GetContactJSONResponse> = test_app.get_contacts().await.json().await.unwrap();
+
+    assert_eq!(contacts.len(), 1);
+    assert_eq!(
+        contacts[0].user_id,
+        test_app.test_creditor.user_id.to_string()
+    );
+}
+
+#[tokio::test]
+async fn an_accepted_request_makes_users_contacts() {
+    let test_app = spawn_app().await;
+    let friend = store_user(&test_app).await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.post_contact_request(&friend.username).await;
+    assert_eq!(200, response.status().as_u16());
+    let requests: GetContactRequestsJSONResponse =
+        test_app.get_contact_requests().await.json().await.unwrap();
+    assert_eq!(requests.outgoing.len(), 1);
+    assert!(requests.incoming.is_empty());
+
+    log_in_as(&test_app, &friend).await;
+    let requests: GetContactRequestsJSONResponse =
+        test_app.get_contact_requests().await.json().await.unwrap();
+    assert_eq!(requests.incoming.len(), 1);
+    let response = test_app
+        .post_accept_contact_request(test_app.test_creditor.user_id)
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let contacts: Vec<GetContactJSONResponse> = test_app.get_contacts().await.json().await.unwrap();
+    assert_eq!(contacts.len(), 1);
+    assert_eq!(
+        contacts[0].user_id,
+        test_app.test_creditor.user_id.to_string()
+    );
+}
+
+#[tokio::test]
+async fn a_request_can_only_be_accepted_by_its_addressee() {
+    let test_app = spawn_app().await;
+    let friend = store_user(&test_app).await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_contact_request(&friend.username).await;
+
+    let response = test_app
+        .post_accept_contact_request(test_app.test_creditor.user_id)
+        .await;
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_declined_request_is_removed() {
+    let test_app = spawn_app().await;
+    let friend = store_user(&test_app).await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_contact_request(&friend.username).await;
+
+    log_in_as(&test_app, &friend).await;
+    let response = test_app
+        .post_decline_contact_request(test_app.test_creditor.user_id)
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let requests: GetContactRequestsJSONResponse =
+        test_app.get_contact_requests().await.json().await.unwrap();
+    assert!(requests.incoming.is_empty());
+    let contacts: Vec<GetContactJSONResponse> = test_app.get_contacts().await.json().await.unwrap();
+    assert!(contacts.is_empty());
+}
+
+#[tokio::test]
+async fn a_duplicate_request_is_rejected() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_contact_request(&test_app.test_debtor.username)
+        .await;
+
+    assert_eq!(409, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn you_cannot_add_yourself() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_contact_request(&test_app.test_creditor.username)
+        .await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn requests_to_unknown_users_look_like_any_other() {
+    let test_app = spawn_app().await;
+    let friend = store_user(&test_app).await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.post_contact_request(&friend.email).await;
+    assert_eq!(200, response.status().as_u16());
+    let response = test_app
+        .post_contact_request("nobody@debt-tracer.com")
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let requests: GetContactRequestsJSONResponse =
+        test_app.get_contact_requests().await.json().await.unwrap();
+    assert_eq!(requests.outgoing.len(), 1);
+    assert_eq!(requests.outgoing[0].user, friend.email);
+    let body = test_app.get_contact_requests().await.text().await.unwrap();
+    assert!(!body.contains(&friend.username));
+}
+
+#[tokio::test]
+async fn a_removed_contact_can_no_longer_be_charged() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.delete_contact(test_app.test_debtor.user_id).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let contacts: Vec<GetContactJSONResponse> = test_app.get_contacts().await.json().await.unwrap();
+    assert!(contacts.is_empty());
+    let response = test_app.post_debt(10.0, "USD", "Coffee").await;
+    assert_eq!(403, response.status().as_u16());
+    let response = test_app.delete_contact(test_app.test_debtor.user_id).await;
+    assert_eq!(404, response.status().as_u16());
+}
@@ -1,4 +1,4 @@
-use crate::helpers::{spawn_app, TestApp};
+use crate::helpers::{spawn_app, TestApp, TestUser};
 use debt_tracer::debts::{CreateDebtJSONResponse, GetDebtJSONResponse};
 use rust_decimal::prelude::ToPrimitive;
 use std::collections::HashMap;
@@ -61,7 +61,7 @@ async fn create_debt_returns_a_400_when_data_is_missing() {
 
     let response = test_app
         .api_client
-        .post(&format!("{}/debt", &test_app.address))
+        .post("/debt")
         .json(&create_debt_request)
         .send()
         .await
@@ -118,3 +118,181 @@ async fn get_debts_returns_a_list_of_debts() {
         assert_eq!(debt.status, "pending".to_owned());
     }
 }
+
+#[tokio::test]
+async fn create_debt_accepts_usernames_and_emails() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": &test_app.test_debtor.username,
+            "creditor_id": &test_app.test_creditor.email,
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let saved = sqlx::query!("SELECT creditor_id, debtor_id FROM debts")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch saved debt.");
+    assert_eq!(saved.debtor_id, test_app.test_debtor.user_id);
+    assert_eq!(saved.creditor_id, test_app.test_creditor.user_id);
+}
+
+#[tokio::test]
+async fn create_debt_answers_unknown_users_like_users_who_do_not_accept() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": "nobody-by-this-name",
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await;
+    let stranger = TestUser::generate();
+    stranger.store(&test_app.db_pool).await;
+    let stranger_response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": &stranger.username,
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await;
+
+    assert_eq!(403, response.status().as_u16());
+    assert_eq!(403, stranger_response.status().as_u16());
+    let body = response.text().await.unwrap();
+    assert_eq!(body, stranger_response.text().await.unwrap());
+    assert!(!body.contains(&stranger.user_id.to_string()));
+}
+
+#[tokio::test]
+async fn create_debt_returns_a_403_when_the_caller_is_not_a_party() {
+    let test_app = spawn_app().await;
+    let other = TestUser::generate();
+    other.store(&test_app.db_pool).await;
+    sqlx::query!(
+        "UPDATE users SET accept_debts_from_strangers = TRUE WHERE user_id = $1",
+        other.user_id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .unwrap();
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": other.user_id.to_string(),
+            "creditor_id": test_app.test_debtor.user_id.to_string(),
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await;
+
+    assert_eq!(403, response.status().as_u16());
+    let count = sqlx::query_scalar!("SELECT COUNT(*) FROM debts")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(count, Some(0));
+}
+
+#[tokio::test]
+async fn create_debt_returns_a_403_for_a_user_who_is_not_a_contact() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let stranger = TestUser::generate();
+    stranger.store(&test_app.db_pool).await;
+
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": stranger.user_id.to_string(),
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await;
+
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn create_debt_accepts_strangers_who_opted_in() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let stranger = TestUser::generate();
+    stranger.store(&test_app.db_pool).await;
+    sqlx::query!(
+        "UPDATE users SET accept_debts_from_strangers = TRUE WHERE user_id = $1",
+        stranger.user_id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .unwrap();
+
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": stranger.user_id.to_string(),
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn the_creditor_can_mark_a_debt_as_paid() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app.post_debt(12.5, "EUR", "Lunch").await;
+    let debt_id = response
+        .json::<CreateDebtJSONResponse>()
+        .await
+        .unwrap()
+        .debt_id;
+
+    let response = test_app.post_mark_debt_paid(&debt_id).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let debt = sqlx::query!(
+        "SELECT status, paid_at FROM debts WHERE debt_id = $1",
+        debt_id.parse::<uuid::Uuid>().unwrap()
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(debt.status, "paid");
+    assert!(debt.paid_at.is_some());
+}
+
+#[tokio::test]
+async fn the_debtor_cannot_mark_a_debt_as_paid() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app.post_debt(12.5, "EUR", "Lunch").await;
+    let debt_id = response
+        .json::<CreateDebtJSONResponse>()
+        .await
+        .unwrap()
+        .debt_id;
+    test_app.post_login_as_test_debtor().await;
+
+    let response = test_app.post_mark_debt_paid(&debt_id).await;
+
+    assert_eq!(404, response.status().as_u16());
+}
@@ -0,0 +1,166 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::debts::GetDebtJSONResponse;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_delete_your_account() {
+    let test_app = spawn_app().await;
+
+    let response = test_app
+        .delete_user(&serde_json::json!({ "password": "whatever" }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn account_is_not_deleted_with_a_wrong_password() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .delete_user(&serde_json::json!({ "password": "wrong-password" }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+    assert_eq!(200, test_app.get_user().await.status().as_u16());
+}
+
+#[tokio::test]
+async fn deleting_an_account_anonymises_the_user_and_logs_them_out() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .delete_user(&serde_json::json!({
+            "password": &test_app.test_creditor.password,
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(401, test_app.get_user().await.status().as_u16());
+    let user = sqlx::query!(
+        "SELECT username, email, password_hash, status FROM users WHERE user_id = $1",
+        test_app.test_creditor.user_id
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert_ne!(user.username, test_app.test_creditor.username);
+    assert_ne!(user.email, test_app.test_creditor.email);
+    assert_eq!(user.password_hash, None);
+    assert_eq!(user.status, "deleted");
+    let response = test_app.post_login_as_test_creditor().await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn debts_of_a_deleted_account_remain_visible_to_the_counterparty() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_debt(15.0, "USD", "Taxi").await;
+    test_app
+        .delete_user(&serde_json::json!({
+            "password": &test_app.test_creditor.password,
+        }))
+        .await;
+
+    test_app
+        .post_login(&serde_json::json!({
+            "username": &test_app.test_debtor.username,
+            "password": &test_app.test_debtor.password,
+        }))
+        .await;
+    let response = test_app.get_debts_as_test_creditor().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let debts: Vec<GetDebtJSONResponse> = response.json().await.unwrap();
+    assert_eq!(debts.len(), 1);
+    assert_eq!(
+        debts[0].creditor_id,
+        test_app.test_creditor.user_id.to_string()
+    );
+    assert_ne!(debts[0].creditor_name, test_app.test_creditor.username);
+    let notifications = sqlx::query!(
+        "SELECT notification_id FROM notifications WHERE actor_id = $1",
+        test_app.test_creditor.user_id
+    )
+    .fetch_all(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert!(notifications.is_empty());
+}
+
+#[tokio::test]
+async fn a_pending_email_change_cannot_be_confirmed_after_deletion() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+    test_app
+        .patch_user(&serde_json::json!({
+            "email": "new-address@debt-tracer.com",
+            "display_name": "Ada Lovelace",
+        }))
+        .await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+    test_app
+        .delete_user(&serde_json::json!({
+            "password": &test_app.test_creditor.password,
+        }))
+        .await;
+
+    let response = test_app.get_email_change_confirm(&token).await;
+
+    assert_eq!(401, response.status().as_u16());
+    let user = sqlx::query!(
+        "SELECT email, display_name FROM users WHERE user_id = $1",
+        test_app.test_creditor.user_id
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert!(user.email.ends_with("@deleted.invalid"));
+    assert_eq!(user.display_name, None);
+}
+
+async fn remove_password(test_app: &TestApp) {
+    sqlx::query!(
+        "UPDATE users SET password_hash = NULL WHERE user_id = $1",
+        test_app.test_creditor.user_id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn a_passwordless_user_who_just_logged_in_can_delete_their_account() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    remove_password(&test_app).await;
+
+    let response = test_app.delete_user(&serde_json::json!({})).await;
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(401, test_app.get_user().await.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_passwordless_user_is_not_deleted_with_a_wrong_code() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    remove_password(&test_app).await;
+
+    let response = test_app
+        .delete_user(&serde_json::json!({ "code": "123456" }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+    assert_eq!(200, test_app.get_user().await.status().as_u16());
+}
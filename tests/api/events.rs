@@ -0,0 +1,67 @@
+use crate::helpers::spawn_app;
+use std::time::Duration;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_stream_events() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_events().await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_new_debt_is_pushed_to_the_counterparty() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_debtor().await;
+    let mut events = test_app.get_events().await;
+    assert_eq!(200, events.status().as_u16());
+    assert_eq!(
+        events.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+
+    // The creditor acts from another client, as they would from another device.
+    let creditor_client = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    creditor_client
+        .post(format!("{}/login", &test_app.address))
+        .json(&serde_json::json!({
+            "username": &test_app.test_creditor.username,
+            "password": &test_app.test_creditor.password,
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    let debt_id = creditor_client
+        .post(format!("{}/debt", &test_app.address))
+        .json(&serde_json::json!({
+            "debtor_id": test_app.test_debtor.user_id.to_string(),
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 10.0,
+            "currency": "USD",
+            "description": "Coffee",
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json::<serde_json::Value>()
+        .await
+        .unwrap()["debt_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let mut received = String::new();
+    while !(received.contains("debt_created") && received.contains("notification")) {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), events.chunk())
+            .await
+            .expect("Timed out waiting for an event.")
+            .unwrap()
+            .expect("The event stream ended.");
+        received.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+    assert!(received.contains(&debt_id));
+}
@@ -0,0 +1,122 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::debts::GetDebtJSONResponse;
+
+async fn create_debts(test_app: &TestApp) {
+    test_app.post_login_as_test_creditor().await;
+    for (amount, currency, description) in [(12.5, "EUR", "Pizza"), (30.0, "USD", "Tickets")] {
+        let response = test_app.post_debt(amount, currency, description).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_export_debts() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_debts_export(&[("format", "csv")]).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn export_returns_a_400_for_an_unknown_format() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_debts_export(&[("format", "xlsx")]).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn the_json_export_matches_the_debts_list() {
+    let test_app = spawn_app().await;
+    create_debts(&test_app).await;
+
+    let response = test_app.get_debts_export(&[("format", "json")]).await;
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        response.headers()["Content-Disposition"],
+        "attachment; filename=\"debts.json\""
+    );
+    let exported: Vec<GetDebtJSONResponse> = response.json().await.unwrap();
+    let mut listed: Vec<GetDebtJSONResponse> = test_app
+        .get_debts_as_test_creditor()
+        .await
+        .json()
+        .await
+        .unwrap();
+    listed.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    assert_eq!(
+        serde_json::to_value(exported).unwrap(),
+        serde_json::to_value(listed).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn the_csv_export_has_a_header_and_one_line_per_debt() {
+    let test_app = spawn_app().await;
+    create_debts(&test_app).await;
+
+    let response = test_app.get_debts_export(&[("format", "csv")]).await;
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(response.headers()["Content-Type"], "text/csv");
+    let body = response.text().await.unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("debt_id,creditor_id,creditor_name"));
+    assert!(lines[1].contains("Pizza"));
+}
+
+#[tokio::test]
+async fn the_export_applies_the_filters() {
+    let test_app = spawn_app().await;
+    create_debts(&test_app).await;
+
+    let response = test_app
+        .get_debts_export(&[("format", "json"), ("currency", "USD")])
+        .await;
+
+    let exported: Vec<GetDebtJSONResponse> = response.json().await.unwrap();
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0].description, "Tickets");
+
+    let response = test_app
+        .get_debts_export(&[("format", "json"), ("status", "paid")])
+        .await;
+
+    let exported: Vec<GetDebtJSONResponse> = response.json().await.unwrap();
+    assert!(exported.is_empty());
+}
+
+#[tokio::test]
+async fn the_ofx_export_lists_debts_as_transactions() {
+    let test_app = spawn_app().await;
+    create_debts(&test_app).await;
+
+    let response = test_app
+        .get_debts_export(&[("format", "ofx"), ("currency", "EUR")])
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let body = response.text().await.unwrap();
+    assert!(body.starts_with("<?xml"));
+    assert!(body.contains("<CURDEF>EUR</CURDEF>"));
+    assert_eq!(body.matches("<STMTTRN>").count(), 1);
+    assert!(body.contains("<TRNTYPE>CREDIT</TRNTYPE>"));
+    assert!(body.contains("<TRNAMT>12.50</TRNAMT>"));
+    assert!(body.contains("<BALAMT>12.50</BALAMT>"));
+    assert!(body.trim_end().ends_with("</OFX>"));
+}
+
+#[tokio::test]
+async fn the_ofx_export_returns_a_400_without_a_currency() {
+    let test_app = spawn_app().await;
+    create_debts(&test_app).await;
+
+    let response = test_app.get_debts_export(&[("format", "ofx")]).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
@@ -0,0 +1,75 @@
+use crate::helpers::spawn_app;
+use debt_tracer::routes::users::export::UserExportJSONResponse;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_export_your_data() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_user_export().await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn export_contains_everything_stored_about_the_user() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    test_app
+        .patch_user(&serde_json::json!({
+            "display_name": "Cora",
+            "default_currency": "EUR",
+            "timezone": "Europe/Berlin",
+        }))
+        .await;
+    test_app.post_debt(20.0, "USD", "Concert tickets").await;
+    test_app
+        .post_webhook(&serde_json::json!({
+            "url": "https://hooks.example.com/debts",
+            "event_types": ["debt_created"],
+        }))
+        .await;
+    test_app
+        .post_api_token(&serde_json::json!({
+            "name": "spreadsheet",
+            "scopes": ["debts:read"],
+        }))
+        .await;
+
+    let response = test_app.get_user_export().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let content_disposition = response
+        .headers()
+        .get("Content-Disposition")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(content_disposition.starts_with("attachment"));
+    let export: UserExportJSONResponse = response.json().await.unwrap();
+    assert_eq!(
+        export.profile.user_id,
+        test_app.test_creditor.user_id.to_string()
+    );
+    assert_eq!(export.profile.email, test_app.test_creditor.email);
+    assert_eq!(export.profile.display_name.as_deref(), Some("Cora"));
+    assert_eq!(export.profile.default_currency, "EUR");
+    assert_eq!(export.profile.timezone, "Europe/Berlin");
+    assert!(!export.profile.accept_debts_from_strangers);
+    assert_eq!(export.profile.reminder_frequency, "weekly");
+    assert_eq!(export.debts.len(), 1);
+    assert_eq!(export.debts[0].description, "Concert tickets");
+    assert_eq!(export.api_tokens.len(), 1);
+    assert_eq!(export.api_tokens[0].name, "spreadsheet");
+    assert!(export.identities.is_empty());
+    assert_eq!(export.webhooks.len(), 1);
+    assert_eq!(export.webhooks[0].url, "https://hooks.example.com/debts");
+    assert!(export.notifications.is_empty());
+
+    test_app.post_login_as_test_debtor().await;
+    let export: UserExportJSONResponse = test_app.get_user_export().await.json().await.unwrap();
+    assert_eq!(export.notifications.len(), 1);
+    assert_eq!(
+        export.notifications[0].actor_id,
+        test_app.test_creditor.user_id.to_string()
+    );
+}
@@ -4,10 +4,9 @@ use super::helpers::spawn_app;
 async fn health_check_works() {
     let test_app = spawn_app().await;
 
-    let client = reqwest::Client::new();
-
-    let response = client
-        .get(&format!("{}/health_check", &test_app.address))
+    let response = test_app
+        .api_client
+        .get("/health_check")
         .send()
         .await
         .expect("Failed to execute request");
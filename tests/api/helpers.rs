@@ -1,12 +1,20 @@
 use argon2::password_hash::SaltString;
 use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
 use debt_tracer::configuration::get_configuration;
-use debt_tracer::configuration::DatabaseSettings;
+use debt_tracer::configuration::{DatabaseSettings, OidcSettings, Settings, WebhookSettings};
+use debt_tracer::email_client::EmailClient;
+use debt_tracer::operations;
+use debt_tracer::reminder_worker;
 use debt_tracer::startup::get_connection_pool;
 use debt_tracer::telemetry::{get_subscriber, init_subscriber};
+use debt_tracer::webhook_delivery_worker::{try_execute_task, ExecutionOutcome};
+use debt_tracer_client::types::JsonData;
+use debt_tracer_client::Client;
 use once_cell::sync::Lazy;
+use secrecy::Secret;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
 use uuid::Uuid;
+use wiremock::MockServer;
 
 static TRACING: Lazy<()> = Lazy::new(|| {
     let default_filter_level = "info".to_string();
@@ -28,7 +36,11 @@ pub struct TestApp {
     pub port: u16,
     pub test_creditor: TestUser,
     pub test_debtor: TestUser,
-    pub api_client: reqwest::Client,
+    pub api_client: Client,
+    pub email_server: MockServer,
+    pub oidc_server: MockServer,
+    pub webhook_settings: WebhookSettings,
+    pub email_client: EmailClient,
 }
 
 pub struct TestUser {
@@ -48,25 +60,297 @@ impl TestApp {
         let debtor_id = self.test_debtor.user_id.to_string();
         let creditor_id = self.test_creditor.user_id.to_string();
 
-        let create_debt_request = CreateDebtRequest {
+        let create_debt_request = JsonData {
             debtor_id: debtor_id.clone(),
             creditor_id: creditor_id.clone(),
             amount,
             currency: currency.to_string(),
             description: description.to_string(),
+            due_date: None,
         };
 
         self.api_client
-            .post(&format!("{}/debt", &self.address))
+            .post("/debt")
             .json(&create_debt_request)
             .send()
             .await
             .expect("Failed to execute request")
     }
 
+    pub async fn post_debt_json<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/debt")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_debts_export(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.api_client
+            .get("/debts/export")
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_statement(&self, year: i32, month: u32, format: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("/statements/{}/{}", year, month))
+            .query(&[("format", format)])
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_timeseries(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.api_client
+            .get("/reports/timeseries")
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_debts_import(&self, csv: &str, dry_run: bool) -> reqwest::Response {
+        self.api_client
+            .post("/debts/import")
+            .query(&[("dry_run", dry_run)])
+            .header("Content-Type", "text/csv")
+            .body(csv.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_splitwise_import<Body>(&self, body: &Body, dry_run: bool) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/debts/import/splitwise")
+            .query(&[("dry_run", dry_run)])
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    /// A separate client that authenticates with an API token instead of the session.
+    pub fn token_client(&self, token: &str) -> Client {
+        Client::new(&self.address).unwrap().with_token(token)
+    }
+
+    pub async fn post_login_as_test_debtor(&self) -> reqwest::Response {
+        let login_request_body = serde_json::json!({
+            "username" : &self.test_debtor.username,
+            "password" : &self.test_debtor.password,
+        });
+
+        self.post_login(&login_request_body).await
+    }
+
+    pub async fn dispatch_all_pending_webhooks(&self) {
+        let http_client = self.webhook_settings.client();
+        loop {
+            if let ExecutionOutcome::EmptyQueue =
+                try_execute_task(&self.db_pool, &http_client, &self.webhook_settings)
+                    .await
+                    .unwrap()
+            {
+                break;
+            }
+        }
+    }
+
+    pub async fn send_all_due_reminders(&self) {
+        loop {
+            if let reminder_worker::ExecutionOutcome::EmptyQueue =
+                reminder_worker::try_execute_task(&self.db_pool, &self.email_client)
+                    .await
+                    .unwrap()
+            {
+                break;
+            }
+        }
+    }
+
+    pub async fn post_remind_debtor(&self, debt_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("/debt/{}/remind", debt_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_mark_debt_paid(&self, debt_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("/debt/{}/paid", debt_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_webhook<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/webhooks")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_openapi(&self) -> reqwest::Response {
+        self.api_client
+            .get("/openapi.json")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_swagger_ui(&self) -> reqwest::Response {
+        self.api_client
+            .get("/swagger-ui/")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_webhooks(&self) -> reqwest::Response {
+        self.api_client
+            .get("/webhooks")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(&format!("/webhooks/{}", webhook_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_webhook_deliveries(&self, webhook_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("/webhooks/{}/deliveries", webhook_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_events(&self) -> reqwest::Response {
+        self.api_client
+            .get("/events")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_notifications(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.api_client
+            .get("/notifications")
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_notifications_read<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/notifications/read")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_contacts(&self) -> reqwest::Response {
+        self.api_client
+            .get("/contacts")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_contact_requests(&self) -> reqwest::Response {
+        self.api_client
+            .get("/contacts/requests")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_contact_request(&self, user: &str) -> reqwest::Response {
+        self.api_client
+            .post("/contacts/requests")
+            .json(&serde_json::json!({ "user": user }))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_accept_contact_request(&self, requester_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .post(&format!("/contacts/requests/{}/accept", requester_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_decline_contact_request(&self, requester_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .post(&format!("/contacts/requests/{}/decline", requester_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn delete_contact(&self, user_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .delete(&format!("/contacts/{}", user_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn add_contact(&self, requester_id: Uuid, addressee_id: Uuid) {
+        sqlx::query!(
+            r#"
+            INSERT INTO contacts (requester_id, addressee_id, status, created_at, accepted_at)
+            VALUES ($1, $2, 'accepted', now(), now())
+            "#,
+            requester_id,
+            addressee_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to store test contact.");
+    }
+
+    pub async fn get_users_search(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get("/users/search")
+            .query(&[("q", query)])
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
     pub async fn get_debts_as_test_creditor(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/debts", &self.address))
+            .get("/debts")
             .send()
             .await
             .expect("Failed to execute request")
@@ -78,13 +362,253 @@ impl TestApp {
             "password" : &self.test_creditor.password,
         });
 
+        self.post_login(&login_request_body).await
+    }
+
+    pub async fn post_login<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
         self.api_client
-            .post(&format!("{}/login", &self.address))
-            .json(&login_request_body)
+            .post("/login")
+            .json(body)
             .send()
             .await
             .expect("Failed to execute request")
     }
+
+    pub async fn post_sign_up<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/sign_up")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_sign_up_confirm(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get("/sign_up/confirm")
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_user_export(&self) -> reqwest::Response {
+        self.api_client
+            .get("/user/export")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn delete_user<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .delete("/user")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_admin_users(&self) -> reqwest::Response {
+        self.api_client
+            .get("/admin/users")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_disable_user(&self, user_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .post(&format!("/admin/users/{}/disable", user_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_admin_debt(&self, debt_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("/admin/debts/{}", debt_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    /// Roles are read at login, so set them before logging in.
+    pub async fn set_role(&self, user_id: Uuid, role: &str) {
+        sqlx::query!(
+            "UPDATE users SET role = $1 WHERE user_id = $2",
+            role,
+            user_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to set the user role.");
+    }
+
+    pub async fn get_login_oidc(&self) -> reqwest::Response {
+        self.api_client
+            .get("/login/oidc")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_login_oidc_callback(&self, code: &str, state: &str) -> reqwest::Response {
+        self.api_client
+            .get("/login/oidc/callback")
+            .query(&[("code", code), ("state", state)])
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_api_token<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/tokens")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_api_tokens(&self) -> reqwest::Response {
+        self.api_client
+            .get("/tokens")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn delete_api_token(&self, token_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(&format!("/tokens/{}", token_id))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_enroll_totp(&self) -> reqwest::Response {
+        self.api_client
+            .post("/2fa/totp")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_confirm_totp(&self, code: &str) -> reqwest::Response {
+        self.api_client
+            .post("/2fa/totp/confirm")
+            .json(&serde_json::json!({ "code": code }))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_login_two_factor(&self, code: &str) -> reqwest::Response {
+        self.api_client
+            .post("/login/2fa")
+            .json(&serde_json::json!({ "code": code }))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_user(&self) -> reqwest::Response {
+        self.api_client
+            .get("/user")
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn patch_user<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .patch("/user")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_email_change_confirm(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get("/user/email/confirm")
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_change_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/password")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_password_reset(&self, email: &str) -> reqwest::Response {
+        self.api_client
+            .post("/password/reset")
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_password_reset_confirm<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post("/password/reset/confirm")
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    /// Extract the token embedded in the link of an email sent to the mock email server.
+    pub fn get_token_from_email(&self, email_request: &wiremock::Request) -> String {
+        let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+
+        let get_token = |s: &str| {
+            let links: Vec<_> = linkify::LinkFinder::new()
+                .links(s)
+                .filter(|l| *l.kind() == linkify::LinkKind::Url)
+                .collect();
+            assert_eq!(links.len(), 1);
+            let link = reqwest::Url::parse(links[0].as_str()).unwrap();
+            link.query_pairs()
+                .find(|(key, _)| key == "token")
+                .map(|(_, value)| value.into_owned())
+                .expect("The link does not contain a token.")
+        };
+
+        let html_token = get_token(body["HtmlBody"].as_str().unwrap());
+        let text_token = get_token(body["TextBody"].as_str().unwrap());
+        assert_eq!(html_token, text_token);
+        html_token
+    }
 }
 
 impl TestUser {
@@ -93,11 +617,11 @@ impl TestUser {
             user_id: Uuid::new_v4(),
             username: Uuid::new_v4().to_string(),
             password: Uuid::new_v4().to_string(),
-            email: Uuid::new_v4().to_string(),
+            email: format!("{}@debt-tracer.com", Uuid::new_v4()),
         }
     }
 
-    async fn store(&self, pool: &PgPool) {
+    pub async fn store(&self, pool: &PgPool) {
         let salt = SaltString::generate(&mut rand::thread_rng());
         let password_hash = Argon2::new(
             Algorithm::Argon2id,
@@ -108,7 +632,8 @@ impl TestUser {
         .unwrap()
         .to_string();
         sqlx::query!(
-            "INSERT INTO users (user_id, username, password_hash, email) VALUES ($1, $2, $3,$4)",
+            "INSERT INTO users (user_id, username, password_hash, email, status) \
+            VALUES ($1, $2, $3, $4, 'active')",
             self.user_id,
             self.username,
             password_hash,
@@ -121,12 +646,35 @@ impl TestUser {
 }
 
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+/// Like `spawn_app`, with `configure` applied on top of the test configuration.
+pub async fn spawn_app_with(configure: impl FnOnce(&mut Settings)) -> TestApp {
     Lazy::force(&TRACING);
 
+    let email_server = MockServer::start().await;
+    let oidc_server = MockServer::start().await;
+
     let configuration = {
         let mut c = get_configuration().expect("Failed to read configuration.");
         c.database.database_name = Uuid::new_v4().to_string();
         c.application.port = 0;
+        c.email_client.base_url = email_server.uri();
+        // Every test logs in from 127.0.0.1: keep the per-IP lockout out of the way
+        // and make lockouts short enough to wait for.
+        c.login_throttle.max_attempts_per_ip = None;
+        c.login_throttle.base_lockout_seconds = 1;
+        c.oidc = Some(OidcSettings {
+            issuer_url: oidc_server.uri(),
+            client_id: "debt-tracer".into(),
+            client_secret: Secret::new("oidc-client-secret".into()),
+            redirect_url: format!("{}/login/oidc/callback", c.application.base_url),
+            timeout_milliseconds: 2000,
+        });
+        // The webhook receivers are mock servers on 127.0.0.1.
+        c.webhooks.allow_private_targets = true;
+        configure(&mut c);
         c
     };
 
@@ -140,15 +688,12 @@ pub async fn spawn_app() -> TestApp {
         .expect("Failed to build application.");
     let application_port = application.port();
 
+    #[allow(clippy::let_underscore_future)]
     let _ = tokio::spawn(application.run_until_stopped());
 
     let address = format!("http://127.0.0.1:{}", application_port);
 
-    let client = reqwest::Client::builder()
-        .redirect(reqwest::redirect::Policy::none())
-        .cookie_store(true)
-        .build()
-        .unwrap();
+    let client = Client::new(&address).unwrap();
 
     let test_app = TestApp {
         address,
@@ -157,10 +702,17 @@ pub async fn spawn_app() -> TestApp {
         test_creditor,
         test_debtor,
         api_client: client,
+        email_server,
+        oidc_server,
+        webhook_settings: configuration.webhooks.clone(),
+        email_client: configuration.email_client.client(),
     };
 
     test_app.test_creditor.store(&test_app.db_pool).await;
     test_app.test_debtor.store(&test_app.db_pool).await;
+    test_app
+        .add_contact(test_app.test_creditor.user_id, test_app.test_debtor.user_id)
+        .await;
 
     test_app
 }
@@ -177,19 +729,9 @@ async fn configure_database(config: &DatabaseSettings) -> PgPool {
     let connection_pool = PgPool::connect_with(config.with_db())
         .await
         .expect("Failed to connect to Postgres");
-    sqlx::migrate!("./migrations")
-        .run(&connection_pool)
+    operations::migrate(&connection_pool)
         .await
         .expect("Failed to migrate the database");
 
     connection_pool
 }
-
-#[derive(serde::Serialize)]
-struct CreateDebtRequest {
-    debtor_id: String,
-    creditor_id: String,
-    amount: f64,
-    currency: String,
-    description: String,
-}
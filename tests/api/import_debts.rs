@@ -0,0 +1,150 @@
+use crate::helpers::{spawn_app, TestApp, TestUser};
+use debt_tracer::routes::debts::import::ImportDebtsJSONResponse;
+
+fn csv(test_app: &TestApp, rows: &[&str]) -> String {
+    let mut csv = "counterparty,amount,currency,description,date,status\n".to_string();
+    for row in rows {
+        csv.push_str(&row.replace("{debtor}", &test_app.test_debtor.username));
+        csv.push('\n');
+    }
+    csv
+}
+
+async fn count_debts(test_app: &TestApp) -> i64 {
+    sqlx::query!("SELECT COUNT(*) AS count FROM debts")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap()
+        .count
+        .unwrap()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_import_debts() {
+    let test_app = spawn_app().await;
+
+    let response = test_app
+        .post_debts_import(&csv(&test_app, &[]), false)
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_dry_run_reports_invalid_rows_without_inserting_anything() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let csv = csv(
+        &test_app,
+        &[
+            "{debtor},12.50,EUR,Pizza,2021-03-04,paid",
+            "{debtor},abc,EUR,Pizza,2021-03-04,paid",
+            "nobody-by-that-name,10,EUR,Pizza,2021-03-04,paid",
+            "{debtor},10,XXX,Pizza,2021-03-04,paid",
+            "{debtor},10,EUR,Pizza,04/03/2021,forgiven",
+        ],
+    );
+
+    let response = test_app.post_debts_import(&csv, true).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let report: ImportDebtsJSONResponse = response.json().await.unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.valid_rows, 1);
+    let rows: Vec<usize> = report.errors.iter().map(|error| error.row).collect();
+    assert_eq!(rows, vec![2, 3, 4, 5]);
+    assert_eq!(count_debts(&test_app).await, 0);
+}
+
+#[tokio::test]
+async fn valid_rows_are_imported_with_their_direction_and_date() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let csv = csv(
+        &test_app,
+        &[
+            "{debtor},12.50,EUR,Pizza,2021-03-04,paid",
+            "{debtor},-30,USD,Train tickets,2022-11-30,unpaid",
+            "{debtor},10,ZZZ,Lunch,2021-03-04,paid",
+        ],
+    );
+
+    let response = test_app.post_debts_import(&csv, false).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let report: ImportDebtsJSONResponse = response.json().await.unwrap();
+    assert_eq!(report.valid_rows, 2);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].row, 3);
+
+    let debts = sqlx::query!(
+        "SELECT creditor_id, debtor_id, status, created_at FROM debts ORDER BY created_at"
+    )
+    .fetch_all(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(debts.len(), 2);
+    assert_eq!(debts[0].creditor_id, test_app.test_creditor.user_id);
+    assert_eq!(debts[0].status, "paid");
+    assert_eq!(
+        debts[0].created_at.to_rfc3339(),
+        "2021-03-04T00:00:00+00:00"
+    );
+    assert_eq!(debts[1].creditor_id, test_app.test_debtor.user_id);
+    assert_eq!(debts[1].debtor_id, test_app.test_creditor.user_id);
+}
+
+#[tokio::test]
+async fn debts_with_users_who_are_not_contacts_are_reported() {
+    let test_app = spawn_app().await;
+    let stranger = TestUser::generate();
+    stranger.store(&test_app.db_pool).await;
+    test_app.post_login_as_test_creditor().await;
+    let csv = format!(
+        "counterparty,amount,currency,description,date,status\n{},5,EUR,Coffee,2021-03-04,paid\n",
+        stranger.username
+    );
+
+    let response = test_app.post_debts_import(&csv, false).await;
+
+    let report: ImportDebtsJSONResponse = response.json().await.unwrap();
+    assert_eq!(report.valid_rows, 0);
+    assert!(report.errors[0]
+        .message
+        .contains("does not accept debts from you"));
+    assert_eq!(count_debts(&test_app).await, 0);
+}
+
+#[tokio::test]
+async fn import_returns_a_400_when_a_column_is_missing() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_debts_import("counterparty,amount,currency\nursula,5,EUR\n", false)
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn statuses_in_the_importers_favour_are_left_pending() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let csv = csv(
+        &test_app,
+        &[
+            "{debtor},12.50,EUR,Pizza,2021-03-04,unpaid",
+            "{debtor},-30,USD,Train tickets,2022-11-30,paid",
+        ],
+    );
+
+    let response = test_app.post_debts_import(&csv, false).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let statuses = sqlx::query_scalar!("SELECT status FROM debts")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(statuses, vec!["pending", "pending"]);
+}
@@ -0,0 +1,166 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::routes::debts::splitwise::SplitwiseImportJSONResponse;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+const EXPORT: &str = "\
+Date,Description,Category,Cost,Currency,Alice,Bob,Carol
+,,,,,,,
+2021-01-01,Dinner,Dining out,60.00,EUR,40.00,-20.00,-20.00
+2021-01-02,Taxi,Taxi,10.00,EUR,-5.00,5.00,0.00
+2021-01-03,Souvenir,General,10.00,XBT,5.00,-5.00,0.00
+,,,,,,,
+2021-03-04,Total balance, , ,EUR,35.00,-15.00,-20.00
+";
+
+fn members(test_app: &TestApp) -> serde_json::Value {
+    serde_json::json!({
+        "Alice": test_app.test_creditor.username,
+        "Bob": test_app.test_debtor.email,
+        "Carol": "carol@example.com",
+    })
+}
+
+#[tokio::test]
+async fn expenses_are_converted_into_debts_between_members() {
+    let test_app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_splitwise_import(
+            &serde_json::json!({ "csv": EXPORT, "members": members(&test_app) }),
+            false,
+        )
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let report: SplitwiseImportJSONResponse = response.json().await.unwrap();
+    assert_eq!(report.debts, 3);
+    assert_eq!(report.unsupported_currencies, vec!["XBT"]);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].row, 4);
+
+    let debts = sqlx::query!(
+        r#"
+        SELECT creditors.username AS creditor, debtors.email, debts.amount::text AS "amount!"
+        FROM debts
+        JOIN users creditors ON creditors.user_id = debts.creditor_id
+        JOIN users debtors ON debtors.user_id = debts.debtor_id
+        ORDER BY debts.created_at, debts.amount
+        "#
+    )
+    .fetch_all(&test_app.db_pool)
+    .await
+    .unwrap();
+    let debts: Vec<(String, String, String)> = debts
+        .into_iter()
+        .map(|debt| (debt.creditor, debt.email, debt.amount))
+        .collect();
+    assert_eq!(
+        debts,
+        vec![
+            (
+                test_app.test_creditor.username.clone(),
+                test_app.test_debtor.email.clone(),
+                "20.00".to_string()
+            ),
+            (
+                test_app.test_creditor.username.clone(),
+                "carol@example.com".to_string(),
+                "20.00".to_string()
+            ),
+            (
+                test_app.test_debtor.username.clone(),
+                test_app.test_creditor.email.clone(),
+                "5.00".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_dry_run_reports_unmapped_members_without_inserting_anything() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let mut members = members(&test_app);
+    members.as_object_mut().unwrap().remove("Carol");
+
+    let response = test_app
+        .post_splitwise_import(
+            &serde_json::json!({ "csv": EXPORT, "members": members }),
+            true,
+        )
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let report: SplitwiseImportJSONResponse = response.json().await.unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.unmapped_members, vec!["Carol"]);
+    let rows: Vec<usize> = report.errors.iter().map(|error| error.row).collect();
+    assert_eq!(rows, vec![2, 4]);
+    let count = sqlx::query!("SELECT COUNT(*) AS count FROM debts")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(count, Some(0));
+    let invited = sqlx::query!("SELECT user_id FROM users WHERE email = 'carol@example.com'")
+        .fetch_optional(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert!(invited.is_none());
+}
+
+#[tokio::test]
+async fn debts_between_other_members_are_skipped() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let csv = "\
+Date,Description,Category,Cost,Currency,Alice,Bob,Carol
+2021-01-01,Cinema,Entertainment,30.00,EUR,-10.00,20.00,-10.00
+";
+
+    let response = test_app
+        .post_splitwise_import(
+            &serde_json::json!({ "csv": csv, "members": members(&test_app) }),
+            true,
+        )
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let report: SplitwiseImportJSONResponse = response.json().await.unwrap();
+    assert_eq!(report.debts, 1);
+    assert_eq!(report.skipped.len(), 1);
+    let skipped = &report.skipped[0];
+    assert_eq!(skipped.row, 1);
+    assert_eq!(
+        (skipped.creditor.as_str(), skipped.debtor.as_str()),
+        ("Bob", "Carol")
+    );
+    assert_eq!(skipped.amount, 10.0);
+    assert_eq!(skipped.currency, "EUR");
+}
+
+#[tokio::test]
+async fn splitwise_import_returns_a_400_for_other_csv_files() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_splitwise_import(
+            &serde_json::json!({
+                "csv": "counterparty,amount,currency\nursula,5,EUR\n",
+                "members": {},
+            }),
+            false,
+        )
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
@@ -0,0 +1,240 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::debts::GetDebtJSONResponse;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn post_debt_to(test_app: &TestApp, email: &str) -> reqwest::Response {
+    test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": email,
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 12.0,
+            "currency": "EUR",
+            "description": "Pizza",
+        }))
+        .await
+}
+
+async fn invite(test_app: &TestApp, email: &str) -> String {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+    test_app.post_login_as_test_creditor().await;
+    let response = post_debt_to(test_app, email).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    test_app.get_token_from_email(email_request)
+}
+
+fn invited_email() -> String {
+    format!("{}@debt-tracer.com", Uuid::new_v4())
+}
+
+#[tokio::test]
+async fn a_debt_against_an_unknown_email_creates_a_placeholder_user() {
+    let test_app = spawn_app().await;
+    let email = invited_email();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = post_debt_to(&test_app, &email).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let saved = sqlx::query!(
+        "SELECT status, password_hash FROM users WHERE email = $1",
+        email
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch the placeholder user.");
+    assert_eq!(saved.status, "invited");
+    assert!(saved.password_hash.is_none());
+}
+
+#[tokio::test]
+async fn the_debt_is_recorded_even_if_the_invite_email_fails() {
+    let test_app = spawn_app().await;
+    let email = invited_email();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = post_debt_to(&test_app, &email).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let debts = test_app
+        .get_debts_as_test_creditor()
+        .await
+        .json::<Vec<GetDebtJSONResponse>>()
+        .await
+        .unwrap();
+    assert_eq!(debts.len(), 1);
+}
+
+#[tokio::test]
+async fn signing_up_with_an_invite_claims_the_placeholder_and_its_debts() {
+    let test_app = spawn_app().await;
+    let email = invited_email();
+    let token = invite(&test_app, &email).await;
+    let username = Uuid::new_v4().to_string();
+    let password = Uuid::new_v4().to_string();
+
+    let response = test_app
+        .post_sign_up(&serde_json::json!({
+            "username": &username,
+            "password": &password,
+            "email": &email,
+            "invite_token": token,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": &username,
+            "password": &password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let debts: Vec<GetDebtJSONResponse> = test_app
+        .get_debts_as_test_creditor()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(debts.len(), 1);
+    assert_eq!(debts[0].debtor_name, username);
+    // Only the invite was sent: the invited address needs no confirmation.
+    assert_eq!(
+        test_app
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn signing_up_with_another_email_still_requires_confirmation() {
+    let test_app = spawn_app().await;
+    let token = invite(&test_app, &invited_email()).await;
+    let username = Uuid::new_v4().to_string();
+
+    let response = test_app
+        .post_sign_up(&serde_json::json!({
+            "username": &username,
+            "password": Uuid::new_v4().to_string(),
+            "email": invited_email(),
+            "invite_token": token,
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let saved = sqlx::query!("SELECT status FROM users WHERE username = $1", username)
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch saved user.");
+    assert_eq!(saved.status, "pending_confirmation");
+    assert_eq!(
+        test_app
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn an_invite_token_can_only_be_used_once() {
+    let test_app = spawn_app().await;
+    let email = invited_email();
+    let token = invite(&test_app, &email).await;
+    let sign_up = |username: String| {
+        serde_json::json!({
+            "username": username,
+            "password": Uuid::new_v4().to_string(),
+            "email": &email,
+            "invite_token": &token,
+        })
+    };
+
+    let response = test_app
+        .post_sign_up(&sign_up(Uuid::new_v4().to_string()))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = test_app
+        .post_sign_up(&sign_up(Uuid::new_v4().to_string()))
+        .await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn an_expired_invite_token_is_rejected() {
+    let test_app = spawn_app().await;
+    let email = invited_email();
+    let token = invite(&test_app, &email).await;
+    sqlx::query!("UPDATE invite_tokens SET expires_at = now() - interval '1 minute'")
+        .execute(&test_app.db_pool)
+        .await
+        .unwrap();
+
+    let response = test_app
+        .post_sign_up(&serde_json::json!({
+            "username": Uuid::new_v4().to_string(),
+            "password": Uuid::new_v4().to_string(),
+            "email": &email,
+            "invite_token": token,
+        }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn signing_up_without_the_invite_sends_a_new_one() {
+    let test_app = spawn_app().await;
+    let email = invited_email();
+    invite(&test_app, &email).await;
+
+    let response = test_app
+        .post_sign_up(&serde_json::json!({
+            "username": Uuid::new_v4().to_string(),
+            "password": Uuid::new_v4().to_string(),
+            "email": &email,
+        }))
+        .await;
+
+    assert_eq!(409, response.status().as_u16());
+    let email_requests = test_app.email_server.received_requests().await.unwrap();
+    assert_eq!(email_requests.len(), 2);
+    let token = test_app.get_token_from_email(&email_requests[1]);
+    let response = test_app
+        .post_sign_up(&serde_json::json!({
+            "username": Uuid::new_v4().to_string(),
+            "password": Uuid::new_v4().to_string(),
+            "email": &email,
+            "invite_token": token,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+}
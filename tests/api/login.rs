@@ -1,4 +1,6 @@
-use crate::helpers::spawn_app;
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+use debt_tracer_client::{Client, LoginOutcome, StatusCode};
+use uuid::Uuid;
 
 #[tokio::test]
 async fn login_returns_a_200_for_correct_credentials() {
@@ -8,3 +10,166 @@ async fn login_returns_a_200_for_correct_credentials() {
 
     assert_eq!(200, response.status().as_u16());
 }
+
+#[tokio::test]
+async fn login_returns_a_401_for_invalid_credentials() {
+    let test_app = spawn_app().await;
+
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": &test_app.test_creditor.username,
+            "password": Uuid::new_v4().to_string(),
+        }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn repeated_failures_lock_the_username_out() {
+    let test_app = spawn_app().await;
+    let wrong_credentials = serde_json::json!({
+        "username": &test_app.test_creditor.username,
+        "password": Uuid::new_v4().to_string(),
+    });
+
+    for _ in 0..5 {
+        let response = test_app.post_login(&wrong_credentials).await;
+        assert_eq!(401, response.status().as_u16());
+    }
+
+    // Even the right password is rejected during the lockout
+    let response = test_app.post_login_as_test_creditor().await;
+
+    assert_eq!(429, response.status().as_u16());
+    assert_eq!(
+        response
+            .headers()
+            .get("Retry-After")
+            .expect("Missing Retry-After header"),
+        "1"
+    );
+}
+
+/// A random address, so concurrent tests do not share a per-IP counter.
+fn random_ip() -> String {
+    let n = Uuid::new_v4().as_u128();
+    format!("fd00::{:x}:{:x}", (n >> 16) & 0xffff, n & 0xffff)
+}
+
+async fn post_login_from(
+    test_app: &TestApp,
+    ip: &str,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/login", test_app.address))
+        .header("X-Forwarded-For", ip)
+        .json(body)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[tokio::test]
+async fn repeated_failures_from_a_proxied_client_lock_its_ip_out() {
+    let test_app = spawn_app_with(|c| {
+        c.login_throttle.max_attempts_per_ip = Some(3);
+        c.login_throttle.trust_proxy_headers = true;
+    })
+    .await;
+    let ip = random_ip();
+
+    for _ in 0..3 {
+        let wrong_credentials = serde_json::json!({
+            "username": Uuid::new_v4().to_string(),
+            "password": Uuid::new_v4().to_string(),
+        });
+        let response = post_login_from(&test_app, &ip, &wrong_credentials).await;
+        assert_eq!(401, response.status().as_u16());
+    }
+
+    let credentials = serde_json::json!({
+        "username": &test_app.test_creditor.username,
+        "password": &test_app.test_creditor.password,
+    });
+    let response = post_login_from(&test_app, &ip, &credentials).await;
+    assert_eq!(429, response.status().as_u16());
+    // Other clients behind the same proxy are not affected.
+    let response = post_login_from(&test_app, &random_ip(), &credentials).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn lockouts_grow_exponentially() {
+    let test_app = spawn_app().await;
+    let wrong_credentials = serde_json::json!({
+        "username": &test_app.test_creditor.username,
+        "password": Uuid::new_v4().to_string(),
+    });
+
+    for _ in 0..5 {
+        test_app.post_login(&wrong_credentials).await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+    let response = test_app.post_login(&wrong_credentials).await;
+    assert_eq!(401, response.status().as_u16());
+
+    let response = test_app.post_login_as_test_creditor().await;
+    assert_eq!(429, response.status().as_u16());
+    assert_eq!(response.headers().get("Retry-After").unwrap(), "2");
+}
+
+#[tokio::test]
+async fn a_successful_login_resets_the_failure_count() {
+    let test_app = spawn_app().await;
+    let wrong_credentials = serde_json::json!({
+        "username": &test_app.test_creditor.username,
+        "password": Uuid::new_v4().to_string(),
+    });
+
+    for _ in 0..4 {
+        test_app.post_login(&wrong_credentials).await;
+    }
+    let response = test_app.post_login_as_test_creditor().await;
+    assert_eq!(200, response.status().as_u16());
+
+    for _ in 0..4 {
+        let response = test_app.post_login(&wrong_credentials).await;
+        assert_eq!(401, response.status().as_u16());
+    }
+}
+
+#[tokio::test]
+async fn the_client_stays_logged_in_after_logging_in() {
+    let test_app = spawn_app().await;
+    let client = Client::new(&test_app.address).unwrap();
+
+    let outcome = client
+        .login(
+            &test_app.test_creditor.username,
+            &test_app.test_creditor.password,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(outcome, LoginOutcome::LoggedIn);
+    let user = client.get_user().await.unwrap();
+    assert_eq!(user.username, test_app.test_creditor.username);
+}
+
+#[tokio::test]
+async fn the_client_reports_the_status_of_a_failed_login() {
+    let test_app = spawn_app().await;
+    let client = Client::new(&test_app.address).unwrap();
+
+    let error = client
+        .login(&test_app.test_creditor.username, "wrong-password")
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+    let error = client.get_user().await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+}
@@ -1,4 +1,28 @@
+mod admin;
+mod api_tokens;
+mod change_password;
+mod contacts;
 mod debts;
+mod delete_user;
+mod events;
+mod export_debts;
+mod export_user_data;
 mod health_check;
 mod helpers;
+mod import_debts;
+mod import_splitwise;
+mod invites;
 mod login;
+mod notifications;
+mod oidc;
+mod openapi;
+mod operations;
+mod password_reset;
+mod reminders;
+mod search_users;
+mod sign_up;
+mod statements;
+mod timeseries;
+mod two_factor;
+mod user_profile;
+mod webhooks;
@@ -0,0 +1,102 @@
+use crate::helpers::spawn_app;
+use debt_tracer::routes::notifications::get::GetNotificationsJSONResponse;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_your_notifications() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_notifications(&[]).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn creating_a_debt_notifies_the_counterparty_only() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_debt(10.0, "USD", "Coffee").await;
+
+    let feed: GetNotificationsJSONResponse =
+        test_app.get_notifications(&[]).await.json().await.unwrap();
+    assert!(feed.notifications.is_empty());
+    assert_eq!(feed.unread_count, 0);
+
+    test_app.post_login_as_test_debtor().await;
+    let feed: GetNotificationsJSONResponse =
+        test_app.get_notifications(&[]).await.json().await.unwrap();
+    assert_eq!(feed.notifications.len(), 1);
+    assert_eq!(feed.unread_count, 1);
+    let notification = &feed.notifications[0];
+    assert_eq!(notification.kind, "debt_created");
+    assert_eq!(notification.actor_name, test_app.test_creditor.username);
+    assert!(!notification.read);
+}
+
+#[tokio::test]
+async fn notifications_are_paginated_newest_first() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    for description in ["First", "Second", "Third"] {
+        test_app.post_debt(10.0, "USD", description).await;
+    }
+    test_app.post_login_as_test_debtor().await;
+
+    let all: GetNotificationsJSONResponse =
+        test_app.get_notifications(&[]).await.json().await.unwrap();
+    let page: GetNotificationsJSONResponse = test_app
+        .get_notifications(&[("limit", "2"), ("offset", "1")])
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(all.notifications.len(), 3);
+    assert!(all.notifications[0].created_at >= all.notifications[1].created_at);
+    assert_eq!(page.notifications.len(), 2);
+    assert_eq!(
+        page.notifications[0].notification_id,
+        all.notifications[1].notification_id
+    );
+    assert_eq!(page.unread_count, 3);
+}
+
+#[tokio::test]
+async fn an_invalid_page_size_is_rejected() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    for limit in ["0", "101"] {
+        let response = test_app.get_notifications(&[("limit", limit)]).await;
+
+        assert_eq!(400, response.status().as_u16());
+    }
+}
+
+#[tokio::test]
+async fn notifications_can_be_marked_as_read() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_debt(10.0, "USD", "Coffee").await;
+    test_app.post_debt(20.0, "USD", "Lunch").await;
+    test_app.post_login_as_test_debtor().await;
+    let feed: GetNotificationsJSONResponse =
+        test_app.get_notifications(&[]).await.json().await.unwrap();
+
+    let response = test_app
+        .post_notifications_read(&serde_json::json!({
+            "notification_ids": [&feed.notifications[0].notification_id],
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let feed: GetNotificationsJSONResponse =
+        test_app.get_notifications(&[]).await.json().await.unwrap();
+    assert_eq!(feed.unread_count, 1);
+    assert!(feed.notifications[0].read);
+
+    test_app
+        .post_notifications_read(&serde_json::json!({}))
+        .await;
+    let feed: GetNotificationsJSONResponse =
+        test_app.get_notifications(&[]).await.json().await.unwrap();
+    assert_eq!(feed.unread_count, 0);
+}
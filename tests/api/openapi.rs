@@ -0,0 +1,62 @@
+use crate::helpers::spawn_app;
+use debt_tracer::startup::routes;
+use std::collections::BTreeSet;
+
+#[tokio::test]
+async fn the_openapi_document_is_served() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_openapi().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let spec: serde_json::Value = response.json().await.unwrap();
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+    assert!(spec["components"]["schemas"]["GetDebtJSONResponse"].is_object());
+    assert!(spec["components"]["schemas"]["JsonLoginData"].is_object());
+}
+
+#[tokio::test]
+async fn the_swagger_ui_is_served() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_swagger_ui().await;
+
+    assert_eq!(200, response.status().as_u16());
+    assert!(response.text().await.unwrap().contains("swagger"));
+}
+
+#[tokio::test]
+async fn the_openapi_document_matches_the_registered_routes() {
+    let test_app = spawn_app().await;
+
+    let spec: serde_json::Value = test_app.get_openapi().await.json().await.unwrap();
+
+    let documented: BTreeSet<(String, String)> = spec["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, operations)| {
+            operations
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(move |method| (method.clone(), path.clone()))
+        })
+        .collect();
+    let registered: BTreeSet<(String, String)> = routes()
+        .into_iter()
+        .map(|(method, path)| (method.to_string(), path.to_string()))
+        .collect();
+    let undocumented: Vec<_> = registered.difference(&documented).collect();
+    let unregistered: Vec<_> = documented.difference(&registered).collect();
+    assert!(
+        undocumented.is_empty(),
+        "Routes missing from the OpenAPI document: {:?}",
+        undocumented
+    );
+    assert!(
+        unregistered.is_empty(),
+        "Documented routes that are not registered: {:?}",
+        unregistered
+    );
+}
@@ -0,0 +1,129 @@
+use crate::helpers::spawn_app;
+use debt_tracer::domain::UserRole;
+use debt_tracer::operations;
+use rust_decimal_macros::dec;
+use secrecy::Secret;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn a_created_user_can_log_in_without_confirming_their_email() {
+    let test_app = spawn_app().await;
+    let password = Uuid::new_v4().to_string();
+
+    let user_id = operations::create_user(
+        &test_app.db_pool,
+        "operator",
+        "operator@example.com",
+        Secret::new(password.clone()),
+        UserRole::Admin,
+    )
+    .await
+    .unwrap();
+
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": "operator",
+            "password": &password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let role = sqlx::query_scalar!("SELECT role FROM users WHERE user_id = $1", user_id)
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(role, "admin");
+}
+
+#[tokio::test]
+async fn create_user_rejects_a_short_password() {
+    let test_app = spawn_app().await;
+
+    let outcome = operations::create_user(
+        &test_app.db_pool,
+        "operator",
+        "operator@example.com",
+        Secret::new("too short".to_string()),
+        UserRole::User,
+    )
+    .await;
+
+    assert!(outcome.is_err());
+}
+
+#[tokio::test]
+async fn reset_password_replaces_the_password() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    operations::reset_password(
+        &test_app.db_pool,
+        &test_app.test_creditor.username,
+        Secret::new(new_password.clone()),
+    )
+    .await
+    .unwrap();
+
+    let response = test_app.post_login_as_test_creditor().await;
+    assert_eq!(401, response.status().as_u16());
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": &test_app.test_creditor.username,
+            "password": &new_password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn reset_password_rejects_unknown_users() {
+    let test_app = spawn_app().await;
+
+    let outcome = operations::reset_password(
+        &test_app.db_pool,
+        "nobody",
+        Secret::new(Uuid::new_v4().to_string()),
+    )
+    .await;
+
+    assert!(outcome.is_err());
+}
+
+#[tokio::test]
+async fn the_seeded_admin_has_no_password() {
+    let test_app = spawn_app().await;
+
+    let password_hash =
+        sqlx::query_scalar!("SELECT password_hash FROM users WHERE username = 'admin'")
+            .fetch_one(&test_app.db_pool)
+            .await
+            .unwrap();
+
+    assert_eq!(password_hash, None);
+}
+
+#[tokio::test]
+async fn balances_net_the_outstanding_debts_of_each_user() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_debt(10.0, "EUR", "Lunch").await;
+    test_app.post_debt(2.5, "EUR", "Coffee").await;
+    test_app.post_debt(4.0, "USD", "Taxi").await;
+
+    let balances = operations::recompute_balances(&test_app.db_pool)
+        .await
+        .unwrap();
+
+    let mut nets: Vec<_> = balances
+        .iter()
+        .map(|b| (b.user_id, b.currency.as_str(), b.net))
+        .collect();
+    nets.sort();
+    let mut expected = vec![
+        (test_app.test_creditor.user_id, "EUR", dec!(12.5)),
+        (test_app.test_creditor.user_id, "USD", dec!(4)),
+        (test_app.test_debtor.user_id, "EUR", dec!(-12.5)),
+        (test_app.test_debtor.user_id, "USD", dec!(-4)),
+    ];
+    expected.sort();
+    assert_eq!(nets, expected);
+}
@@ -0,0 +1,166 @@
+use crate::helpers::{spawn_app, spawn_app_with};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn requesting_a_reset_sends_an_email_with_a_token() {
+    let test_app = spawn_app().await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    let response = test_app
+        .post_password_reset(&test_app.test_creditor.email)
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn the_reset_link_points_at_the_frontend() {
+    let test_app =
+        spawn_app_with(|c| c.application.frontend_base_url = "https://app.example.com".into())
+            .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app
+        .post_password_reset(&test_app.test_creditor.email)
+        .await;
+
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert!(body["TextBody"]
+        .as_str()
+        .unwrap()
+        .contains("https://app.example.com/password/reset?token="));
+}
+
+#[tokio::test]
+async fn requesting_a_reset_for_an_unknown_email_returns_200_without_sending_an_email() {
+    let test_app = spawn_app().await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&test_app.email_server)
+        .await;
+
+    let response = test_app
+        .post_password_reset(&Uuid::new_v4().to_string())
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_reset_token_can_be_used_only_once() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app
+        .post_password_reset(&test_app.test_creditor.email)
+        .await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+
+    let body = serde_json::json!({
+        "token": &token,
+        "new_password": &new_password,
+        "new_password_check": &new_password,
+    });
+
+    let response = test_app.post_password_reset_confirm(&body).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = test_app.post_password_reset_confirm(&body).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn resetting_the_password_works_and_invalidates_sessions() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app.post_login_as_test_creditor().await;
+    assert_eq!(200, test_app.get_user().await.status().as_u16());
+
+    test_app
+        .post_password_reset(&test_app.test_creditor.email)
+        .await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+
+    let response = test_app
+        .post_password_reset_confirm(&serde_json::json!({
+            "token": &token,
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    // The existing session has been invalidated
+    assert_eq!(401, test_app.get_user().await.status().as_u16());
+
+    let response = test_app
+        .post_login(&serde_json::json!({
+            "username": &test_app.test_creditor.username,
+            "password": &new_password,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn an_expired_reset_token_is_rejected() {
+    let test_app = spawn_app().await;
+    let new_password = Uuid::new_v4().to_string();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app
+        .post_password_reset(&test_app.test_creditor.email)
+        .await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+
+    sqlx::query!("UPDATE password_reset_tokens SET expires_at = now() - interval '1 minute'")
+        .execute(&test_app.db_pool)
+        .await
+        .unwrap();
+
+    let response = test_app
+        .post_password_reset_confirm(&serde_json::json!({
+            "token": &token,
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+    assert_eq!(401, response.status().as_u16());
+}
@@ -0,0 +1,168 @@
+use crate::helpers::{spawn_app, TestApp};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn create_debt(test_app: &TestApp, due_date: Option<&str>) -> String {
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": test_app.test_debtor.user_id.to_string(),
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 42.0,
+            "currency": "EUR",
+            "description": "Concert tickets",
+            "due_date": due_date,
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["debt_id"].as_str().unwrap().to_string()
+}
+
+async fn set_reminder_frequency(test_app: &TestApp, user_id: Uuid, frequency: &str) {
+    sqlx::query!(
+        "UPDATE users SET reminder_frequency = $1 WHERE user_id = $2",
+        frequency,
+        user_id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn the_creditor_can_remind_the_debtor_once_a_day() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let debt_id = create_debt(&test_app, None).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    let response = test_app.post_remind_debtor(&debt_id).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = test_app.post_remind_debtor(&debt_id).await;
+    assert_eq!(429, response.status().as_u16());
+    assert!(response.headers().get("Retry-After").is_some());
+}
+
+#[tokio::test]
+async fn the_debtor_cannot_send_reminders_for_their_own_debt() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let debt_id = create_debt(&test_app, None).await;
+    test_app.post_login_as_test_debtor().await;
+
+    let response = test_app.post_remind_debtor(&debt_id).await;
+
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn create_debt_returns_a_400_for_an_invalid_due_date() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .post_debt_json(&serde_json::json!({
+            "debtor_id": test_app.test_debtor.user_id.to_string(),
+            "creditor_id": test_app.test_creditor.user_id.to_string(),
+            "amount": 42.0,
+            "currency": "EUR",
+            "description": "Concert tickets",
+            "due_date": "next friday",
+        }))
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_weekly_digest_is_sent_once_to_debtors_with_outstanding_debts() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    create_debt(&test_app, None).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app.send_all_due_reminders().await;
+    test_app.send_all_due_reminders().await;
+
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(body["To"], test_app.test_debtor.email);
+    assert!(body["TextBody"]
+        .as_str()
+        .unwrap()
+        .contains("Concert tickets"));
+}
+
+#[tokio::test]
+async fn overdue_reminders_are_only_sent_for_debts_past_their_due_date() {
+    let test_app = spawn_app().await;
+    set_reminder_frequency(&test_app, test_app.test_debtor.user_id, "when_overdue").await;
+    test_app.post_login_as_test_creditor().await;
+    create_debt(&test_app, Some("2999-01-01")).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app.send_all_due_reminders().await;
+    create_debt(&test_app, Some("2020-01-01")).await;
+    test_app.send_all_due_reminders().await;
+
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert!(body["TextBody"]
+        .as_str()
+        .unwrap()
+        .contains("overdue since 2020-01-01"));
+    assert!(!body["TextBody"].as_str().unwrap().contains("2999-01-01"));
+}
+
+#[tokio::test]
+async fn no_digest_is_sent_when_reminders_are_off() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_debtor().await;
+    let response = test_app
+        .patch_user(&serde_json::json!({ "reminder_frequency": "off" }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    test_app.post_login_as_test_creditor().await;
+    create_debt(&test_app, Some("2020-01-01")).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app.send_all_due_reminders().await;
+}
+
+#[tokio::test]
+async fn update_user_returns_a_400_for_an_unknown_reminder_frequency() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_debtor().await;
+
+    let response = test_app
+        .patch_user(&serde_json::json!({ "reminder_frequency": "hourly" }))
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+}
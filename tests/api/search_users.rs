@@ -0,0 +1,73 @@
+use crate::helpers::{spawn_app, TestUser};
+use debt_tracer::routes::users::search::SearchUserJSONResponse;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_search_users() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_users_search("admin").await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn an_empty_query_is_rejected() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_users_search("  ").await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn any_user_can_be_found_by_exact_username() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .get_users_search(&test_app.test_debtor.username)
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let users: Vec<SearchUserJSONResponse> = response.json().await.unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].user_id, test_app.test_debtor.user_id.to_string());
+}
+
+#[tokio::test]
+async fn prefix_search_does_not_reveal_strangers() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let stranger = TestUser::generate();
+    stranger.store(&test_app.db_pool).await;
+
+    let prefix = &stranger.username[..8];
+    let users: Vec<SearchUserJSONResponse> = test_app
+        .get_users_search(prefix)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert!(users.is_empty());
+}
+
+#[tokio::test]
+async fn prefix_search_finds_contacts() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let stranger = TestUser::generate();
+    stranger.store(&test_app.db_pool).await;
+
+    let prefix = &test_app.test_debtor.username[..8];
+    let users: Vec<SearchUserJSONResponse> = test_app
+        .get_users_search(prefix)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].username, test_app.test_debtor.username);
+}
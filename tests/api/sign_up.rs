@@ -0,0 +1,164 @@
+use crate::helpers::spawn_app;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn sign_up_body() -> serde_json::Value {
+    serde_json::json!({
+        "username": Uuid::new_v4().to_string(),
+        "password": Uuid::new_v4().to_string(),
+        "email": format!("{}@debt-tracer.com", Uuid::new_v4()),
+    })
+}
+
+#[tokio::test]
+async fn sign_up_persists_a_pending_user_and_sends_a_confirmation_email() {
+    let test_app = spawn_app().await;
+    let body = sign_up_body();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    let response = test_app.post_sign_up(&body).await;
+
+    assert_eq!(200, response.status().as_u16());
+
+    let saved = sqlx::query!(
+        "SELECT status FROM users WHERE username = $1",
+        body["username"].as_str().unwrap()
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch saved user.");
+
+    assert_eq!(saved.status, "pending_confirmation");
+}
+
+#[tokio::test]
+async fn sign_up_fails_if_the_confirmation_email_cannot_be_sent() {
+    let test_app = spawn_app().await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    let response = test_app.post_sign_up(&sign_up_body()).await;
+
+    assert_eq!(500, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn unconfirmed_users_cannot_log_in() {
+    let test_app = spawn_app().await;
+    let body = sign_up_body();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app.post_sign_up(&body).await;
+
+    let response = test_app.post_login(&body).await;
+
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn confirming_with_the_emailed_token_activates_the_user() {
+    let test_app = spawn_app().await;
+    let body = sign_up_body();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+
+    test_app.post_sign_up(&body).await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+
+    let response = test_app.get_sign_up_confirm(&token).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = test_app.post_login(&body).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // The token cannot be reused
+    let response = test_app.get_sign_up_confirm(&token).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn an_old_confirmation_link_does_not_re_enable_a_disabled_user() {
+    let test_app = spawn_app().await;
+    let body = sign_up_body();
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+    test_app.post_sign_up(&body).await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+    let user_id = sqlx::query_scalar!(
+        "SELECT user_id FROM users WHERE username = $1",
+        body["username"].as_str().unwrap()
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .unwrap();
+    test_app
+        .set_role(test_app.test_creditor.user_id, "admin")
+        .await;
+    test_app.post_login_as_test_creditor().await;
+    assert_eq!(
+        200,
+        test_app.post_disable_user(user_id).await.status().as_u16()
+    );
+
+    let response = test_app.get_sign_up_confirm(&token).await;
+
+    assert_eq!(401, response.status().as_u16());
+    let response = test_app.post_login(&body).await;
+    assert_eq!(403, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn an_expired_confirmation_token_is_rejected() {
+    let test_app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+    test_app.post_sign_up(&sign_up_body()).await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+    sqlx::query!("UPDATE user_tokens SET expires_at = now() - interval '1 minute'")
+        .execute(&test_app.db_pool)
+        .await
+        .unwrap();
+
+    let response = test_app.get_sign_up_confirm(&token).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn confirming_with_an_unknown_token_is_rejected() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_sign_up_confirm("not-a-real-token").await;
+
+    assert_eq!(401, response.status().as_u16());
+}
@@ -0,0 +1,118 @@
+use crate::helpers::{spawn_app, TestApp};
+use chrono::Datelike;
+use debt_tracer::debts::CreateDebtJSONResponse;
+use debt_tracer::routes::statements::get::StatementJSONResponse;
+use rust_decimal_macros::dec;
+
+async fn import_history(test_app: &TestApp) {
+    let csv = format!(
+        "counterparty,amount,currency,description,date,status\n\
+        {debtor},100,EUR,Rent share,2024-09-15,unpaid\n\
+        {debtor},20,EUR,Groceries,2024-10-05,unpaid\n\
+        {debtor},5,EUR,Coffee,2024-10-10,paid\n\
+        {debtor},7,USD,Book,2024-10-12,pending\n\
+        {debtor},50,EUR,Concert,2024-11-02,unpaid\n",
+        debtor = test_app.test_debtor.username
+    );
+    let response = test_app.post_debts_import(&csv, false).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_get_a_statement() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_statement(2024, 10, "json").await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn get_statement_returns_a_400_for_an_invalid_month() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_statement(2024, 13, "json").await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn the_statement_balances_each_counterparty_and_currency() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    import_history(&test_app).await;
+
+    let response = test_app.get_statement(2024, 10, "json").await;
+
+    assert_eq!(200, response.status().as_u16());
+    let statement: StatementJSONResponse = response.json().await.unwrap();
+    assert_eq!(statement.lines.len(), 2);
+    let eur = &statement.lines[0];
+    assert_eq!(eur.counterparty_name, test_app.test_debtor.username);
+    assert_eq!(eur.currency, "EUR");
+    assert_eq!(eur.opening_balance, dec!(100));
+    assert_eq!(eur.new_debts, dec!(25));
+    assert_eq!(eur.payments, dec!(5));
+    assert_eq!(eur.closing_balance, dec!(120));
+    let usd = &statement.lines[1];
+    assert_eq!(usd.currency, "USD");
+    assert_eq!(usd.opening_balance, dec!(0));
+    assert_eq!(usd.closing_balance, dec!(7));
+}
+
+#[tokio::test]
+async fn the_counterparty_sees_the_mirrored_statement() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    import_history(&test_app).await;
+    test_app.post_login_as_test_debtor().await;
+
+    let response = test_app.get_statement(2024, 10, "json").await;
+
+    let statement: StatementJSONResponse = response.json().await.unwrap();
+    assert_eq!(statement.lines[0].closing_balance, dec!(-120));
+}
+
+#[tokio::test]
+async fn debts_marked_as_paid_show_up_as_payments() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app.post_debt(30.0, "EUR", "Dinner").await;
+    let debt_id = response
+        .json::<CreateDebtJSONResponse>()
+        .await
+        .unwrap()
+        .debt_id;
+    let response = test_app.post_mark_debt_paid(&debt_id).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let today = chrono::Utc::now();
+    let response = test_app
+        .get_statement(today.year(), today.month(), "json")
+        .await;
+
+    let statement: StatementJSONResponse = response.json().await.unwrap();
+    let line = &statement.lines[0];
+    assert_eq!(line.new_debts, dec!(30));
+    assert_eq!(line.payments, dec!(30));
+    assert_eq!(line.closing_balance, dec!(0));
+}
+
+#[tokio::test]
+async fn the_statement_can_be_rendered_as_a_pdf() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    import_history(&test_app).await;
+
+    let response = test_app.get_statement(2024, 10, "pdf").await;
+
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(response.headers()["Content-Type"], "application/pdf");
+    let body = response.bytes().await.unwrap();
+    assert!(body.starts_with(b"%PDF-1.4"));
+    let text = String::from_utf8_lossy(&body);
+    assert!(text.contains("October 2024"));
+    // Long names are cut to fit the counterparty column.
+    assert!(text.contains(&test_app.test_debtor.username[..20]));
+}
@@ -0,0 +1,88 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::routes::reports::timeseries::TimeseriesJSONResponse;
+use rust_decimal_macros::dec;
+
+async fn import_history(test_app: &TestApp) {
+    let csv = format!(
+        "counterparty,amount,currency,description,date,status\n\
+        {debtor},100,EUR,Rent share,2024-09-15,unpaid\n\
+        {debtor},20,EUR,Groceries,2024-10-05,unpaid\n\
+        {debtor},-5.25,EUR,Coffee,2024-10-10,paid\n\
+        {debtor},7,USD,Book,2024-10-12,pending\n",
+        debtor = test_app.test_debtor.username
+    );
+    let response = test_app.post_debts_import(&csv, false).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_get_a_timeseries() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_timeseries(&[("bucket", "month")]).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn get_timeseries_returns_a_400_for_an_invalid_bucket() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_timeseries(&[("bucket", "year")]).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn the_timeseries_totals_debts_per_month() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    import_history(&test_app).await;
+
+    let response = test_app
+        .get_timeseries(&[("bucket", "month"), ("currency", "EUR")])
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let timeseries: TimeseriesJSONResponse = response.json().await.unwrap();
+    assert_eq!(timeseries.currency, "EUR");
+    assert_eq!(timeseries.points.len(), 2);
+    assert_eq!(timeseries.points[0].start, "2024-09-01");
+    assert_eq!(timeseries.points[0].net, dec!(100));
+    let october = &timeseries.points[1];
+    assert_eq!(october.start, "2024-10-01");
+    assert_eq!(october.lent, dec!(20));
+    assert_eq!(october.borrowed, dec!(5.25));
+    assert_eq!(october.net, dec!(14.75));
+}
+
+#[tokio::test]
+async fn weekly_buckets_start_on_monday() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    import_history(&test_app).await;
+
+    let response = test_app
+        .get_timeseries(&[("bucket", "week"), ("currency", "USD")])
+        .await;
+
+    let timeseries: TimeseriesJSONResponse = response.json().await.unwrap();
+    assert_eq!(timeseries.points.len(), 1);
+    assert_eq!(timeseries.points[0].start, "2024-10-07");
+    assert_eq!(timeseries.points[0].lent, dec!(7));
+}
+
+#[tokio::test]
+async fn the_timeseries_defaults_to_the_users_currency() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    import_history(&test_app).await;
+
+    let response = test_app.get_timeseries(&[("bucket", "day")]).await;
+
+    let timeseries: TimeseriesJSONResponse = response.json().await.unwrap();
+    assert_eq!(timeseries.currency, "USD");
+    assert_eq!(timeseries.points.len(), 1);
+    assert_eq!(timeseries.points[0].start, "2024-10-12");
+}
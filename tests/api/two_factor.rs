@@ -0,0 +1,117 @@
+use crate::helpers::{spawn_app, TestApp};
+use debt_tracer::routes::two_factor::confirm::ConfirmTotpJSONResponse;
+use debt_tracer::routes::two_factor::post::EnrollTotpJSONResponse;
+use std::time::{SystemTime, UNIX_EPOCH};
+use totp_rs::TOTP;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Enable TOTP for the test creditor, returning the generator and the recovery codes.
+async fn enable_two_factor(test_app: &TestApp) -> (TOTP, Vec<String>) {
+    test_app.post_login_as_test_creditor().await;
+    let enrollment: EnrollTotpJSONResponse =
+        test_app.post_enroll_totp().await.json().await.unwrap();
+    let totp = TOTP::from_url(&enrollment.otpauth_uri).unwrap();
+
+    let response = test_app.post_confirm_totp(&totp.generate(now())).await;
+    assert_eq!(200, response.status().as_u16());
+    let confirmation: ConfirmTotpJSONResponse = response.json().await.unwrap();
+
+    (totp, confirmation.recovery_codes)
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_enroll() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.post_enroll_totp().await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn enrollment_returns_an_otpauth_uri_for_the_secret() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.post_enroll_totp().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let enrollment: EnrollTotpJSONResponse = response.json().await.unwrap();
+    let totp = TOTP::from_url(&enrollment.otpauth_uri).unwrap();
+    assert_eq!(totp.get_secret_base32(), enrollment.secret);
+    assert_eq!(totp.issuer.as_deref(), Some("debt-tracer"));
+}
+
+#[tokio::test]
+async fn enrollment_is_not_confirmed_with_a_wrong_code() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    test_app.post_enroll_totp().await;
+
+    let response = test_app.post_confirm_totp("000000x").await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn confirming_enrollment_returns_recovery_codes_and_prevents_reenrollment() {
+    let test_app = spawn_app().await;
+
+    let (_, recovery_codes) = enable_two_factor(&test_app).await;
+
+    assert_eq!(recovery_codes.len(), 10);
+    let response = test_app.post_enroll_totp().await;
+    assert_eq!(409, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn login_requires_a_second_factor_once_enabled() {
+    let test_app = spawn_app().await;
+    let (totp, _) = enable_two_factor(&test_app).await;
+
+    let response = test_app.post_login_as_test_creditor().await;
+    assert_eq!(202, response.status().as_u16());
+
+    // The password alone does not grant access
+    assert_eq!(401, test_app.get_user().await.status().as_u16());
+
+    let response = test_app.post_login_two_factor("123456x").await;
+    assert_eq!(401, response.status().as_u16());
+    assert_eq!(401, test_app.get_user().await.status().as_u16());
+
+    // The code used during enrollment cannot be replayed: use the next one
+    let response = test_app
+        .post_login_two_factor(&totp.generate(now() + 30))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(200, test_app.get_user().await.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_recovery_code_can_be_used_only_once() {
+    let test_app = spawn_app().await;
+    let (_, recovery_codes) = enable_two_factor(&test_app).await;
+
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app.post_login_two_factor(&recovery_codes[0]).await;
+    assert_eq!(200, response.status().as_u16());
+
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app.post_login_two_factor(&recovery_codes[0]).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn a_second_factor_without_a_pending_login_is_rejected() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.post_login_two_factor("123456").await;
+
+    assert_eq!(401, response.status().as_u16());
+}
@@ -0,0 +1,151 @@
+use crate::helpers::spawn_app;
+use debt_tracer::routes::users::get::GetUserJSONResponse;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn get_user_returns_the_stored_profile() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app.get_user().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let user: GetUserJSONResponse = response.json().await.unwrap();
+    assert_eq!(user.user_id, test_app.test_creditor.user_id.to_string());
+    assert_eq!(user.email, test_app.test_creditor.email);
+    assert_eq!(user.display_name, None);
+    assert_eq!(user.default_currency, "USD");
+    assert_eq!(user.timezone, "UTC");
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_update_your_profile() {
+    let test_app = spawn_app().await;
+
+    let response = test_app
+        .patch_user(&serde_json::json!({ "display_name": "Alice" }))
+        .await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn profile_fields_are_updated() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .patch_user(&serde_json::json!({
+            "display_name": "Alice",
+            "default_currency": "eur",
+            "timezone": "Europe/Berlin",
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let user: GetUserJSONResponse = test_app.get_user().await.json().await.unwrap();
+    assert_eq!(user.display_name.as_deref(), Some("Alice"));
+    assert_eq!(user.default_currency, "EUR");
+    assert_eq!(user.timezone, "Europe/Berlin");
+}
+
+#[tokio::test]
+async fn invalid_profile_fields_are_rejected() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let test_cases = vec![
+        (
+            serde_json::json!({ "display_name": "  " }),
+            "empty display name",
+        ),
+        (
+            serde_json::json!({ "default_currency": "XYZ" }),
+            "unknown currency",
+        ),
+        (
+            serde_json::json!({ "timezone": "Mars/Olympus_Mons" }),
+            "unknown timezone",
+        ),
+        (
+            serde_json::json!({ "email": "not-an-email" }),
+            "invalid email",
+        ),
+    ];
+
+    for (body, description) in test_cases {
+        let response = test_app.patch_user(&body).await;
+
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request when the payload had an {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_username_cannot_be_changed() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let response = test_app
+        .patch_user(&serde_json::json!({ "username": "renamed-creditor" }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let user: GetUserJSONResponse = response.json().await.unwrap();
+    assert_eq!(user.username, test_app.test_creditor.username);
+}
+
+#[tokio::test]
+async fn a_new_email_is_only_applied_once_confirmed() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&test_app.email_server)
+        .await;
+
+    let response = test_app
+        .patch_user(&serde_json::json!({ "email": "new-address@debt-tracer.com" }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let user: GetUserJSONResponse = test_app.get_user().await.json().await.unwrap();
+    assert_eq!(user.email, test_app.test_creditor.email);
+
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(body["To"], "new-address@debt-tracer.com");
+    let token = test_app.get_token_from_email(email_request);
+    let response = test_app.get_email_change_confirm(&token).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let user: GetUserJSONResponse = test_app.get_user().await.json().await.unwrap();
+    assert_eq!(user.email, "new-address@debt-tracer.com");
+}
+
+#[tokio::test]
+async fn an_email_change_token_can_only_be_used_once() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&test_app.email_server)
+        .await;
+    test_app
+        .patch_user(&serde_json::json!({ "email": "new-address@debt-tracer.com" }))
+        .await;
+    let email_request = &test_app.email_server.received_requests().await.unwrap()[0];
+    let token = test_app.get_token_from_email(email_request);
+    test_app.get_email_change_confirm(&token).await;
+
+    let response = test_app.get_email_change_confirm(&token).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
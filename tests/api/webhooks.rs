@@ -0,0 +1,271 @@
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+use debt_tracer::routes::webhooks::deliveries::WebhookDeliveryJSONResponse;
+use debt_tracer::routes::webhooks::get::GetWebhookJSONResponse;
+use debt_tracer::routes::webhooks::post::CreateWebhookJSONResponse;
+use debt_tracer::webhook_delivery_worker::{
+    sign_payload, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn register_webhook(test_app: &TestApp, receiver: &MockServer) -> CreateWebhookJSONResponse {
+    let response = test_app
+        .post_webhook(&serde_json::json!({
+            "url": format!("{}/hooks/debts", receiver.uri()),
+            "event_types": ["debt_created"],
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    response.json().await.unwrap()
+}
+
+async fn deliveries(test_app: &TestApp, webhook_id: &str) -> Vec<WebhookDeliveryJSONResponse> {
+    test_app
+        .get_webhook_deliveries(webhook_id)
+        .await
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_webhooks() {
+    let test_app = spawn_app().await;
+
+    let response = test_app.get_webhooks().await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn registered_webhooks_are_listed_without_their_secret() {
+    let test_app = spawn_app().await;
+    let receiver = MockServer::start().await;
+    test_app.post_login_as_test_creditor().await;
+
+    let webhook = register_webhook(&test_app, &receiver).await;
+    let response = test_app.get_webhooks().await;
+
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(!body.to_string().contains(&webhook.secret));
+    let webhooks: Vec<GetWebhookJSONResponse> = serde_json::from_value(body).unwrap();
+    assert_eq!(webhooks.len(), 1);
+    assert_eq!(webhooks[0].webhook_id, webhook.webhook_id);
+    assert_eq!(webhooks[0].event_types, vec!["debt_created"]);
+}
+
+#[tokio::test]
+async fn create_webhook_returns_a_400_for_invalid_data() {
+    let test_app = spawn_app().await;
+    test_app.post_login_as_test_creditor().await;
+    let test_cases = vec![
+        (
+            serde_json::json!({"url": "not a url", "event_types": ["debt_created"]}),
+            "invalid url",
+        ),
+        (
+            serde_json::json!({"url": "ftp://example.com/hook", "event_types": ["debt_created"]}),
+            "unsupported scheme",
+        ),
+        (
+            serde_json::json!({"url": "https://example.com/hook", "event_types": []}),
+            "no event types",
+        ),
+        (
+            serde_json::json!({"url": "https://example.com/hook", "event_types": ["debt_forgotten"]}),
+            "unknown event type",
+        ),
+    ];
+
+    for (body, description) in test_cases {
+        let response = test_app.post_webhook(&body).await;
+
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request when the payload had {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_new_debt_is_delivered_as_a_signed_payload() {
+    let test_app = spawn_app().await;
+    let receiver = MockServer::start().await;
+    test_app.post_login_as_test_creditor().await;
+    let webhook = register_webhook(&test_app, &receiver).await;
+
+    Mock::given(path("/hooks/debts"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&receiver)
+        .await;
+
+    test_app.post_debt(10.0, "USD", "Coffee").await;
+    test_app.dispatch_all_pending_webhooks().await;
+
+    let request = &receiver.received_requests().await.unwrap()[0];
+    let body = std::str::from_utf8(&request.body).unwrap();
+    assert_eq!(
+        request.headers[SIGNATURE_HEADER].to_str().unwrap(),
+        sign_payload(&webhook.secret, body)
+    );
+    assert_eq!(request.headers[EVENT_HEADER], "debt_created");
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(payload["event_type"], "debt_created");
+    assert_eq!(payload["data"]["description"], "Coffee");
+
+    let deliveries = deliveries(&test_app, &webhook.webhook_id).await;
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(
+        request.headers[DELIVERY_HEADER].to_str().unwrap(),
+        deliveries[0].delivery_id
+    );
+    assert_eq!(deliveries[0].status, "delivered");
+    assert_eq!(deliveries[0].response_status, Some(200));
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_retried_later() {
+    let test_app = spawn_app().await;
+    let receiver = MockServer::start().await;
+    test_app.post_login_as_test_creditor().await;
+    let webhook = register_webhook(&test_app, &receiver).await;
+
+    Mock::given(path("/hooks/debts"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&receiver)
+        .await;
+    Mock::given(path("/hooks/debts"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&receiver)
+        .await;
+
+    test_app.post_debt(10.0, "USD", "Coffee").await;
+    test_app.dispatch_all_pending_webhooks().await;
+
+    // The retry is not due yet.
+    test_app.dispatch_all_pending_webhooks().await;
+    let delivery = &deliveries(&test_app, &webhook.webhook_id).await[0];
+    assert_eq!(delivery.status, "pending");
+    assert_eq!(delivery.attempts, 1);
+    assert_eq!(delivery.response_status, Some(500));
+    assert!(delivery.next_attempt_at > delivery.last_attempt_at.clone().unwrap());
+
+    sqlx::query!("UPDATE webhook_deliveries SET next_attempt_at = now()")
+        .execute(&test_app.db_pool)
+        .await
+        .unwrap();
+    test_app.dispatch_all_pending_webhooks().await;
+
+    let delivery = &deliveries(&test_app, &webhook.webhook_id).await[0];
+    assert_eq!(delivery.status, "delivered");
+    assert_eq!(delivery.attempts, 2);
+}
+
+#[tokio::test]
+async fn deliveries_are_given_up_after_the_last_attempt() {
+    let mut test_app = spawn_app().await;
+    test_app.webhook_settings.max_attempts = 1;
+    let receiver = MockServer::start().await;
+    test_app.post_login_as_test_creditor().await;
+    let webhook = register_webhook(&test_app, &receiver).await;
+
+    Mock::given(path("/hooks/debts"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&receiver)
+        .await;
+
+    test_app.post_debt(10.0, "USD", "Coffee").await;
+    test_app.dispatch_all_pending_webhooks().await;
+    sqlx::query!("UPDATE webhook_deliveries SET next_attempt_at = now()")
+        .execute(&test_app.db_pool)
+        .await
+        .unwrap();
+    test_app.dispatch_all_pending_webhooks().await;
+
+    let delivery = &deliveries(&test_app, &webhook.webhook_id).await[0];
+    assert_eq!(delivery.status, "failed");
+}
+
+#[tokio::test]
+async fn webhooks_are_private_to_their_owner() {
+    let test_app = spawn_app().await;
+    let receiver = MockServer::start().await;
+    test_app.post_login_as_test_creditor().await;
+    let webhook = register_webhook(&test_app, &receiver).await;
+
+    test_app.post_login_as_test_debtor().await;
+    let response = test_app.get_webhook_deliveries(&webhook.webhook_id).await;
+    assert_eq!(404, response.status().as_u16());
+    let response = test_app.delete_webhook(&webhook.webhook_id).await;
+    assert_eq!(404, response.status().as_u16());
+
+    test_app.post_login_as_test_creditor().await;
+    let response = test_app.delete_webhook(&webhook.webhook_id).await;
+    assert_eq!(200, response.status().as_u16());
+    let response = test_app.get_webhook_deliveries(&webhook.webhook_id).await;
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn webhook_urls_must_not_be_ip_addresses() {
+    let test_app = spawn_app_with(|c| c.webhooks.allow_private_targets = false).await;
+    test_app.post_login_as_test_creditor().await;
+
+    for url in [
+        "http://127.0.0.1:8080/hooks",
+        "http://169.254.169.254/latest/meta-data",
+        "https://[::1]/hooks",
+    ] {
+        let response = test_app
+            .post_webhook(&serde_json::json!({
+                "url": url,
+                "event_types": ["debt_created"],
+            }))
+            .await;
+
+        assert_eq!(400, response.status().as_u16(), "{}", url);
+    }
+}
+
+#[tokio::test]
+async fn deliveries_to_private_addresses_are_refused() {
+    let mut test_app = spawn_app().await;
+    let receiver = MockServer::start().await;
+    test_app.post_login_as_test_creditor().await;
+    let by_ip = register_webhook(&test_app, &receiver).await;
+    let by_name = test_app
+        .post_webhook(&serde_json::json!({
+            "url": format!("http://localhost:{}/hooks/debts", receiver.address().port()),
+            "event_types": ["debt_created"],
+        }))
+        .await
+        .json::<CreateWebhookJSONResponse>()
+        .await
+        .unwrap();
+    test_app.webhook_settings.allow_private_targets = false;
+
+    Mock::given(path("/hooks/debts"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&receiver)
+        .await;
+
+    test_app.post_debt(10.0, "USD", "Coffee").await;
+    test_app.dispatch_all_pending_webhooks().await;
+
+    for webhook in [by_ip, by_name] {
+        let delivery = &deliveries(&test_app, &webhook.webhook_id).await[0];
+        assert_eq!(delivery.status, "pending");
+        assert_eq!(delivery.response_status, None);
+        assert!(delivery.last_error.is_some());
+    }
+}